@@ -0,0 +1,57 @@
+// builders for the trees tests check, resolve and compile; every node is at 0:0 unless moved with `at`
+use super::{ * };
+use crate::{ lexer::{ Span } };
+
+// `node` placed at `start`, for tests that look things up by where they are
+pub(crate) fn at<T>(start: usize, node: Spanned<T>) -> Spanned<T> {
+    Spanned::from_span(Span::new(start, start), node.node)
+}
+
+pub(crate) fn ident(name: &'static str) -> Expr {
+    Spanned::new(0, 0, ExprKind::Identifier(name))
+}
+
+pub(crate) fn int(value: u64) -> Expr {
+    Spanned::new(0, 0, ExprKind::Literal(Literal::Int(value, None)))
+}
+
+pub(crate) fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+    Spanned::new(0, 0, ExprKind::Binary(Box::new(lhs), op, Box::new(rhs)))
+}
+
+pub(crate) fn deref(pointer: Expr) -> Expr {
+    Spanned::new(0, 0, ExprKind::Unary(UnaryOp::Deref, Box::new(pointer)))
+}
+
+pub(crate) fn call(callee: &'static str, args: Vec<Expr>) -> Expr {
+    Spanned::new(0, 0, ExprKind::Call(Box::new(ident(callee)), args))
+}
+
+pub(crate) fn stmt(kind: StmtKind) -> Stmt {
+    Spanned::new(0, 0, kind)
+}
+
+pub(crate) fn builtin(ty: SimpleType) -> TypeExpr {
+    Spanned::new(0, 0, TypeExprKind::Builtin(ty))
+}
+
+pub(crate) fn i32_type() -> TypeExpr {
+    builtin(SimpleType::signed(SignedIntegerTypes::Int32))
+}
+
+pub(crate) fn named(name: &'static str) -> TypeExpr {
+    instance(name, vec![])
+}
+
+// `Name<Args...>`
+pub(crate) fn instance(name: &'static str, args: Vec<TypeExpr>) -> TypeExpr {
+    Spanned::new(0, 0, TypeExprKind::Named(name, args))
+}
+
+pub(crate) fn param(name: &'static str, ty: TypeExpr) -> Param {
+    Param { name: Spanned::new(0, 0, name), ty }
+}
+
+pub(crate) fn function(name: &'static str, params: Vec<Param>, ret: Option<TypeExpr>, body: Block) -> Item {
+    Item::Fn(FnDecl { is_pub: false, name: Spanned::new(0, 0, name), generics: vec![], params, ret, body: Some(body) })
+}
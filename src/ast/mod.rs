@@ -0,0 +1,318 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use crate::{ lexer::{ Spanned }, types::{ *, half::{ F16 } } };
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+pub type Expr = Spanned<ExprKind>;
+pub type TypeExpr = Spanned<TypeExprKind>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IntegerSuffix {
+    Signed(SignedIntegerTypes),
+    Unsigned(UnsignedIntegerTypes),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Literal {
+    Int(u64, Option<IntegerSuffix>),
+    Float(f64, Option<FloatingPointTypes>),
+    Bool(bool),
+    Char(char),
+    String(&'static str),
+    Null,
+}
+
+impl Literal {
+    // parses the text of a float literal such as `1_000.5` or `0.1f16`, rounding it
+    // straight from the decimal text to the precision of its suffix
+    pub fn parse_float(text: &str) -> Option<Self> {
+        let text = text.replace('_', "");
+        let suffixes = [ ("f16", FloatingPointTypes::FP16), ("f32", FloatingPointTypes::FP32), ("f64", FloatingPointTypes::FP64) ];
+        let (digits, suffix) = suffixes.iter()
+            .find_map(|(name, ty)| text.strip_suffix(name).map(|digits| (digits, Some(*ty))))
+            .unwrap_or((&text, None));
+
+        let value = match suffix {
+            Some(FloatingPointTypes::FP16) => digits.parse::<F16>().ok()?.to_f64(),
+            Some(FloatingPointTypes::FP32) => digits.parse::<f32>().ok()? as f64,
+            _ => digits.parse::<f64>().ok()?,
+        };
+
+        Some(Self::Float(value, suffix))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UnaryOp {
+    Negate,                 // -
+    Not,                    // !
+    Deref,                  // *
+    AddressOf,              // &
+    AddressOfMut,           // &mut
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BinaryOp {
+    Add,                    // +
+    Sub,                    // -
+    Mul,                    // *
+    Div,                    // /
+    Mod,                    // %
+    BitAnd,                 // &
+    BitOr,                  // |
+
+    Equals,                 // ==
+    NotEquals,              // !=
+    Smaller,                // <
+    Greater,                // >
+    SmallerEquals,          // <=
+    GreaterEquals,          // >=
+
+    And,                    // &&
+    Or,                     // ||
+}
+
+impl BinaryOp {
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, Self::Add | Self::Sub | Self::Mul | Self::Div | Self::Mod | Self::BitAnd | Self::BitOr)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, Self::Equals | Self::NotEquals | Self::Smaller | Self::Greater | Self::SmallerEquals | Self::GreaterEquals)
+    }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Self::And | Self::Or)
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Sub => write!(f, "-"),
+            Self::Mul => write!(f, "*"),
+            Self::Div => write!(f, "/"),
+            Self::Mod => write!(f, "%"),
+            Self::BitAnd => write!(f, "&"),
+            Self::BitOr => write!(f, "|"),
+            Self::Equals => write!(f, "=="),
+            Self::NotEquals => write!(f, "!="),
+            Self::Smaller => write!(f, "<"),
+            Self::Greater => write!(f, ">"),
+            Self::SmallerEquals => write!(f, "<="),
+            Self::GreaterEquals => write!(f, ">="),
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SizeofOperand {
+    Type(TypeExpr),         // sizeof(T)
+    Expr(Box<Expr>),        // sizeof expr
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+    Identifier(&'static str),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Cast(Box<Expr>, TypeExpr),
+    Sizeof(SizeofOperand),
+    Field(Box<Expr>, &'static str),
+    // `Enum.Variant(payload...)`
+    Variant(&'static str, &'static str, Vec<Expr>),
+    Match(Box<Expr>, Vec<MatchArm>),
+    Call(Box<Expr>, Vec<Expr>),
+    // `receiver.method(args...)`
+    MethodCall(Box<Expr>, Spanned<&'static str>, Vec<Expr>),
+    Closure(Box<ClosureExpr>),
+    // `pointer?`, a nullable pointer asserted to be non-null
+    Unwrap(Box<Expr>),
+    // `new T`, a `*mut T` to a fresh heap allocation
+    New(TypeExpr),
+}
+
+pub type Pattern = Spanned<PatternKind>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    Wildcard,                                   // _
+    Binding(&'static str),                      // name
+    Literal(Literal),                           // 1, true, 'c'
+    Variant(&'static str, Vec<Pattern>),        // Variant(p1, p2)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExprKind {
+    Builtin(SimpleType),
+    // `Name` or `Name<Args...>`
+    Named(&'static str, Vec<TypeExpr>),
+    SelfType,
+    Dyn(&'static str),
+    // `*T` or `*mut T`; the flag applies to the outermost level
+    Pointer(usize, bool, Box<TypeExpr>),
+    // `?*T`
+    Nullable(Box<TypeExpr>),
+    // `None` is the runtime sized `[?]T`
+    Array(Option<Box<Expr>>, Box<TypeExpr>),
+    // `fn(params...) -> ret`, where a trailing `...` parameter makes it variadic
+    Function(Vec<TypeExpr>, Option<Box<TypeExpr>>, CallingConvention),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub is_pub: bool,
+    pub name: &'static str,
+    pub ty: TypeExpr,
+}
+
+// `Trait` or `Trait<Args...>`, as used in bounds and impls
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitRef {
+    pub name: Spanned<&'static str>,
+    pub args: Vec<TypeExpr>,
+}
+
+// `T: Bound1 + Bound2`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericParam {
+    pub name: Spanned<&'static str>,
+    pub bounds: Vec<TraitRef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub generics: Vec<GenericParam>,
+    pub fields: Vec<StructField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariantDecl {
+    pub name: Spanned<&'static str>,
+    pub payload: Vec<TypeExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub generics: Vec<GenericParam>,
+    pub variants: Vec<EnumVariantDecl>,
+}
+
+pub type Stmt = Spanned<StmtKind>;
+pub type Block = Vec<Stmt>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    // `let name: T = value;` or `let mut ...`
+    Let(&'static str, bool, Option<TypeExpr>, Expr),
+    // `place = value;`
+    Assign(Expr, Expr),
+    Expr(Expr),
+    Return(Option<Expr>),
+    If(Expr, Block, Option<Block>),
+    While(Expr, Block),
+    // `delete pointer;`, frees what `new` allocated
+    Delete(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: Spanned<&'static str>,
+    pub ty: TypeExpr,
+}
+
+// `fn[&total](x: i32) -> i32 { ... }`, every other captured variable is copied into the closure
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureExpr {
+    pub by_ref: Vec<Spanned<&'static str>>,
+    pub params: Vec<Param>,
+    pub ret: Option<TypeExpr>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub generics: Vec<GenericParam>,
+    pub params: Vec<Param>,
+    pub ret: Option<TypeExpr>,
+    // `None` for `abstract` trait methods
+    pub body: Option<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitMethod {
+    pub is_abstract: bool,
+    pub decl: FnDecl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub generics: Vec<GenericParam>,
+    pub methods: Vec<TraitMethod>,
+}
+
+// `impl Trait for Type { ... }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImplDecl {
+    pub trait_name: Spanned<&'static str>,
+    pub trait_args: Vec<TypeExpr>,
+    pub for_type: TypeExpr,
+    pub methods: Vec<FnDecl>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAliasDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub ty: TypeExpr,
+}
+
+// `import Vec, Map from "collections/map";`, where the path is relative to the project root
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    pub names: Vec<Spanned<&'static str>>,
+    pub path: Spanned<&'static str>,
+}
+
+// `static NAME: Type = value;`, whose value is computed at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticDecl {
+    pub is_pub: bool,
+    pub name: Spanned<&'static str>,
+    pub ty: TypeExpr,
+    pub value: Expr,
+}
+
+// a top-level declaration of a module
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Import(ImportDecl),
+    Struct(StructDecl),
+    Enum(EnumDecl),
+    Trait(TraitDecl),
+    Impl(ImplDecl),
+    Fn(FnDecl),
+    ExternFn(FnDecl),
+    TypeAlias(TypeAliasDecl),
+    Static(StaticDecl),
+}
@@ -0,0 +1,713 @@
+use std::{ collections::{ HashMap }, str::{ FromStr } };
+
+use super::{ * };
+use crate::{ error::{ * }, lexer::{ Span } };
+
+type Assembled<T> = std::result::Result<T, Error>;
+
+// the text form of a program, which is what the disassembler writes:
+//
+//   constants
+//       #0    -1i32
+//   fn abs(x: i32) -> i32
+//       local $tmp: bool
+//       0000  load 0              @20:21      ; comments run to the end of the line
+//   L0:
+//       0001  jump_if_false L0
+//
+// offsets and spans may be left out, an instruction without a span gets that of its text. operands
+// may also name what they refer to: a local or function by its name, a jump target by its label and
+// a constant by its value, which adds it to the pool
+pub fn assemble(text: &str) -> Assembled<Program> {
+    let mut assembler = Assembler::default();
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let tokens = tokenize(line, offset)?;
+
+        assembler.line(Line { tokens: &tokens, at: 0, end: offset + line.trim_end().len() })?;
+        offset += line.len();
+    }
+
+    assembler.finish()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind<'a> {
+    Word(&'a str),
+    String(String),
+    Char(char),
+    Punct(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token<'a> {
+    kind: TokenKind<'a>,
+    span: Span,
+}
+
+const PUNCTUATION: &str = "()[],:@#";
+
+// the type suffixes a constant's value ends in
+const SUFFIXES: [&str; 11] = [ "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f16", "f32", "f64" ];
+
+fn tokenize(line: &str, offset: usize) -> Assembled<Vec<Token<'_>>> {
+    let mut tokens = vec![];
+    let mut at = 0;
+
+    while let Some(c) = line[at ..].chars().next() {
+        let start = at;
+
+        at += c.len_utf8();
+
+        let kind = match c {
+            ';' => break,
+            _ if c.is_whitespace() => continue,
+            _ if PUNCTUATION.contains(c) => TokenKind::Punct(c),
+
+            '"' | '\'' => {
+                let (value, len) = unquote(&line[start ..], offset + start)?;
+                let mut chars = value.chars();
+
+                at = start + len;
+
+                match (c, chars.next(), chars.next()) {
+                    ('"', _, _) => TokenKind::String(value),
+                    (_, Some(c), None) => TokenKind::Char(c),
+                    _ => return Err(error(Span::new(offset + start, offset + at - 1), "a char literal holds exactly one character")),
+                }
+            },
+
+            _ => {
+                at = line[start ..].find(|c: char| c.is_whitespace() || PUNCTUATION.contains(c) || ";\"'".contains(c)).map_or(line.len(), |len| start + len);
+
+                TokenKind::Word(&line[start .. at])
+            },
+        };
+
+        let last = line[.. at].char_indices().next_back().map_or(start, |(last, _)| last);
+
+        tokens.push(Token { kind, span: Span::new(offset + start, offset + last) });
+    }
+
+    Ok(tokens)
+}
+
+// the value of the quoted literal `text` starts with, escaped the way `{:?}` escapes, and its length
+fn unquote(text: &str, offset: usize) -> Assembled<(String, usize)> {
+    let quote = text.chars().next().expect("the literal starts with its quote");
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((at, c)) = chars.next() {
+        if c == quote {
+            return Ok((value, at + 1));
+        }
+
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next().map(|(_, c)| c) {
+            Some('n') => Some('\n'),
+            Some('r') => Some('\r'),
+            Some('t') => Some('\t'),
+            Some('0') => Some('\0'),
+            Some(c @ ('\\' | '"' | '\'')) => Some(c),
+
+            Some('u') => match (chars.next(), text[at ..].find('}')) {
+                (Some((_, '{')), Some(close)) => {
+                    let digits = &text[at + 3 .. at + close];
+
+                    while chars.next().is_some_and(|(next, _)| next < at + close) {}
+
+                    u32::from_str_radix(digits, 16).ok().and_then(char::from_u32)
+                },
+
+                _ => None,
+            },
+
+            _ => None,
+        };
+
+        match escaped {
+            Some(c) => value.push(c),
+            None => return Err(error(Span::new(offset + at, offset + at + 1), "invalid escape sequence")),
+        }
+    }
+
+    Err(error(Span::new(offset, offset + text.trim_end().len() - 1), "unterminated literal"))
+}
+
+fn error(span: Span, message: &str) -> Error {
+    Error::from_span(span, ErrorType::ParseError, message)
+}
+
+fn value_type_named(name: &str) -> Option<ValueType> {
+    let ty = match name {
+        "i8" => ValueType::Int(SignedIntegerTypes::Int8),
+        "i16" => ValueType::Int(SignedIntegerTypes::Int16),
+        "i32" => ValueType::Int(SignedIntegerTypes::Int32),
+        "i64" => ValueType::Int(SignedIntegerTypes::Int64),
+        "u8" => ValueType::Uint(UnsignedIntegerTypes::Uint8),
+        "u16" => ValueType::Uint(UnsignedIntegerTypes::Uint16),
+        "u32" => ValueType::Uint(UnsignedIntegerTypes::Uint32),
+        "u64" => ValueType::Uint(UnsignedIntegerTypes::Uint64),
+        "f16" => ValueType::Float(FloatingPointTypes::FP16),
+        "f32" => ValueType::Float(FloatingPointTypes::FP32),
+        "f64" => ValueType::Float(FloatingPointTypes::FP64),
+        "bool" => ValueType::Bool,
+        "char" => ValueType::Char,
+        "ptr" => ValueType::Pointer,
+        "fn" => ValueType::Function,
+        _ => return None,
+    };
+
+    Some(ty)
+}
+
+fn is_number(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_digit())
+}
+
+// the tokens of one line, read front to back
+struct Line<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    at: usize,
+    // where the line ends, which is where a missing token is reported
+    end: usize,
+}
+
+impl<'a> Line<'_, 'a> {
+    fn peek(&self) -> Option<&TokenKind<'a>> {
+        self.tokens.get(self.at).map(|token| &token.kind)
+    }
+
+    fn peek_word(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self, what: &str) -> Assembled<Token<'a>> {
+        match self.tokens.get(self.at) {
+            Some(token) => {
+                self.at += 1;
+
+                Ok(token.clone())
+            },
+
+            None => Err(error(Span::new(self.end, self.end), &format!("expected {}, found the end of the line", what))),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&TokenKind::Punct(c));
+
+        self.at += found as usize;
+        found
+    }
+
+    fn punct(&mut self, c: char) -> Assembled<()> {
+        let token = self.next(&format!("`{}`", c))?;
+
+        match token.kind {
+            TokenKind::Punct(found) if found == c => Ok(()),
+            _ => Err(unexpected(&token, &format!("`{}`", c))),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Assembled<(&'a str, Span)> {
+        let token = self.next(what)?;
+
+        match token.kind {
+            TokenKind::Word(word) => Ok((word, token.span)),
+            _ => Err(unexpected(&token, what)),
+        }
+    }
+
+    // a name as the disassembler writes it, plain or quoted
+    fn name(&mut self, what: &str) -> Assembled<(&'static str, Span)> {
+        let token = self.next(what)?;
+
+        match token.kind {
+            TokenKind::Word(word) => Ok((Box::leak(word.to_owned().into_boxed_str()), token.span)),
+            TokenKind::String(name) => Ok((Box::leak(name.into_boxed_str()), token.span)),
+            _ => Err(unexpected(&token, what)),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, what: &str) -> Assembled<T> {
+        let (word, span) = self.word(what)?;
+
+        match is_number(word) {
+            true => word.parse().map_err(|_| error(span, &format!("{} `{}` is out of range", what, word))),
+            false => Err(error(span, &format!("expected {}, found `{}`", what, word))),
+        }
+    }
+
+    fn value_type(&mut self) -> Assembled<(ValueType, Span)> {
+        let (word, span) = self.word("a type")?;
+
+        value_type_named(word).map(|ty| (ty, span)).ok_or_else(|| error(span, &format!("unknown type `{}`", word)))
+    }
+
+    // `-> type`, or nothing for a function that returns nothing
+    fn return_type(&mut self) -> Assembled<Option<ValueType>> {
+        match self.peek_word() {
+            Some("->") => {
+                self.at += 1;
+
+                Ok(Some(self.value_type()?.0))
+            },
+
+            _ => Ok(None),
+        }
+    }
+
+    fn numeric_type(&mut self) -> Assembled<NumericType> {
+        let (ty, span) = self.value_type()?;
+
+        ty.numeric().ok_or_else(|| error(span, &format!("expected a numeric type, found `{}`", ty)))
+    }
+
+    fn constant(&mut self) -> Assembled<Constant> {
+        let token = self.next("a constant")?;
+
+        let word = match token.kind {
+            TokenKind::String(s) => return Ok(Constant::String(Box::leak(s.into_boxed_str()))),
+            TokenKind::Char(c) => return Ok(Constant::Char(c)),
+            TokenKind::Word(word) => word,
+            _ => return Err(unexpected(&token, "a constant")),
+        };
+
+        match word {
+            "true" => return Ok(Constant::Bool(true)),
+            "false" => return Ok(Constant::Bool(false)),
+
+            "vtable" => {
+                let size = self.number("a size")?;
+                let align = self.number("an alignment")?;
+                let mut methods = vec![];
+
+                self.punct('[')?;
+
+                while !self.eat(']') {
+                    if !methods.is_empty() {
+                        self.punct(',')?;
+                    }
+
+                    methods.push(self.number("a function index")?);
+                }
+
+                return Ok(Constant::VTable { size, align, methods });
+            },
+
+            _ => {},
+        }
+
+        let invalid = || error(token.span, &format!("invalid constant `{}`", word));
+        let suffix = SUFFIXES.iter().find(|suffix| word.len() > suffix.len() && word.ends_with(*suffix)).ok_or_else(invalid)?;
+        let digits = &word[.. word.len() - suffix.len()];
+
+        let constant = match value_type_named(suffix) {
+            Some(ValueType::Int(ty)) => {
+                let value = digits.parse::<i64>().ok().filter(|value| (ty.min() ..= ty.max()).contains(&(*value as i128))).ok_or_else(invalid)?;
+
+                Constant::Int(SignedInteger::new(ty, value))
+            },
+
+            Some(ValueType::Uint(ty)) => {
+                let value = digits.parse::<u64>().ok().filter(|value| *value as i128 <= ty.max()).ok_or_else(invalid)?;
+
+                Constant::Uint(UnsignedInteger::new(ty, value))
+            },
+
+            Some(ValueType::Float(ty)) => Constant::Float(FloatingPoint::new(ty, digits.parse().map_err(|_| invalid())?)),
+            _ => return Err(invalid()),
+        };
+
+        Ok(constant)
+    }
+
+    // the span of the tokens from `start` up to where the line has been read
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(self.tokens[start].span.start, self.tokens[self.at - 1].span.end)
+    }
+
+    fn finish(&self) -> Assembled<()> {
+        match self.tokens.get(self.at) {
+            Some(token) => Err(unexpected(token, "the end of the line")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> Error {
+    let found = match &token.kind {
+        TokenKind::Word(word) => format!("`{}`", word),
+        TokenKind::String(s) => format!("{:?}", s),
+        TokenKind::Char(c) => format!("{:?}", c),
+        TokenKind::Punct(c) => format!("`{}`", c),
+    };
+
+    error(token.span, &format!("expected {}, found {}", expected, found))
+}
+
+#[derive(Default)]
+struct Assembler {
+    program: Program,
+    // whether the lines being read are the entries of the constant pool
+    in_constants: bool,
+    // the labels of the current function and the jumps to them that precede them
+    labels: HashMap<String, (u32, Span)>,
+    jumps: Vec<(usize, String, Span)>,
+    // the instructions that name the function they refer to, resolved once all functions are known
+    calls: Vec<(usize, usize, &'static str, Span)>,
+}
+
+impl Assembler {
+    fn line(&mut self, mut line: Line) -> Assembled<()> {
+        match line.tokens.iter().map(|token| &token.kind).collect::<Vec<_>>()[..] {
+            [] => Ok(()),
+            [ TokenKind::Word("constants") ] => {
+                self.in_constants = true;
+
+                Ok(())
+            },
+
+            [ TokenKind::Word(label), TokenKind::Punct(':') ] => self.label(label, line.tokens[0].span),
+            [ TokenKind::Punct('#'), .. ] if self.in_constants => self.constant(line),
+            [ TokenKind::Word("fn"), _, TokenKind::Punct('('), .. ] => self.function(line),
+
+            [ TokenKind::Word("local"), .. ] => {
+                line.at += 1;
+                self.local(line)
+            },
+
+            _ => self.instruction(line),
+        }
+    }
+
+    fn constant(&mut self, mut line: Line) -> Assembled<()> {
+        line.punct('#')?;
+
+        let index: u32 = line.number("a constant index")?;
+        let span = line.span_from(0);
+
+        if index as usize != self.program.constants.len() {
+            return Err(error(span, &format!("constant #{} is listed where #{} belongs", index, self.program.constants.len())));
+        }
+
+        let constant = line.constant()?;
+
+        line.finish()?;
+        self.program.constants.push(constant);
+
+        Ok(())
+    }
+
+    fn function(&mut self, mut line: Line) -> Assembled<()> {
+        self.finish_function()?;
+        self.in_constants = false;
+
+        line.word("`fn`")?;
+
+        let (name, _) = line.name("a function name")?;
+        let mut params = vec![];
+        let mut locals = vec![];
+
+        line.punct('(')?;
+
+        while !line.eat(')') {
+            if !params.is_empty() {
+                line.punct(',')?;
+            }
+
+            // a parameter is named after its local slot, if it has one
+            let is_named = matches!(line.tokens.get(line.at + 1), Some(Token { kind: TokenKind::Punct(':'), .. }));
+
+            if is_named {
+                let (param, span) = line.name("a parameter name")?;
+
+                if locals.len() < params.len() {
+                    return Err(error(span, &format!("parameter `{}` is named, but one before it is not", param)));
+                }
+
+                line.punct(':')?;
+
+                let (ty, _) = line.value_type()?;
+
+                locals.push(Local { name: param, ty });
+                params.push(ty);
+            } else {
+                params.push(line.value_type()?.0);
+            }
+        }
+
+        let ret = line.return_type()?;
+
+        line.finish()?;
+
+        let mut function = Function::new(name, params, ret);
+
+        function.locals = locals;
+        self.program.add_function(function);
+
+        Ok(())
+    }
+
+    fn local(&mut self, mut line: Line) -> Assembled<()> {
+        let (name, span) = line.name("a local name")?;
+
+        line.punct(':')?;
+
+        let (ty, _) = line.value_type()?;
+
+        line.finish()?;
+
+        let function = self.current(span)?;
+
+        if function.locals.len() < function.params.len() {
+            return Err(error(span, &format!("local `{}` follows a parameter without a name, whose slot it would take", name)));
+        }
+
+        function.locals.push(Local { name, ty });
+
+        Ok(())
+    }
+
+    fn label(&mut self, label: &str, span: Span) -> Assembled<()> {
+        let at = self.current(span)?.chunk.len() as u32;
+
+        if let Some((_, previous)) = self.labels.insert(label.to_owned(), (at, span)) {
+            return Err(error(span, &format!("label `{}` is already defined at {}", label, previous)));
+        }
+
+        Ok(())
+    }
+
+    fn instruction(&mut self, mut line: Line) -> Assembled<()> {
+        let first = line.tokens[0].span;
+        let at = self.current(first)?.chunk.len();
+
+        // the offset the disassembler puts in front, which has to be where the instruction ends up
+        if line.peek_word().is_some_and(is_number) && line.tokens.len() > 1 {
+            let offset: usize = line.number("an offset")?;
+
+            if offset != at {
+                return Err(error(first, &format!("instruction {:04} is listed at {:04}", at, offset)));
+            }
+        }
+
+        let start = line.at;
+        let (mnemonic, span) = line.word("an instruction")?;
+        let kind = Instruction::operand_kind(mnemonic).ok_or_else(|| error(span, &format!("unknown instruction `{}`", mnemonic)))?;
+
+        let operands = match kind {
+            OperandKind::None => Operands::None,
+            OperandKind::Index => Operands::Index(self.index(mnemonic, &mut line, at)?),
+            OperandKind::Slot => Operands::Slot(self.slot(&mut line)?),
+            OperandKind::Call => Operands::Call(line.number("an argument count")?, line.return_type()?),
+            OperandKind::Numeric => Operands::Numeric(line.numeric_type()?),
+            OperandKind::Value => Operands::Value(line.value_type()?.0),
+            OperandKind::Cast => Operands::Cast(line.value_type()?.0, line.value_type()?.0),
+        };
+
+        let span = match line.eat('@') {
+            true => {
+                let start = line.number("the start of a span")?;
+
+                line.punct(':')?;
+
+                Span::new(start, line.number("the end of a span")?)
+            },
+
+            false => line.span_from(start),
+        };
+
+        line.finish()?;
+
+        let instruction = Instruction::new(mnemonic, operands).expect("the operands are of the kind the mnemonic takes");
+
+        self.current(span)?.chunk.push(instruction, span);
+
+        Ok(())
+    }
+
+    // an index may also be given as a label, a function name or the value of a constant
+    fn index(&mut self, mnemonic: &str, line: &mut Line, at: usize) -> Assembled<u32> {
+        if line.peek_word().is_some_and(is_number) {
+            return line.number("an index");
+        }
+
+        match mnemonic {
+            "jump" | "jump_if_false" | "jump_if_true" => {
+                let (label, span) = line.word("a label")?;
+
+                self.jumps.push((at, label.to_owned(), span));
+
+                Ok(0)
+            },
+
+            "fn" | "call" => {
+                let (name, span) = line.name("a function")?;
+
+                self.calls.push((self.program.functions.len() - 1, at, name, span));
+
+                Ok(0)
+            },
+
+            "const" | "trap" => Ok(self.program.constants.add(line.constant()?)),
+            _ => line.number("a number"),
+        }
+    }
+
+    fn slot(&mut self, line: &mut Line) -> Assembled<u16> {
+        if line.peek_word().is_some_and(is_number) {
+            return line.number("a local slot");
+        }
+
+        let (name, span) = line.name("a local")?;
+        let function = self.current(span)?;
+
+        match function.locals.iter().position(|local| local.name == name) {
+            Some(slot) => Ok(slot as u16),
+            None => Err(error(span, &format!("`{}` has no local named `{}`", function.name, name))),
+        }
+    }
+
+    fn current(&mut self, span: Span) -> Assembled<&mut Function> {
+        self.program.functions.last_mut().ok_or_else(|| error(span, "instructions, locals and labels belong inside a function"))
+    }
+
+    // points the jumps of the function just read at their labels
+    fn finish_function(&mut self) -> Assembled<()> {
+        let labels = std::mem::take(&mut self.labels);
+
+        for (at, label, span) in std::mem::take(&mut self.jumps) {
+            let function = self.current(span)?;
+
+            match labels.get(&label) {
+                Some((target, _)) => function.chunk.patch_jump(at, *target as usize),
+                None => return Err(error(span, &format!("`{}` has no label `{}`", function.name, label))),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Assembled<Program> {
+        self.finish_function()?;
+
+        for (function, at, name, span) in std::mem::take(&mut self.calls) {
+            let index = self.program.function_index(name).ok_or_else(|| error(span, &format!("the program has no function named `{}`", name)))?;
+            let chunk = &mut self.program.functions[function].chunk;
+
+            chunk.code[at] = Instruction::new(chunk.code[at].mnemonic(), Operands::Index(index)).expect("the instruction takes an index");
+        }
+
+        Ok(self.program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ bytecode::{ disasm::{ Disassembler }, file }, vm::{ Vm, value::{ Value } } };
+
+    #[test]
+    fn listings_reassemble_to_the_same_program() {
+        let mut program = Program::new();
+        let int = ValueType::Int(SignedIntegerTypes::Int32);
+
+        for constant in [
+            Constant::Int(SignedInteger::new(SignedIntegerTypes::Int64, i64::MIN)),
+            Constant::Uint(UnsignedInteger::new(UnsignedIntegerTypes::Uint8, 255)),
+            Constant::Float(FloatingPoint::new(FloatingPointTypes::FP64, -0.0)),
+            Constant::Float(FloatingPoint::new(FloatingPointTypes::FP32, f64::NAN)),
+            Constant::Float(FloatingPoint::new(FloatingPointTypes::FP64, 1e300)),
+            Constant::Char('\''),
+            Constant::String("tab\t, \"quote\" ; λ\u{7}"),
+            Constant::VTable { size: 16, align: 8, methods: vec![ 1, 0 ] },
+        ] {
+            program.constants.push(constant);
+        }
+
+        let mut function = Function::new("main.closure@4:20", vec![ int, int ], None);
+
+        function.locals = vec![ Local { name: "$ret", ty: int } ];
+        function.chunk.push(Instruction::Const(6), Span::new(1, 2));
+        function.chunk.push(Instruction::JumpIfTrue(3), Span::new(3, 4));
+        function.chunk.push(Instruction::Cast(int, ValueType::Float(FloatingPointTypes::FP16)), Span::new(5, 6));
+        function.chunk.push(Instruction::Function(1), Span::new(7, 8));
+        function.chunk.push(Instruction::CallIndirect(2, Some(ValueType::Pointer)), Span::new(9, 10));
+        function.chunk.push(Instruction::CallIndirect(0, None), Span::new(9, 10));
+        function.chunk.push(Instruction::Jump(6), Span::new(11, 12));
+        program.add_function(function);
+
+        let mut function = Function::new("f", vec![], Some(ValueType::Char));
+
+        function.locals = vec![ Local { name: "", ty: ValueType::Bool } ];
+        function.chunk.push(Instruction::Store(0), Span::new(0, 0));
+        program.add_function(function);
+
+        let listing = Disassembler::new(&program).program();
+
+        // compared encoded, as the NaN constant is not equal to itself
+        assert_eq!(file::encode(&assemble(&listing).unwrap()), file::encode(&program));
+    }
+
+    #[test]
+    fn operands_may_be_named() {
+        let program = assemble("
+            fn factorial(n: u64) -> u64
+                local result: u64
+                const 1u64
+                store result
+            loop:
+                load n
+                const 0u64
+                eq u64
+                jump_if_true done
+                load result
+                load n
+                mul u64
+                store result
+                load n
+                const 1u64
+                sub u64
+                store n
+                jump loop
+            done:
+                load result
+                ret
+
+            fn main() -> u64
+                const 10u64
+                call factorial
+                ret
+        ").unwrap();
+
+        assert_eq!(program.constants.len(), 3);
+        assert_eq!(program.functions[0].chunk.get(5), Some(Instruction::JumpIfTrue(15)));
+        assert_eq!(Vm::new(&program).call("main", &[]).unwrap(), Some(Value::Uint(3628800)));
+    }
+
+    #[test]
+    fn mistakes_are_reported_where_they_are() {
+        let text = "fn f()\n    jump nowhere\n";
+        let error = assemble(text).unwrap_err();
+
+        assert_eq!(error.get_message(), "`f` has no label `nowhere`");
+        assert_eq!(error.get_location(), Span::new(16, 22));
+
+        let error = assemble("fn f()\n    load 0\n    frobnicate i32 @1:2\n").unwrap_err();
+
+        assert_eq!(error.get_error_type(), ErrorType::ParseError);
+        assert_eq!(error.get_message(), "unknown instruction `frobnicate`");
+        assert_eq!(error.get_location(), Span::new(22, 31));
+    }
+}
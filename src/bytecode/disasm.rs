@@ -0,0 +1,195 @@
+use std::{ collections::{ BTreeSet }, fmt::{ Write } };
+
+use super::{ * };
+use crate::{ lexer::{ Source } };
+
+// the widths the instruction and span columns of a listing are padded to
+const INSTRUCTION_WIDTH: usize = 24;
+const SPAN_WIDTH: usize = 12;
+
+// turns compiled code back into a listing the assembler accepts, with what the operands refer to
+// and, given the source it was compiled from, the line each instruction came from in comments
+pub struct Disassembler<'a> {
+    program: &'a Program,
+    source: Option<&'a Source>,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            source: None,
+        }
+    }
+
+    pub fn with_source(self, source: &'a Source) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
+    }
+
+    // the constant pool followed by every function
+    pub fn program(&self) -> String {
+        let mut listing = String::from("constants\n");
+
+        for (index, constant) in self.program.constants.iter().enumerate() {
+            writeln!(listing, "    #{:<4} {}", index, constant).unwrap();
+        }
+
+        for function in &self.program.functions {
+            listing.push('\n');
+            listing.push_str(&self.function(function));
+        }
+
+        listing
+    }
+
+    // the signature, with the parameters named after their local slots, the other locals and the code
+    pub fn function(&self, function: &Function) -> String {
+        let params: Vec<String> = function.params.iter().enumerate().map(|(slot, ty)| match function.locals.get(slot) {
+            Some(local) => format!("{}: {}", name(local.name), ty),
+            None => ty.to_string(),
+        }).collect();
+
+        let mut listing = format!("fn {}({})", name(function.name), params.join(", "));
+
+        if let Some(ret) = function.ret {
+            write!(listing, " -> {}", ret).unwrap();
+        }
+
+        listing.push('\n');
+
+        for local in function.locals.iter().skip(function.params.len()) {
+            writeln!(listing, "    local {}: {}", name(local.name), local.ty).unwrap();
+        }
+
+        listing.push_str(&self.disassemble(&function.chunk, &function.locals));
+        listing
+    }
+
+    // one line per instruction: its offset, the instruction with jump targets as labels and its span,
+    // then a comment with the local, constant or function an operand refers to and the source line
+    pub fn disassemble(&self, chunk: &Chunk, locals: &[Local]) -> String {
+        let targets: BTreeSet<u32> = chunk.code().iter().filter_map(|instruction| instruction.jump_target()).collect();
+        let label = |target: u32| format!("L{}", targets.iter().position(|other| *other == target).unwrap());
+
+        let mut listing = String::new();
+
+        for (at, (instruction, span)) in chunk.iter().enumerate() {
+            if targets.contains(&(at as u32)) {
+                writeln!(listing, "{}:", label(at as u32)).unwrap();
+            }
+
+            let text = match instruction.jump_target() {
+                Some(target) => format!("{} {}", instruction.mnemonic(), label(target)),
+                None => instruction.to_string(),
+            };
+
+            let mut notes = vec![];
+
+            if let Some(note) = self.resolve(instruction, locals) {
+                notes.push(note);
+            }
+
+            if let Some(source) = self.source {
+                let (line, code) = source.line_at(span.start);
+
+                notes.push(format!("line {}: {}", line, code.trim()));
+            }
+
+            let line = match notes.is_empty() {
+                true => format!("    {:04}  {:<width$} @{}", at, text, span, width = INSTRUCTION_WIDTH),
+                false => format!("    {:04}  {:<width$} {:<span_width$} ; {}", at, text, format!("@{}", span), notes.join("  "), width = INSTRUCTION_WIDTH, span_width = SPAN_WIDTH),
+            };
+
+            listing.push_str(line.trim_end());
+            listing.push('\n');
+        }
+
+        // a jump may also leave the chunk by targeting its end
+        if targets.contains(&(chunk.len() as u32)) {
+            writeln!(listing, "{}:", label(chunk.len() as u32)).unwrap();
+        }
+
+        listing
+    }
+
+    // what the operand of `instruction` refers to, if it is not plain from the operand itself
+    fn resolve(&self, instruction: Instruction, locals: &[Local]) -> Option<String> {
+        match instruction {
+            Instruction::Const(index) | Instruction::Trap(index) => self.program.constants.get(index).map(|constant| constant.to_string()),
+            Instruction::Function(index) | Instruction::Call(index) => self.program.functions.get(index as usize).map(|function| function.name.to_owned()),
+            Instruction::Load(slot) | Instruction::Store(slot) => locals.get(slot as usize).map(|local| local.name.to_owned()),
+            _ => None,
+        }
+    }
+}
+
+// a name is written as is if it is a plain identifier and quoted otherwise, as compiler generated
+// ones like `main.closure@4:20` are not
+pub fn name(name: &str) -> String {
+    let is_plain = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+
+    match is_plain {
+        true => name.to_owned(),
+        false => format!("{:?}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    fn program() -> Program {
+        let mut program = Program::new();
+        let int = ValueType::Int(SignedIntegerTypes::Int32);
+        let zero = program.constants.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int32, 0)));
+
+        let mut function = Function::new("abs", vec![ int ], Some(int));
+
+        function.locals = vec![ Local { name: "x", ty: int }, Local { name: "$tmp", ty: ValueType::Bool } ];
+        function.chunk.push(Instruction::Load(0), Span::new(20, 21));
+        function.chunk.push(Instruction::Const(zero), Span::new(24, 25));
+        function.chunk.push(Instruction::Lt(NumericType::Int(SignedIntegerTypes::Int32)), Span::new(20, 25));
+        function.chunk.push(Instruction::JumpIfFalse(6), Span::new(20, 25));
+        function.chunk.push(Instruction::Load(0), Span::new(38, 40));
+        function.chunk.push(Instruction::Neg(NumericType::Int(SignedIntegerTypes::Int32)), Span::new(38, 40));
+        function.chunk.push(Instruction::Return, Span::new(31, 41));
+
+        program.add_function(function);
+        program
+    }
+
+    #[test]
+    fn listings_resolve_operands_and_label_jumps() {
+        let program = program();
+        let listing = Disassembler::new(&program).function(&program.functions[0]);
+
+        assert_eq!(listing, [
+            "fn abs(x: i32) -> i32",
+            "    local $tmp: bool",
+            "    0000  load 0                   @20:21       ; x",
+            "    0001  const 0                  @24:25       ; 0i32",
+            "    0002  lt i32                   @20:25",
+            "    0003  jump_if_false L0         @20:25",
+            "    0004  load 0                   @38:40       ; x",
+            "    0005  neg i32                  @38:40",
+            "L0:",
+            "    0006  ret                      @31:41",
+            "",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn listings_show_the_source_line_of_each_instruction() {
+        let program = program();
+        let source = Source::new("abs", "fn abs(x: i32) {\n    if x < 0 {\n        return -x;\n    }\n}");
+        let listing = Disassembler::new(&program).with_source(&source).program();
+
+        assert!(listing.starts_with("constants\n    #0    0i32\n\nfn abs(x: i32) -> i32\n"));
+        assert!(listing.contains("    0002  lt i32                   @20:25       ; line 2: if x < 0 {\n"));
+        assert!(listing.contains("    0004  load 0                   @38:40       ; x  line 3: return -x;\n"));
+    }
+}
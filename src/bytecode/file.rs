@@ -0,0 +1,538 @@
+use std::{ path::{ Path } };
+
+use super::{ *, verify::{ verify } };
+use crate::{ error::{ * } };
+
+type Loaded<T> = std::result::Result<T, Error>;
+
+// a compiled module on disk, every number little-endian:
+//
+//   header      magic "SYNB", format version u16
+//   constants   count u32, then a tag u8 and the value of each
+//   functions   count u32, then name, parameter types, return type, local types and code of each
+//   debug info  for every function, the name of each local followed by the span of each instruction
+//   checksum    CRC-32 of everything before it
+//
+// strings are a u32 length followed by their UTF-8 bytes, types a single byte
+pub const MAGIC: [u8; 4] = *b"SYNB";
+// version 3 widened `char` to 4 bytes, which moves the fields after it
+pub const FORMAT_VERSION: u16 = 3;
+pub const EXTENSION: &str = "synb";
+
+const HEADER_SIZE: usize = MAGIC.len() + 2;
+const CHECKSUM_SIZE: usize = 4;
+
+pub fn encode(program: &Program) -> Vec<u8> {
+    let mut writer = Writer::default();
+
+    writer.bytes.extend_from_slice(&MAGIC);
+    writer.u16(FORMAT_VERSION);
+
+    writer.u32(program.constants.len() as u32);
+
+    for constant in program.constants.iter() {
+        writer.constant(constant);
+    }
+
+    writer.u32(program.functions.len() as u32);
+
+    for function in &program.functions {
+        writer.string(function.name);
+        writer.u16(function.params.len() as u16);
+
+        for param in &function.params {
+            writer.value_type(*param);
+        }
+
+        writer.return_type(function.ret);
+
+        writer.u16(function.locals.len() as u16);
+
+        for local in &function.locals {
+            writer.value_type(local.ty);
+        }
+
+        writer.u32(function.chunk.len() as u32);
+
+        for instruction in function.chunk.code() {
+            writer.instruction(*instruction);
+        }
+    }
+
+    for function in &program.functions {
+        for local in &function.locals {
+            writer.string(local.name);
+        }
+
+        for (_, span) in function.chunk.iter() {
+            writer.u64(span.start as u64);
+            writer.u64(span.end as u64);
+        }
+    }
+
+    let checksum = checksum(&writer.bytes);
+
+    writer.u32(checksum);
+    writer.bytes
+}
+
+// the header is checked first so that files of other versions are reported as such rather than as corrupt
+pub fn decode(bytes: &[u8]) -> Loaded<Program> {
+    if bytes.len() < HEADER_SIZE || bytes[.. MAGIC.len()] != MAGIC {
+        return Err(Error::io(codes::NOT_BYTECODE, "not a compiled module: the file does not start with `SYNB`"));
+    }
+
+    let version = u16::from_le_bytes([ bytes[4], bytes[5] ]);
+
+    if version != FORMAT_VERSION {
+        return Err(Error::io(codes::BYTECODE_VERSION_MISMATCH, &format!("the module was compiled to format version {}, but only version {} can be loaded", version, FORMAT_VERSION)));
+    }
+
+    if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE {
+        return Err(corrupt("the file ends before its checksum"));
+    }
+
+    let (contents, trailer) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+    let expected = u32::from_le_bytes([ trailer[0], trailer[1], trailer[2], trailer[3] ]);
+    let actual = checksum(contents);
+
+    if expected != actual {
+        return Err(corrupt(&format!("checksum mismatch: the file records {:08x}, but its contents hash to {:08x}", expected, actual)));
+    }
+
+    let mut reader = Reader { bytes: contents, at: HEADER_SIZE };
+    let mut program = Program::new();
+
+    for _ in 0 .. reader.u32()? {
+        let constant = reader.constant()?;
+
+        program.constants.push(constant);
+    }
+
+    // the code of every function, kept apart until its spans are read from the debug info
+    let mut code = vec![];
+
+    for _ in 0 .. reader.u32()? {
+        let name = reader.string()?;
+        let params = (0 .. reader.u16()?).map(|_| reader.value_type()).collect::<Loaded<Vec<_>>>()?;
+
+        let ret = reader.return_type()?;
+
+        let locals = (0 .. reader.u16()?).map(|_| reader.value_type()).collect::<Loaded<Vec<_>>>()?;
+        let instructions = (0 .. reader.u32()?).map(|_| reader.instruction()).collect::<Loaded<Vec<_>>>()?;
+
+        let mut function = Function::new(name, params, ret);
+
+        function.locals = locals.into_iter().map(|ty| Local { name: "", ty }).collect();
+        program.add_function(function);
+        code.push(instructions);
+    }
+
+    for (function, instructions) in program.functions.iter_mut().zip(code) {
+        for local in &mut function.locals {
+            local.name = reader.string()?;
+        }
+
+        for instruction in instructions {
+            let start = reader.u64()? as usize;
+            let end = reader.u64()? as usize;
+
+            function.chunk.push(instruction, Span::new(start, end));
+        }
+    }
+
+    if reader.at != contents.len() {
+        return Err(corrupt(&format!("{} unexpected bytes after the debug info", contents.len() - reader.at)));
+    }
+
+    verify(&program).map_err(|error| corrupt(&error.get_message()))?;
+
+    Ok(program)
+}
+
+pub fn save(program: &Program, path: &Path) -> Loaded<()> {
+    std::fs::write(path, encode(program)).map_err(|error| {
+        Error::io(codes::WRITE_FAILED, &format!("cannot write `{}`: {}", path.display(), error))
+    })
+}
+
+pub fn load(path: &Path) -> Loaded<Program> {
+    let bytes = std::fs::read(path).map_err(|error| {
+        Error::io(codes::READ_FAILED, &format!("cannot read `{}`: {}", path.display(), error))
+    })?;
+
+    decode(&bytes).map_err(|error| Error::io(error.get_code(), &format!("cannot load `{}`: {}", path.display(), error.get_message())))
+}
+
+fn corrupt(message: &str) -> Error {
+    Error::io(codes::CORRUPT_BYTECODE, &format!("corrupt module: {}", message))
+}
+
+// CRC-32 as used by zip and PNG
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+
+        for _ in 0 .. 8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+
+    !crc
+}
+
+const SIGNED: [SignedIntegerTypes; 4] = [ SignedIntegerTypes::Int8, SignedIntegerTypes::Int16, SignedIntegerTypes::Int32, SignedIntegerTypes::Int64 ];
+const UNSIGNED: [UnsignedIntegerTypes; 4] = [ UnsignedIntegerTypes::Uint8, UnsignedIntegerTypes::Uint16, UnsignedIntegerTypes::Uint32, UnsignedIntegerTypes::Uint64 ];
+const FLOATS: [FloatingPointTypes; 3] = [ FloatingPointTypes::FP16, FloatingPointTypes::FP32, FloatingPointTypes::FP64 ];
+
+// the signed types come first, then the unsigned ones, then the floats, which makes the numeric
+// types a prefix of the value types
+fn value_type_code(ty: ValueType) -> u8 {
+    match ty {
+        ValueType::Int(ty) => SIGNED.iter().position(|other| *other == ty).unwrap() as u8,
+        ValueType::Uint(ty) => 4 + UNSIGNED.iter().position(|other| *other == ty).unwrap() as u8,
+        ValueType::Float(ty) => 8 + FLOATS.iter().position(|other| *other == ty).unwrap() as u8,
+        ValueType::Bool => 11,
+        ValueType::Char => 12,
+        ValueType::Pointer => 13,
+        ValueType::Function => 14,
+    }
+}
+
+fn value_type_from_code(code: u8) -> Option<ValueType> {
+    match code {
+        0 ..= 3 => Some(ValueType::Int(SIGNED[code as usize])),
+        4 ..= 7 => Some(ValueType::Uint(UNSIGNED[code as usize - 4])),
+        8 ..= 10 => Some(ValueType::Float(FLOATS[code as usize - 8])),
+        11 => Some(ValueType::Bool),
+        12 => Some(ValueType::Char),
+        13 => Some(ValueType::Pointer),
+        14 => Some(ValueType::Function),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn value_type(&mut self, ty: ValueType) {
+        self.u8(value_type_code(ty));
+    }
+
+    fn return_type(&mut self, ty: Option<ValueType>) {
+        match ty {
+            Some(ty) => {
+                self.u8(1);
+                self.value_type(ty);
+            },
+
+            None => self.u8(0),
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::Int(v) => {
+                self.u8(0);
+                self.value_type(ValueType::Int(v.base_type));
+                self.u64(v.value as u64);
+            },
+
+            Constant::Uint(v) => {
+                self.u8(1);
+                self.value_type(ValueType::Uint(v.base_type));
+                self.u64(v.value);
+            },
+
+            Constant::Float(v) => {
+                self.u8(2);
+                self.value_type(ValueType::Float(v.base_type));
+                self.u64(v.value.to_bits());
+            },
+
+            Constant::Bool(b) => {
+                self.u8(3);
+                self.u8(*b as u8);
+            },
+
+            Constant::Char(c) => {
+                self.u8(4);
+                self.u32(*c as u32);
+            },
+
+            Constant::String(s) => {
+                self.u8(5);
+                self.string(s);
+            },
+
+            Constant::VTable { size, align, methods } => {
+                self.u8(6);
+                self.u64(*size);
+                self.u64(*align);
+                self.u32(methods.len() as u32);
+
+                for method in methods {
+                    self.u32(*method);
+                }
+            },
+        }
+    }
+
+    fn instruction(&mut self, instruction: Instruction) {
+        let opcode = MNEMONICS.iter().position(|mnemonic| *mnemonic == instruction.mnemonic()).expect("every instruction has an opcode");
+
+        self.u8(opcode as u8);
+
+        match instruction.operands() {
+            Operands::None => {},
+            Operands::Index(n) => self.u32(n),
+            Operands::Slot(slot) => self.u16(slot),
+
+            Operands::Call(argc, ret) => {
+                self.u8(argc);
+                self.return_type(ret);
+            },
+
+            Operands::Numeric(ty) => self.value_type(ValueType::from(ty)),
+            Operands::Value(ty) => self.value_type(ty),
+
+            Operands::Cast(from, to) => {
+                self.value_type(from);
+                self.value_type(to);
+            },
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Loaded<&[u8]> {
+        let end = self.at.checked_add(count).filter(|end| *end <= self.bytes.len()).ok_or_else(|| {
+            corrupt(&format!("the file ends in the middle of a value at byte {}", self.at))
+        })?;
+
+        let bytes = &self.bytes[self.at .. end];
+
+        self.at = end;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Loaded<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Loaded<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Loaded<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Loaded<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Loaded<&'static str> {
+        let at = self.at;
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(Box::leak(s.to_owned().into_boxed_str())),
+            Err(_) => Err(corrupt(&format!("the string at byte {} is not valid UTF-8", at))),
+        }
+    }
+
+    fn value_type(&mut self) -> Loaded<ValueType> {
+        let code = self.u8()?;
+
+        value_type_from_code(code).ok_or_else(|| self.invalid(&format!("type code {}", code)))
+    }
+
+    fn return_type(&mut self) -> Loaded<Option<ValueType>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.value_type()?)),
+            flag => Err(self.invalid(&format!("return type flag {}", flag))),
+        }
+    }
+
+    fn numeric_type(&mut self) -> Loaded<NumericType> {
+        let ty = self.value_type()?;
+
+        ty.numeric().ok_or_else(|| self.invalid(&format!("non-numeric type `{}` for an arithmetic instruction", ty)))
+    }
+
+    fn constant(&mut self) -> Loaded<Constant> {
+        let constant = match self.u8()? {
+            0 => match self.value_type()? {
+                ValueType::Int(base_type) => Constant::Int(SignedInteger::new(base_type, self.u64()? as i64)),
+                ty => return Err(self.invalid(&format!("type `{}` for an integer constant", ty))),
+            },
+
+            1 => match self.value_type()? {
+                ValueType::Uint(base_type) => Constant::Uint(UnsignedInteger::new(base_type, self.u64()?)),
+                ty => return Err(self.invalid(&format!("type `{}` for an unsigned constant", ty))),
+            },
+
+            2 => match self.value_type()? {
+                ValueType::Float(base_type) => Constant::Float(FloatingPoint { base_type, value: f64::from_bits(self.u64()?) }),
+                ty => return Err(self.invalid(&format!("type `{}` for a float constant", ty))),
+            },
+
+            3 => match self.u8()? {
+                0 => Constant::Bool(false),
+                1 => Constant::Bool(true),
+                b => return Err(self.invalid(&format!("bool {}", b))),
+            },
+
+            4 => {
+                let c = self.u32()?;
+
+                Constant::Char(char::from_u32(c).ok_or_else(|| self.invalid(&format!("char {:#x}", c)))?)
+            },
+
+            5 => Constant::String(self.string()?),
+
+            6 => {
+                let size = self.u64()?;
+                let align = self.u64()?;
+                let methods = (0 .. self.u32()?).map(|_| self.u32()).collect::<Loaded<Vec<_>>>()?;
+
+                Constant::VTable { size, align, methods }
+            },
+
+            tag => return Err(self.invalid(&format!("constant tag {}", tag))),
+        };
+
+        Ok(constant)
+    }
+
+    fn instruction(&mut self) -> Loaded<Instruction> {
+        let opcode = self.u8()?;
+        let Some(mnemonic) = MNEMONICS.get(opcode as usize) else {
+            return Err(self.invalid(&format!("opcode {}", opcode)));
+        };
+
+        let operands = match Instruction::operand_kind(mnemonic).expect("every mnemonic has operands") {
+            OperandKind::None => Operands::None,
+            OperandKind::Index => Operands::Index(self.u32()?),
+            OperandKind::Slot => Operands::Slot(self.u16()?),
+            OperandKind::Call => Operands::Call(self.u8()?, self.return_type()?),
+            OperandKind::Numeric => Operands::Numeric(self.numeric_type()?),
+            OperandKind::Value => Operands::Value(self.value_type()?),
+            OperandKind::Cast => Operands::Cast(self.value_type()?, self.value_type()?),
+        };
+
+        Ok(Instruction::new(mnemonic, operands).expect("the operands are of the kind the mnemonic takes"))
+    }
+
+    // the value just read is not one the format allows
+    fn invalid(&self, what: &str) -> Error {
+        corrupt(&format!("invalid {} before byte {}", what, self.at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    fn program() -> Program {
+        let mut program = Program::new();
+        let int = NumericType::Int(SignedIntegerTypes::Int32);
+
+        let one = program.constants.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int32, -1)));
+        let message = program.constants.add(Constant::String("unreachable ✓"));
+
+        program.constants.add(Constant::Float(FloatingPoint::new(FloatingPointTypes::FP16, -0.0)));
+        program.constants.add(Constant::Char('λ'));
+        program.constants.add(Constant::VTable { size: 12, align: 4, methods: vec![ 0, 0 ] });
+
+        let mut function = Function::new("negate", vec![ ValueType::Int(SignedIntegerTypes::Int32) ], Some(ValueType::Int(SignedIntegerTypes::Int32)));
+
+        function.locals = vec![ Local { name: "x", ty: ValueType::Int(SignedIntegerTypes::Int32) }, Local { name: "$tmp", ty: ValueType::Bool } ];
+        function.chunk.push(Instruction::Load(0), Span::new(3, 4));
+        function.chunk.push(Instruction::Const(one), Span::new(5, 7));
+        function.chunk.push(Instruction::Mul(int), Span::new(3, 7));
+        function.chunk.push(Instruction::Cast(ValueType::Int(SignedIntegerTypes::Int32), ValueType::Float(FloatingPointTypes::FP64)), Span::new(3, 12));
+        function.chunk.push(Instruction::Cast(ValueType::Float(FloatingPointTypes::FP64), ValueType::Int(SignedIntegerTypes::Int32)), Span::new(3, 19));
+        function.chunk.push(Instruction::Return, Span::new(0, 20));
+        function.chunk.push(Instruction::Trap(message), Span::new(0, 21));
+
+        program.add_function(function);
+        program
+    }
+
+    #[test]
+    fn programs_survive_a_round_trip() {
+        let program = program();
+        let bytes = encode(&program);
+
+        assert_eq!(bytes[.. 4], MAGIC);
+        assert_eq!(decode(&bytes), Ok(program));
+    }
+
+    #[test]
+    fn damaged_files_are_rejected() {
+        let bytes = encode(&program());
+
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+
+        assert_eq!(decode(b"#!/bin/sh").unwrap_err().get_code(), codes::NOT_BYTECODE);
+        assert_eq!(decode(&flipped).unwrap_err().get_code(), codes::CORRUPT_BYTECODE);
+        assert_eq!(decode(&bytes[.. bytes.len() - 1]).unwrap_err().get_code(), codes::CORRUPT_BYTECODE);
+
+        // a well-formed file can still refer to things it does not contain
+        let mut dangling = program();
+        dangling.functions[0].chunk.push(Instruction::Jump(99), Span::new(0, 0));
+
+        assert_eq!(
+            decode(&encode(&dangling)).unwrap_err().get_message(),
+            "corrupt module: `jump 99` at 7 in `negate` refers to instruction #99, which does not exist",
+        );
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut bytes = encode(&program());
+        bytes[4] = 2;
+
+        let error = decode(&bytes).unwrap_err();
+
+        assert_eq!(error.get_code(), codes::BYTECODE_VERSION_MISMATCH);
+        assert_eq!(error.get_message(), "the module was compiled to format version 2, but only version 3 can be loaded");
+    }
+}
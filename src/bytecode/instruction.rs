@@ -0,0 +1,249 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ ValueType, NumericType };
+
+// jump targets and function indices are absolute; slots, sizes and offsets are in the units the operand names
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Instruction {
+    Const(u32),                         // push constant #n
+    Null,                               // push a null pointer
+    Function(u32),                      // push a reference to function #n
+    Pop,                                // drop the top value
+    Dup,                                // push a copy of the top value
+
+    Load(u16),                          // push local #n
+    Store(u16),                         // pop into local #n
+
+    Add(NumericType),                   // a b -> a + b, integers wrap around at their width
+    Sub(NumericType),                   // a b -> a - b
+    Mul(NumericType),                   // a b -> a * b
+    Div(NumericType),                   // a b -> a / b, traps on integer division by zero
+    Rem(NumericType),                   // a b -> a % b, traps on integer division by zero
+    Neg(NumericType),                   // a -> -a
+    BitAnd(NumericType),                // a b -> a & b, integers only
+    BitOr(NumericType),                 // a b -> a | b, integers only
+    BitNot(NumericType),                // a -> !a, integers only
+    Not,                                // bool -> !bool
+
+    Eq(ValueType),                      // a b -> a == b
+    Ne(ValueType),                      // a b -> a != b
+    Lt(NumericType),                    // a b -> a < b
+    Le(NumericType),                    // a b -> a <= b
+    Gt(NumericType),                    // a b -> a > b
+    Ge(NumericType),                    // a b -> a >= b
+    Cast(ValueType, ValueType),         // value of the first type -> value of the second
+
+    Jump(u32),                          // continue at instruction #n
+    JumpIfFalse(u32),                   // pop a bool, continue at #n if it is false
+    JumpIfTrue(u32),                    // pop a bool, continue at #n if it is true
+
+    Call(u32),                          // args... -> result, calls function #n
+    // function args... -> result, for a function value below n arguments that returns the given type
+    CallIndirect(u8, Option<ValueType>),
+    Return,                             // return the top value to the caller
+    ReturnVoid,                         // return without a value
+
+    StackAlloc(u32),                    // push a pointer to n bytes that live until the function returns
+    New(u32),                           // push a pointer to n fresh bytes on the heap
+    Env(u32),                           // push a pointer to n fresh bytes for a closure environment, never freed
+    Delete,                             // pop a heap pointer and free its block
+    Offset(u32),                        // pointer -> pointer + n bytes
+    Index(u32),                         // pointer index -> pointer + index * n bytes, bounds checked
+    Read(ValueType),                    // pointer -> the value stored there
+    Write(ValueType),                   // pointer value -> , stores the value
+    Copy(u32),                          // destination source -> , copies n bytes
+    NonNull,                            // pointer -> pointer, traps if it is null
+    Trap(u32),                          // stops the program with the string constant #n as its message
+}
+
+// the mnemonic of every instruction; in compiled modules, its opcode is its position here
+pub const MNEMONICS: [&str; 42] = [
+    "const", "null", "fn", "pop", "dup", "load", "store",
+    "add", "sub", "mul", "div", "rem", "neg", "bitand", "bitor", "bitnot", "not",
+    "eq", "ne", "lt", "le", "gt", "ge", "cast",
+    "jump", "jump_if_false", "jump_if_true", "call", "call_indirect", "ret", "ret_void",
+    "alloca", "new", "delete", "offset", "index", "read", "write", "copy", "nonnull", "trap",
+    // added after the rest, so the opcodes of older modules keep their meaning
+    "env",
+];
+
+// what follows the mnemonic of an instruction, in compiled modules and assembly alike
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Operands {
+    None,
+    Index(u32),
+    Slot(u16),
+    // the argument count and return type of an indirect call
+    Call(u8, Option<ValueType>),
+    Numeric(NumericType),
+    Value(ValueType),
+    Cast(ValueType, ValueType),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OperandKind {
+    None,
+    Index,
+    Slot,
+    Call,
+    Numeric,
+    Value,
+    Cast,
+}
+
+impl Instruction {
+    // the instruction `mnemonic` stands for, if `operands` are of the kind it takes
+    pub fn new(mnemonic: &str, operands: Operands) -> Option<Self> {
+        let instruction = match (mnemonic, operands) {
+            ("const", Operands::Index(n)) => Self::Const(n),
+            ("null", Operands::None) => Self::Null,
+            ("fn", Operands::Index(n)) => Self::Function(n),
+            ("pop", Operands::None) => Self::Pop,
+            ("dup", Operands::None) => Self::Dup,
+            ("load", Operands::Slot(slot)) => Self::Load(slot),
+            ("store", Operands::Slot(slot)) => Self::Store(slot),
+            ("add", Operands::Numeric(ty)) => Self::Add(ty),
+            ("sub", Operands::Numeric(ty)) => Self::Sub(ty),
+            ("mul", Operands::Numeric(ty)) => Self::Mul(ty),
+            ("div", Operands::Numeric(ty)) => Self::Div(ty),
+            ("rem", Operands::Numeric(ty)) => Self::Rem(ty),
+            ("neg", Operands::Numeric(ty)) => Self::Neg(ty),
+            ("bitand", Operands::Numeric(ty)) => Self::BitAnd(ty),
+            ("bitor", Operands::Numeric(ty)) => Self::BitOr(ty),
+            ("bitnot", Operands::Numeric(ty)) => Self::BitNot(ty),
+            ("not", Operands::None) => Self::Not,
+            ("eq", Operands::Value(ty)) => Self::Eq(ty),
+            ("ne", Operands::Value(ty)) => Self::Ne(ty),
+            ("lt", Operands::Numeric(ty)) => Self::Lt(ty),
+            ("le", Operands::Numeric(ty)) => Self::Le(ty),
+            ("gt", Operands::Numeric(ty)) => Self::Gt(ty),
+            ("ge", Operands::Numeric(ty)) => Self::Ge(ty),
+            ("cast", Operands::Cast(from, to)) => Self::Cast(from, to),
+            ("jump", Operands::Index(n)) => Self::Jump(n),
+            ("jump_if_false", Operands::Index(n)) => Self::JumpIfFalse(n),
+            ("jump_if_true", Operands::Index(n)) => Self::JumpIfTrue(n),
+            ("call", Operands::Index(n)) => Self::Call(n),
+            ("call_indirect", Operands::Call(argc, ret)) => Self::CallIndirect(argc, ret),
+            ("ret", Operands::None) => Self::Return,
+            ("ret_void", Operands::None) => Self::ReturnVoid,
+            ("alloca", Operands::Index(n)) => Self::StackAlloc(n),
+            ("new", Operands::Index(n)) => Self::New(n),
+            ("env", Operands::Index(n)) => Self::Env(n),
+            ("delete", Operands::None) => Self::Delete,
+            ("offset", Operands::Index(n)) => Self::Offset(n),
+            ("index", Operands::Index(n)) => Self::Index(n),
+            ("read", Operands::Value(ty)) => Self::Read(ty),
+            ("write", Operands::Value(ty)) => Self::Write(ty),
+            ("copy", Operands::Index(n)) => Self::Copy(n),
+            ("nonnull", Operands::None) => Self::NonNull,
+            ("trap", Operands::Index(n)) => Self::Trap(n),
+            _ => return None,
+        };
+
+        Some(instruction)
+    }
+
+    // the kind of operands the instruction `mnemonic` takes, if there is one by that name
+    pub fn operand_kind(mnemonic: &str) -> Option<OperandKind> {
+        let kind = match mnemonic {
+            "const" | "fn" | "jump" | "jump_if_false" | "jump_if_true" | "call" | "alloca" | "new" | "env" | "offset" | "index" | "copy" | "trap" => OperandKind::Index,
+            "load" | "store" => OperandKind::Slot,
+            "call_indirect" => OperandKind::Call,
+            "add" | "sub" | "mul" | "div" | "rem" | "neg" | "bitand" | "bitor" | "bitnot" | "lt" | "le" | "gt" | "ge" => OperandKind::Numeric,
+            "eq" | "ne" | "read" | "write" => OperandKind::Value,
+            "cast" => OperandKind::Cast,
+            "null" | "pop" | "dup" | "not" | "ret" | "ret_void" | "delete" | "nonnull" => OperandKind::None,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Const(_) => "const",
+            Self::Null => "null",
+            Self::Function(_) => "fn",
+            Self::Pop => "pop",
+            Self::Dup => "dup",
+            Self::Load(_) => "load",
+            Self::Store(_) => "store",
+            Self::Add(_) => "add",
+            Self::Sub(_) => "sub",
+            Self::Mul(_) => "mul",
+            Self::Div(_) => "div",
+            Self::Rem(_) => "rem",
+            Self::Neg(_) => "neg",
+            Self::BitAnd(_) => "bitand",
+            Self::BitOr(_) => "bitor",
+            Self::BitNot(_) => "bitnot",
+            Self::Not => "not",
+            Self::Eq(_) => "eq",
+            Self::Ne(_) => "ne",
+            Self::Lt(_) => "lt",
+            Self::Le(_) => "le",
+            Self::Gt(_) => "gt",
+            Self::Ge(_) => "ge",
+            Self::Cast(..) => "cast",
+            Self::Jump(_) => "jump",
+            Self::JumpIfFalse(_) => "jump_if_false",
+            Self::JumpIfTrue(_) => "jump_if_true",
+            Self::Call(_) => "call",
+            Self::CallIndirect(..) => "call_indirect",
+            Self::Return => "ret",
+            Self::ReturnVoid => "ret_void",
+            Self::StackAlloc(_) => "alloca",
+            Self::New(_) => "new",
+            Self::Env(_) => "env",
+            Self::Delete => "delete",
+            Self::Offset(_) => "offset",
+            Self::Index(_) => "index",
+            Self::Read(_) => "read",
+            Self::Write(_) => "write",
+            Self::Copy(_) => "copy",
+            Self::NonNull => "nonnull",
+            Self::Trap(_) => "trap",
+        }
+    }
+
+    pub fn operands(&self) -> Operands {
+        match *self {
+            Self::Const(n) | Self::Function(n) | Self::Jump(n) | Self::JumpIfFalse(n) | Self::JumpIfTrue(n) | Self::Call(n)
+            | Self::StackAlloc(n) | Self::New(n) | Self::Env(n) | Self::Offset(n) | Self::Index(n) | Self::Copy(n) | Self::Trap(n) => Operands::Index(n),
+
+            Self::Load(slot) | Self::Store(slot) => Operands::Slot(slot),
+            Self::CallIndirect(argc, ret) => Operands::Call(argc, ret),
+
+            Self::Add(ty) | Self::Sub(ty) | Self::Mul(ty) | Self::Div(ty) | Self::Rem(ty) | Self::Neg(ty) | Self::BitAnd(ty) | Self::BitOr(ty) | Self::BitNot(ty)
+            | Self::Lt(ty) | Self::Le(ty) | Self::Gt(ty) | Self::Ge(ty) => Operands::Numeric(ty),
+
+            Self::Eq(ty) | Self::Ne(ty) | Self::Read(ty) | Self::Write(ty) => Operands::Value(ty),
+            Self::Cast(from, to) => Operands::Cast(from, to),
+
+            Self::Null | Self::Pop | Self::Dup | Self::Not | Self::Return | Self::ReturnVoid | Self::Delete | Self::NonNull => Operands::None,
+        }
+    }
+
+    // where a jump may continue besides the next instruction
+    pub fn jump_target(&self) -> Option<u32> {
+        match self {
+            Self::Jump(target) | Self::JumpIfFalse(target) | Self::JumpIfTrue(target) => Some(*target),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self.operands() {
+            Operands::None => write!(f, "{}", self.mnemonic()),
+            Operands::Index(n) => write!(f, "{} {}", self.mnemonic(), n),
+            Operands::Slot(slot) => write!(f, "{} {}", self.mnemonic(), slot),
+            Operands::Call(argc, None) => write!(f, "{} {}", self.mnemonic(), argc),
+            Operands::Call(argc, Some(ret)) => write!(f, "{} {} -> {}", self.mnemonic(), argc, ret),
+            Operands::Numeric(ty) => write!(f, "{} {}", self.mnemonic(), ty),
+            Operands::Value(ty) => write!(f, "{} {}", self.mnemonic(), ty),
+            Operands::Cast(from, to) => write!(f, "{} {} {}", self.mnemonic(), from, to),
+        }
+    }
+}
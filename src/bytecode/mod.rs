@@ -0,0 +1,373 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use crate::{ lexer::{ Span }, types::{ *, consteval::{ ConstValue } } };
+
+pub mod instruction;
+pub mod file;
+pub mod disasm;
+pub mod asm;
+pub mod verify;
+
+pub use instruction::{ Instruction, Operands, OperandKind, MNEMONICS };
+
+// the operand type of an arithmetic or ordering instruction
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NumericType {
+    Int(SignedIntegerTypes),
+    Uint(UnsignedIntegerTypes),
+    Float(FloatingPointTypes),
+}
+
+impl NumericType {
+    pub fn is_integer(&self) -> bool {
+        !matches!(self, Self::Float(_))
+    }
+}
+
+impl Display for NumericType {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(ty) => write!(f, "{}", ty),
+            Self::Uint(ty) => write!(f, "{}", ty),
+            Self::Float(ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+// the type of a single value on the operand stack or in a local slot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ValueType {
+    Int(SignedIntegerTypes),
+    Uint(UnsignedIntegerTypes),
+    Float(FloatingPointTypes),
+    Bool,
+    Char,
+    Pointer,
+    Function,
+}
+
+impl ValueType {
+    pub fn numeric(&self) -> Option<NumericType> {
+        match self {
+            Self::Int(ty) => Some(NumericType::Int(*ty)),
+            Self::Uint(ty) => Some(NumericType::Uint(*ty)),
+            Self::Float(ty) => Some(NumericType::Float(*ty)),
+            _ => None,
+        }
+    }
+
+    // the number of bytes the value takes up in memory
+    pub fn size(&self) -> u32 {
+        match self {
+            Self::Int(ty) => ty.bits() / 8,
+            Self::Uint(ty) => ty.bits() / 8,
+            Self::Float(FloatingPointTypes::FP16) => 2,
+            Self::Float(FloatingPointTypes::FP32) => 4,
+            Self::Float(FloatingPointTypes::FP64) => 8,
+            Self::Bool => 1,
+            Self::Char => 4,
+            Self::Pointer | Self::Function => 8,
+        }
+    }
+}
+
+impl From<NumericType> for ValueType {
+    fn from(ty: NumericType) -> Self {
+        match ty {
+            NumericType::Int(ty) => Self::Int(ty),
+            NumericType::Uint(ty) => Self::Uint(ty),
+            NumericType::Float(ty) => Self::Float(ty),
+        }
+    }
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(ty) => write!(f, "{}", ty),
+            Self::Uint(ty) => write!(f, "{}", ty),
+            Self::Float(ty) => write!(f, "{}", ty),
+            Self::Bool => write!(f, "bool"),
+            Self::Char => write!(f, "char"),
+            Self::Pointer => write!(f, "ptr"),
+            Self::Function => write!(f, "fn"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Int(SignedInteger),
+    Uint(UnsignedInteger),
+    Float(FloatingPoint),
+    Bool(bool),
+    Char(char),
+    // loaded as a pointer to its UTF-8 bytes
+    String(&'static str),
+    // the size and alignment of the concrete type followed by the function indices of a trait's
+    // methods in declaration order, loaded as a pointer to them stored as 8 byte slots
+    VTable { size: u64, align: u64, methods: Vec<u32> },
+}
+
+impl Constant {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Int(v) => ValueType::Int(v.base_type),
+            Self::Uint(v) => ValueType::Uint(v.base_type),
+            Self::Float(v) => ValueType::Float(v.base_type),
+            Self::Bool(_) => ValueType::Bool,
+            Self::Char(_) => ValueType::Char,
+            Self::String(_) | Self::VTable { .. } => ValueType::Pointer,
+        }
+    }
+
+    // like `==`, but floats compare by their bits so that `0.0` and `-0.0` stay apart and NaN matches itself
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => a.base_type == b.base_type && a.value.to_bits() == b.value.to_bits(),
+            _ => self == other,
+        }
+    }
+}
+
+impl From<ConstValue> for Constant {
+    fn from(value: ConstValue) -> Self {
+        match value {
+            ConstValue::Int(v) => Self::Int(v),
+            ConstValue::Uint(v) => Self::Uint(v),
+            ConstValue::Fp(v) => Self::Float(v),
+            ConstValue::Bool(b) => Self::Bool(b),
+            ConstValue::Char(c) => Self::Char(c),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(v) => write!(f, "{}{}", v.value, v.base_type),
+            Self::Uint(v) => write!(f, "{}{}", v.value, v.base_type),
+            Self::Float(v) => write!(f, "{:?}{}", v.value, v.base_type),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Char(c) => write!(f, "{:?}", c),
+            Self::String(s) => write!(f, "{:?}", s),
+            Self::VTable { size, align, methods } => write!(f, "vtable {} {} [{}]", size, align, methods.iter().map(|method| method.to_string()).collect::<Vec<String>>().join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConstantPool {
+    constants: Vec<Constant>,
+}
+
+impl ConstantPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // the index of `constant`, which is only added if the pool does not hold it yet
+    pub fn add(&mut self, constant: Constant) -> u32 {
+        if let Some(index) = self.constants.iter().position(|other| other.is_same(&constant)) {
+            return index as u32;
+        }
+
+        self.constants.push(constant);
+
+        (self.constants.len() - 1) as u32
+    }
+
+    // appends `constant` even if the pool holds it already, for pools read back with their indices as written
+    pub fn push(&mut self, constant: Constant) -> u32 {
+        self.constants.push(constant);
+
+        (self.constants.len() - 1) as u32
+    }
+
+    pub fn get(&self, index: u32) -> Option<&Constant> {
+        self.constants.get(index as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.constants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Constant> {
+        self.constants.iter()
+    }
+}
+
+// a function's instructions, each with the span of the source it was compiled from
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Chunk {
+    code: Vec<Instruction>,
+    spans: Vec<Span>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // appends `instruction` and returns its index, which is what jumps refer to
+    pub fn push(&mut self, instruction: Instruction, span: Span) -> usize {
+        self.code.push(instruction);
+        self.spans.push(span);
+
+        self.code.len() - 1
+    }
+
+    // points the jump at `at`, emitted before its target was known, at `target`
+    pub fn patch_jump(&mut self, at: usize, target: usize) {
+        let target = target as u32;
+
+        self.code[at] = match self.code[at] {
+            Instruction::Jump(_) => Instruction::Jump(target),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+            Instruction::JumpIfTrue(_) => Instruction::JumpIfTrue(target),
+            other => panic!("cannot patch `{}` at {}, it is not a jump", other, at),
+        };
+    }
+
+    // puts `prologue` before the code, moving every jump target along with the instruction it points at
+    pub fn prepend(&mut self, prologue: Chunk) {
+        let shift = prologue.len();
+
+        for at in 0 .. self.code.len() {
+            if let Some(target) = self.code[at].jump_target() {
+                self.patch_jump(at, target as usize + shift);
+            }
+        }
+
+        self.code.splice(0 .. 0, prologue.code);
+        self.spans.splice(0 .. 0, prologue.spans);
+    }
+
+    pub fn code(&self) -> &[Instruction] {
+        &self.code
+    }
+
+    pub fn get(&self, index: usize) -> Option<Instruction> {
+        self.code.get(index).copied()
+    }
+
+    pub fn span_at(&self, index: usize) -> Span {
+        self.spans[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Instruction, Span)> + '_ {
+        self.code.iter().copied().zip(self.spans.iter().copied())
+    }
+}
+
+// a local slot and the name it has in the source, kept for debug info
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Local {
+    pub name: &'static str,
+    pub ty: ValueType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: &'static str,
+    pub params: Vec<ValueType>,
+    pub ret: Option<ValueType>,
+    // every local slot, the parameters first
+    pub locals: Vec<Local>,
+    pub chunk: Chunk,
+}
+
+impl Function {
+    pub fn new(name: &'static str, params: Vec<ValueType>, ret: Option<ValueType>) -> Self {
+        Self {
+            name,
+            params,
+            ret,
+            locals: vec![],
+            chunk: Chunk::new(),
+        }
+    }
+}
+
+// everything a program needs to run: the constants its instructions refer to and its functions
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Program {
+    pub constants: ConstantPool,
+    pub functions: Vec<Function>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_function(&mut self, function: Function) -> u32 {
+        self.functions.push(function);
+
+        (self.functions.len() - 1) as u32
+    }
+
+    pub fn function_index(&self, name: &str) -> Option<u32> {
+        self.functions.iter().position(|function| function.name == name).map(|index| index as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    #[test]
+    fn jumps_are_patched_and_spans_kept() {
+        let mut chunk = Chunk::new();
+
+        chunk.push(Instruction::Load(0), Span::new(0, 0));
+        let jump = chunk.push(Instruction::JumpIfFalse(0), Span::new(0, 4));
+        chunk.push(Instruction::Const(0), Span::new(8, 9));
+        chunk.push(Instruction::Return, Span::new(1, 9));
+        let end = chunk.push(Instruction::ReturnVoid, Span::new(1, 10));
+
+        chunk.patch_jump(jump, end);
+
+        assert_eq!(chunk.get(jump), Some(Instruction::JumpIfFalse(4)));
+        assert_eq!(chunk.span_at(2), Span::new(8, 9));
+        assert_eq!(chunk.len(), 5);
+    }
+
+    #[test]
+    fn constants_are_deduplicated() {
+        let mut pool = ConstantPool::new();
+
+        let one = pool.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int32, 1)));
+        let zero = pool.add(Constant::Float(FloatingPoint::new(FloatingPointTypes::FP64, 0.0)));
+        let negative_zero = pool.add(Constant::Float(FloatingPoint::new(FloatingPointTypes::FP64, -0.0)));
+
+        assert_eq!(pool.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int32, 1))), one);
+        assert_ne!(pool.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int64, 1))), one);
+        assert_ne!(zero, negative_zero);
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    fn instructions_display_with_their_operands() {
+        let int = NumericType::Int(SignedIntegerTypes::Int32);
+        let half = ValueType::Float(FloatingPointTypes::FP16);
+
+        assert_eq!(Instruction::Add(int).to_string(), "add i32");
+        assert_eq!(Instruction::Cast(ValueType::from(int), half).to_string(), "cast i32 f16");
+        assert_eq!(Instruction::JumpIfTrue(12).to_string(), "jump_if_true 12");
+        assert_eq!(Instruction::Read(ValueType::Pointer).to_string(), "read ptr");
+        assert_eq!(Constant::String("hi\n").to_string(), "\"hi\\n\"");
+    }
+}
@@ -0,0 +1,441 @@
+use super::{ * };
+use crate::{ error::{ * }, vm::{ value::{ Value } } };
+
+type Verified<T> = std::result::Result<T, Error>;
+
+// checks that every function of `program` can run without the interpreter finding its own code
+// malformed: every index names something that exists, and along every path the operand stack holds
+// what each instruction takes, the same at every instruction however it is reached, and locals are
+// written with values of their declared type before they are read
+pub fn verify(program: &Program) -> Verified<()> {
+    for function in &program.functions {
+        Verifier::new(program, function).verify()?;
+    }
+
+    Ok(())
+}
+
+// whether a local slot is known to be written at an instruction, over all the paths that lead there
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Slot {
+    Unwritten,
+    Written,
+}
+
+impl Slot {
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Written, Self::Written) => Self::Written,
+            _ => Self::Unwritten,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    stack: Vec<ValueType>,
+    slots: Vec<Slot>,
+}
+
+struct Verifier<'a> {
+    program: &'a Program,
+    function: &'a Function,
+}
+
+impl<'a> Verifier<'a> {
+    fn new(program: &'a Program, function: &'a Function) -> Self {
+        Self {
+            program,
+            function,
+        }
+    }
+
+    fn verify(&self) -> Verified<()> {
+        let chunk = &self.function.chunk;
+
+        self.check_references()?;
+
+        // the interpreter gives a call as many slots as the function has locals or parameters,
+        // the parameters holding the arguments
+        let slots = self.function.locals.len().max(self.function.params.len());
+        let mut entry = State {
+            stack: vec![],
+            slots: vec![ Slot::Unwritten; slots ],
+        };
+
+        for slot in entry.slots.iter_mut().take(self.function.params.len()) {
+            *slot = Slot::Written;
+        }
+
+        if chunk.is_empty() {
+            return Err(Error::new(0, 0, ErrorType::InternalError, &format!("`{}` has no code", self.function.name)));
+        }
+
+        // the state on entry to every instruction reached so far, and those whose state changed
+        let mut states: Vec<Option<State>> = vec![ None; chunk.len() ];
+        let mut pending = vec![ 0 ];
+
+        states[0] = Some(entry);
+
+        while let Some(at) = pending.pop() {
+            let mut state = states[at].clone().unwrap();
+            let instruction = chunk.code()[at];
+            let (falls_through, jump) = self.step(at, instruction, &mut state)?;
+
+            if falls_through && at + 1 == chunk.len() {
+                return Err(self.error(at, "is the last instruction, but execution can continue past it"));
+            }
+
+            let successors = falls_through.then_some(at + 1).into_iter().chain(jump.map(|target| target as usize));
+
+            for next in successors {
+                match &mut states[next] {
+                    None => {
+                        states[next] = Some(state.clone());
+                        pending.push(next);
+                    },
+
+                    Some(existing) => {
+                        if existing.stack != state.stack {
+                            return Err(self.error(at, &format!("leaves {} on the stack for instruction {}, which is also reached with {}", describe(&state.stack), next, describe(&existing.stack))));
+                        }
+
+                        let slots: Vec<Slot> = existing.slots.iter().zip(&state.slots).map(|(a, b)| a.merge(*b)).collect();
+
+                        if slots != existing.slots {
+                            existing.slots = slots;
+                            pending.push(next);
+                        }
+                    },
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // every index any instruction holds, reachable or not, names something that exists
+    fn check_references(&self) -> Verified<()> {
+        let functions = self.program.functions.len();
+        let slots = self.function.locals.len().max(self.function.params.len());
+
+        for (index, constant) in self.program.constants.iter().enumerate() {
+            if let Constant::VTable { methods, .. } = constant {
+                if let Some(method) = methods.iter().find(|method| **method as usize >= functions) {
+                    return Err(Error::new(0, 0, ErrorType::InternalError, &format!("the vtable constant #{} refers to function #{}, but there are {} functions", index, method, functions)));
+                }
+            }
+        }
+
+        for (at, instruction) in self.function.chunk.code().iter().enumerate() {
+            let dangling = match *instruction {
+                Instruction::Const(index) if self.program.constants.get(index).is_none() => Some(format!("constant #{}", index)),
+                Instruction::Trap(index) if !matches!(self.program.constants.get(index), Some(Constant::String(_))) => Some(format!("string constant #{}", index)),
+                Instruction::Function(index) | Instruction::Call(index) if index as usize >= functions => Some(format!("function #{}", index)),
+                Instruction::Load(slot) | Instruction::Store(slot) if slot as usize >= slots => Some(format!("local #{}", slot)),
+                _ => instruction.jump_target().filter(|target| *target as usize >= self.function.chunk.len()).map(|target| format!("instruction #{}", target)),
+            };
+
+            if let Some(dangling) = dangling {
+                return Err(self.error(at, &format!("refers to {}, which does not exist", dangling)));
+            }
+        }
+
+        Ok(())
+    }
+
+    // applies `instruction` to `state`, returning whether execution may continue with the next
+    // instruction and where else it may jump to
+    fn step(&self, at: usize, instruction: Instruction, state: &mut State) -> Verified<(bool, Option<u32>)> {
+        match instruction {
+            Instruction::Const(index) => state.stack.push(self.program.constants.get(index).unwrap().value_type()),
+            Instruction::Null => state.stack.push(ValueType::Pointer),
+            Instruction::Function(_) => state.stack.push(ValueType::Function),
+
+            Instruction::Pop => {
+                self.pop_any(at, state)?;
+            },
+
+            Instruction::Dup => {
+                let ty = self.pop_any(at, state)?;
+
+                state.stack.extend([ ty, ty ]);
+            },
+
+            Instruction::Load(slot) => match state.slots[slot as usize] {
+                Slot::Written => state.stack.push(self.slot_type(slot)),
+                Slot::Unwritten => return Err(self.error(at, &format!("reads local #{} before it is written", slot))),
+            },
+
+            Instruction::Store(slot) => {
+                self.pop(at, state, self.slot_type(slot))?;
+
+                state.slots[slot as usize] = Slot::Written;
+            },
+
+            Instruction::Add(ty) | Instruction::Sub(ty) | Instruction::Mul(ty) | Instruction::Div(ty) | Instruction::Rem(ty) => {
+                self.pop(at, state, ty.into())?;
+                self.pop(at, state, ty.into())?;
+                state.stack.push(ty.into());
+            },
+
+            Instruction::BitAnd(ty) | Instruction::BitOr(ty) => {
+                self.integer(at, ty)?;
+                self.pop(at, state, ty.into())?;
+                self.pop(at, state, ty.into())?;
+                state.stack.push(ty.into());
+            },
+
+            Instruction::Neg(ty) => {
+                self.pop(at, state, ty.into())?;
+                state.stack.push(ty.into());
+            },
+
+            Instruction::BitNot(ty) => {
+                self.integer(at, ty)?;
+                self.pop(at, state, ty.into())?;
+                state.stack.push(ty.into());
+            },
+
+            Instruction::Not => {
+                self.pop(at, state, ValueType::Bool)?;
+                state.stack.push(ValueType::Bool);
+            },
+
+            Instruction::Eq(ty) | Instruction::Ne(ty) => {
+                self.pop(at, state, ty)?;
+                self.pop(at, state, ty)?;
+                state.stack.push(ValueType::Bool);
+            },
+
+            Instruction::Lt(ty) | Instruction::Le(ty) | Instruction::Gt(ty) | Instruction::Ge(ty) => {
+                self.pop(at, state, ty.into())?;
+                self.pop(at, state, ty.into())?;
+                state.stack.push(ValueType::Bool);
+            },
+
+            Instruction::Cast(from, to) => {
+                if !Value::can_cast(from, to) {
+                    return Err(self.error(at, &format!("cannot convert a {} to a {}", from, to)));
+                }
+
+                self.pop(at, state, from)?;
+                state.stack.push(to);
+            },
+
+            Instruction::Jump(target) => return Ok((false, Some(target))),
+
+            Instruction::JumpIfFalse(target) | Instruction::JumpIfTrue(target) => {
+                self.pop(at, state, ValueType::Bool)?;
+
+                return Ok((true, Some(target)));
+            },
+
+            Instruction::Call(index) => {
+                let callee = &self.program.functions[index as usize];
+
+                for param in callee.params.iter().rev() {
+                    self.pop(at, state, *param)?;
+                }
+
+                state.stack.extend(callee.ret);
+            },
+
+            // the arguments are checked against the callee when it is entered, and its return type
+            // against the instruction's
+            Instruction::CallIndirect(argc, ret) => {
+                for _ in 0 .. argc {
+                    self.pop_any(at, state)?;
+                }
+
+                self.pop(at, state, ValueType::Function)?;
+                state.stack.extend(ret);
+            },
+
+            Instruction::Return => {
+                let Some(ret) = self.function.ret else {
+                    return Err(self.error(at, &format!("returns a value from `{}`, which returns nothing", self.function.name)));
+                };
+
+                self.pop(at, state, ret)?;
+
+                return Ok((false, None));
+            },
+
+            Instruction::ReturnVoid => {
+                if let Some(ret) = self.function.ret {
+                    return Err(self.error(at, &format!("returns nothing from `{}`, which returns a {}", self.function.name, ret)));
+                }
+
+                return Ok((false, None));
+            },
+
+            Instruction::StackAlloc(_) | Instruction::New(_) | Instruction::Env(_) => state.stack.push(ValueType::Pointer),
+
+            Instruction::Delete => {
+                self.pop(at, state, ValueType::Pointer)?;
+            },
+
+            Instruction::Offset(_) | Instruction::NonNull => {
+                self.pop(at, state, ValueType::Pointer)?;
+                state.stack.push(ValueType::Pointer);
+            },
+
+            Instruction::Index(_) => {
+                let index = self.pop_any(at, state)?;
+
+                if !matches!(index, ValueType::Int(_) | ValueType::Uint(_)) {
+                    return Err(self.error(at, &format!("expects an integer index, found a {}", index)));
+                }
+
+                self.pop(at, state, ValueType::Pointer)?;
+                state.stack.push(ValueType::Pointer);
+            },
+
+            Instruction::Read(ty) => {
+                self.pop(at, state, ValueType::Pointer)?;
+                state.stack.push(ty);
+            },
+
+            Instruction::Write(ty) => {
+                self.pop(at, state, ty)?;
+                self.pop(at, state, ValueType::Pointer)?;
+            },
+
+            Instruction::Copy(_) => {
+                self.pop(at, state, ValueType::Pointer)?;
+                self.pop(at, state, ValueType::Pointer)?;
+            },
+
+            Instruction::Trap(_) => return Ok((false, None)),
+        }
+
+        Ok((true, None))
+    }
+
+    // the declared type of a slot; a parameter's is the one in the signature
+    fn slot_type(&self, slot: u16) -> ValueType {
+        match self.function.params.get(slot as usize) {
+            Some(param) => *param,
+            None => self.function.locals[slot as usize].ty,
+        }
+    }
+
+    fn pop_any(&self, at: usize, state: &mut State) -> Verified<ValueType> {
+        state.stack.pop().ok_or_else(|| self.error(at, "finds too few values on the operand stack"))
+    }
+
+    fn pop(&self, at: usize, state: &mut State, expected: ValueType) -> Verified<()> {
+        match self.pop_any(at, state)? {
+            ty if ty == expected => Ok(()),
+            ty => Err(self.error(at, &format!("expects a {} operand, found a {}", expected, ty))),
+        }
+    }
+
+    fn integer(&self, at: usize, ty: NumericType) -> Verified<()> {
+        match ty.is_integer() {
+            true => Ok(()),
+            false => Err(self.error(at, "needs an integer type")),
+        }
+    }
+
+    fn error(&self, at: usize, message: &str) -> Error {
+        let chunk = &self.function.chunk;
+
+        Error::from_span(chunk.span_at(at), ErrorType::InternalError, &format!("`{}` at {} in `{}` {}", chunk.code()[at], at, self.function.name, message))
+    }
+}
+
+fn describe(stack: &[ValueType]) -> String {
+    format!("[{}]", stack.iter().map(|ty| ty.to_string()).collect::<Vec<String>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ bytecode::{ asm::{ assemble } } };
+
+    fn check(text: &str) -> std::result::Result<(), String> {
+        verify(&assemble(text).unwrap()).map_err(|error| error.get_message())
+    }
+
+    #[test]
+    fn well_formed_code_passes() {
+        assert_eq!(check("
+            fn max(a: i32, b: i32) -> i32
+                local larger: i32
+                load a
+                store larger
+                load b
+                load a
+                gt i32
+                jump_if_false done
+                load b
+                store larger
+            done:
+                load larger
+                ret
+        "), Ok(()));
+    }
+
+    #[test]
+    fn stacks_must_agree_where_paths_meet() {
+        assert_eq!(check("
+            fn f(c: bool) -> i32
+                const 1i32
+                load c
+                jump_if_true end
+                const 2i32
+            end:
+                ret
+        "), Err("`const 1` at 3 in `f` leaves [i32, i32] on the stack for instruction 4, which is also reached with [i32]".to_owned()));
+
+        assert_eq!(check("
+            fn f() -> i64
+                const 1i32
+                ret
+        "), Err("`ret` at 1 in `f` expects a i64 operand, found a i32".to_owned()));
+    }
+
+    #[test]
+    fn locals_are_written_on_every_path_before_they_are_read() {
+        assert_eq!(check("
+            fn f(c: bool) -> i32
+                local x: i32
+                load c
+                jump_if_false skip
+                const 1i32
+                store x
+            skip:
+                load x
+                ret
+        "), Err("`load 1` at 4 in `f` reads local #1 before it is written".to_owned()));
+
+        assert_eq!(check("
+            fn f()
+                jump nowhere_else
+            nowhere_else:
+                const 1i32
+                pop
+        "), Err("`pop` at 2 in `f` is the last instruction, but execution can continue past it".to_owned()));
+    }
+
+    #[test]
+    fn locals_keep_their_declared_type_and_casts_must_be_possible() {
+        assert_eq!(check("
+            fn f() -> i32
+                local x: i32
+                const 1.5f64
+                store x
+                load x
+                ret
+        "), Err("`store 0` at 1 in `f` expects a i32 operand, found a f64".to_owned()));
+
+        assert_eq!(check("
+            fn f(x: f64) -> ptr
+                load x
+                cast f64 ptr
+                ret
+        "), Err("`cast f64 ptr` at 1 in `f` cannot convert a f64 to a ptr".to_owned()));
+    }
+}
@@ -0,0 +1,395 @@
+use super::{ * };
+use crate::{ lexer::{ Spanned } };
+
+impl Compiler<'_> {
+    // a named function is called directly, anything else of function type through its value
+    pub(super) fn compile_call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> Compiled<()> {
+        if let ExprKind::Identifier(name) = callee.node {
+            if self.checker.lookup_variable(name).is_none() {
+                // a generic function is called through the instance its arguments pick
+                let (symbol, sig) = match self.checker.is_generic_function(name) {
+                    true => self.checker.infer_generic_call(name, args, span).map(|(instance, sig)| (instance.symbol(), sig))?,
+                    false => (name.to_owned(), self.checker.function_signature(name).cloned().ok_or_else(|| {
+                        Error::from_span(callee.span, ErrorType::InternalError, &format!("function `{}` has not been checked", name))
+                    })?),
+                };
+
+                let index = self.function_index(&symbol, callee.span)?;
+
+                self.compile_out(sig.ret, span)?;
+                self.compile_args(&sig.params, args)?;
+                self.emit(Instruction::Call(index), span);
+
+                return Ok(());
+            }
+        }
+
+        let function = match self.compile_expr(callee, None)? {
+            Type::Complex(ComplexType::Function(function)) => function,
+            ty => return Err(Error::from_span(callee.span, ErrorType::InternalError, &format!("cannot call a value of type `{}`", ty))),
+        };
+
+        let argc = match function.convention {
+            // a closure is the address of its code followed by that of its environment
+            CallingConvention::Closure => {
+                let closure = self.temporary(ValueType::Pointer)?;
+
+                self.emit(Instruction::Store(closure), callee.span);
+                self.emit(Instruction::Load(closure), callee.span);
+                self.emit(Instruction::Read(ValueType::Function), callee.span);
+
+                let out = self.compile_out(*function.ret, span)?;
+
+                self.emit(Instruction::Load(closure), callee.span);
+                self.emit(Instruction::Offset(8), callee.span);
+                self.emit(Instruction::Read(ValueType::Pointer), callee.span);
+
+                out + 1 + self.compile_args(function.params, args)?
+            },
+
+            _ => self.compile_out(*function.ret, span)? + self.compile_args(function.params, args)?,
+        };
+
+        let ret = self.repr(*function.ret, span)?.value_type();
+
+        self.emit(Instruction::CallIndirect(argc, ret), span);
+
+        Ok(())
+    }
+
+    pub(super) fn compile_method_call(&mut self, receiver: &Expr, method: Spanned<&'static str>, args: &[Expr], span: Span) -> Compiled<()> {
+        let receiver_type = self.checker.check_expr(receiver, None)?;
+        let resolved = self.checker.resolve_method(receiver_type, method.node, method.span)?;
+        let self_param = resolved.sig.params[0];
+
+        match resolved.dispatch {
+            // the method is loaded from the vtable and called with the object's data pointer as `self`
+            Dispatch::Dynamic(slot) => {
+                let object = self.temporary(ValueType::Pointer)?;
+
+                self.compile_expr(receiver, None)?;
+                self.emit(Instruction::Store(object), receiver.span);
+                self.emit(Instruction::Load(object), method.span);
+                self.emit(Instruction::Offset(8), method.span);
+                self.emit(Instruction::Read(ValueType::Pointer), method.span);
+                self.emit(Instruction::Offset(slot as u32 * 8), method.span);
+                self.emit(Instruction::Read(ValueType::Function), method.span);
+
+                let out = self.compile_out(resolved.sig.ret, span)?;
+
+                self.emit(Instruction::Load(object), receiver.span);
+                self.emit(Instruction::Read(ValueType::Pointer), receiver.span);
+
+                let argc = out + 1 + self.compile_args(&resolved.sig.params[1 ..], args)?;
+                let ret = self.repr(resolved.sig.ret, span)?.value_type();
+
+                self.emit(Instruction::CallIndirect(argc, ret), span);
+            },
+
+            Dispatch::Static => {
+                if let Type::Simple(SimpleType::Generic(_)) = resolved.for_type {
+                    return Err(unsupported("methods of type parameters are", method.span));
+                }
+
+                let index = self.function_index(&resolved.symbol(), method.span)?;
+
+                self.compile_out(resolved.sig.ret, span)?;
+                self.compile_receiver(receiver, receiver_type, self_param)?;
+                self.compile_args(&resolved.sig.params[1 ..], args)?;
+                self.emit(Instruction::Call(index), span);
+            },
+        }
+
+        Ok(())
+    }
+
+    // the receiver is passed as is, by address or through its pointer, like the checker allows
+    fn compile_receiver(&mut self, receiver: &Expr, receiver_type: Type, self_param: Type) -> Compiled<()> {
+        if self.checker.can_coerce(receiver_type, self_param) {
+            return self.compile_coerced(receiver, self_param);
+        }
+
+        if receiver_type.pointer_to() == Some(self_param) || receiver_type.pointer_to_mut() == Some(self_param) {
+            self.compile_place(receiver)?;
+
+            return Ok(());
+        }
+
+        self.compile_expr(receiver, None)?;
+        self.read(self_param, receiver.span)
+    }
+
+    // reserves the memory an aggregate result is written to, which the callee gets as its first argument
+    fn compile_out(&mut self, ret: Type, span: Span) -> Compiled<u8> {
+        match self.repr(ret, span)? {
+            Repr::Aggregate(size) => {
+                let memory = self.stack_block(size, span)?;
+
+                self.emit(Instruction::Load(memory), span);
+
+                Ok(1)
+            },
+
+            _ => Ok(0),
+        }
+    }
+
+    // the extra arguments of a variadic call are passed as they are
+    fn compile_args(&mut self, params: &[Type], args: &[Expr]) -> Compiled<u8> {
+        for (index, arg) in args.iter().enumerate() {
+            match params.get(index) {
+                Some(param) => self.compile_coerced(arg, *param)?,
+                None => self.compile_expr(arg, None).map(|_| ())?,
+            }
+        }
+
+        u8::try_from(args.len()).map_err(|_| unsupported("calls with more than 255 arguments are", args[0].span))
+    }
+
+    // the body becomes a function of its own; the captured values, or the addresses of the variables
+    // captured by reference, are copied into an environment on the heap
+    pub(super) fn compile_closure(&mut self, closure: &ClosureExpr, span: Span) -> Compiled<()> {
+        let info = self.checker.closure_at(span).cloned().ok_or_else(|| {
+            Error::from_span(span, ErrorType::InternalError, &format!("the closure at {} has not been checked", span))
+        })?;
+
+        let name = format!("{}.closure@{}", self.function().name, span);
+        let index = self.declare(&name, &info.sig, span)?;
+
+        self.compile_function(index, &closure.params, &closure.body, &info.sig, Some(&info), span)?;
+
+        let env = self.temporary(ValueType::Pointer)?;
+
+        match info.captures.is_empty() {
+            true => self.emit(Instruction::Null, span),
+            false => self.emit(Instruction::Env(info.environment.size as u32), span),
+        };
+
+        self.emit(Instruction::Store(env), span);
+
+        for (capture, offset) in info.captures.iter().zip(&info.offsets) {
+            let slot_type = capture.slot_type().expect("the checker laid out every capture");
+
+            self.emit(Instruction::Load(env), span);
+            self.emit(Instruction::Offset(*offset as u32), span);
+
+            match capture.mode {
+                CaptureMode::Value => self.load_variable(capture.name, capture.ty, span)?,
+                CaptureMode::Ref => self.variable_address(capture.name, span)?,
+            }
+
+            self.write(slot_type, span)?;
+        }
+
+        self.closure_object(index, env, span)
+    }
+
+    // a plain function used as a closure is called through an adapter, which finds it in its environment
+    pub(super) fn compile_adapter(&mut self, function: FunctionType, span: Span) -> Compiled<()> {
+        let sig = FnSignature {
+            convention: CallingConvention::Closure,
+            ..FnSignature::from(function)
+        };
+
+        let name = format!("<{} as closure>", FnSignature::from(function));
+        let adapter = match self.indices.get(&name) {
+            Some(index) => *index,
+            None => self.compile_adapter_body(&name, &sig, span)?,
+        };
+
+        let value = self.temporary(ValueType::Function)?;
+        let env = self.temporary(ValueType::Pointer)?;
+
+        self.emit(Instruction::Store(value), span);
+        self.emit(Instruction::Env(8), span);
+        self.emit(Instruction::Dup, span);
+        self.emit(Instruction::Store(env), span);
+        self.emit(Instruction::Load(value), span);
+        self.emit(Instruction::Write(ValueType::Function), span);
+
+        self.closure_object(adapter, env, span)
+    }
+
+    fn compile_adapter_body(&mut self, name: &str, sig: &FnSignature, span: Span) -> Compiled<u32> {
+        let index = self.declare(name, sig, span)?;
+        let has_out = matches!(self.repr(sig.ret, span)?, Repr::Aggregate(_));
+        let function = &mut self.program.functions[index as usize];
+        let env = has_out as u16;
+
+        function.locals = function.params.iter().enumerate().map(|(slot, ty)| Local {
+            name: match slot as u16 {
+                slot if slot == env => "$env",
+                _ if has_out && slot == 0 => "$ret",
+                _ => "$arg",
+            },
+            ty: *ty,
+        }).collect();
+
+        let params = function.params.len() as u16;
+        let chunk = &mut function.chunk;
+
+        chunk.push(Instruction::Load(env), span);
+        chunk.push(Instruction::Read(ValueType::Function), span);
+
+        // every argument but the environment is passed on
+        for slot in (0 .. params).filter(|slot| *slot != env) {
+            chunk.push(Instruction::Load(slot), span);
+        }
+
+        chunk.push(Instruction::CallIndirect((params - 1) as u8, function.ret), span);
+
+        match function.ret {
+            Some(_) => chunk.push(Instruction::Return, span),
+            None => chunk.push(Instruction::ReturnVoid, span),
+        };
+
+        Ok(index)
+    }
+
+    // a closure is the function implementing it followed by its environment
+    fn closure_object(&mut self, code: u32, env: u16, span: Span) -> Compiled<()> {
+        let object = self.stack_block(16, span)?;
+
+        self.emit(Instruction::Load(object), span);
+        self.emit(Instruction::Function(code), span);
+        self.emit(Instruction::Write(ValueType::Function), span);
+        self.emit(Instruction::Load(object), span);
+        self.emit(Instruction::Offset(8), span);
+        self.emit(Instruction::Load(env), span);
+        self.emit(Instruction::Write(ValueType::Pointer), span);
+        self.emit(Instruction::Load(object), span);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, compiler::pipeline::{ compile_module }, lexer::{ Spanned }, vm::{ Vm, value::{ Value } } };
+
+    fn generic_function(name: &'static str, bound: Option<&'static str>, params: Vec<Param>, ret: TypeExpr, value: Expr) -> Item {
+        let bounds = bound.into_iter().map(|bound| TraitRef { name: Spanned::new(0, 0, bound), args: vec![] }).collect();
+
+        Item::Fn(FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, name),
+            generics: vec![ GenericParam { name: Spanned::new(0, 0, "T"), bounds } ],
+            params,
+            ret: Some(ret),
+            body: Some(vec![ stmt(StmtKind::Return(Some(value))) ]),
+        })
+    }
+
+    #[test]
+    fn closures_capture_by_value_and_by_reference() {
+        // fn counter() -> i32 { let mut total = 0; let step = 5; let add = fn[&total](x: i32) { total = total + x + step; }; add(1); add(2); return total; }
+        let closure = ClosureExpr {
+            by_ref: vec![ Spanned::new(0, 0, "total") ],
+            params: vec![ param("x", i32_type()) ],
+            ret: None,
+            body: vec![ stmt(StmtKind::Assign(ident("total"), binary(binary(ident("total"), BinaryOp::Add, ident("x")), BinaryOp::Add, ident("step")))) ],
+        };
+
+        let counter = function("counter", vec![], Some(i32_type()), vec![
+            stmt(StmtKind::Let("total", true, None, int(0))),
+            stmt(StmtKind::Let("step", false, None, int(5))),
+            stmt(StmtKind::Let("add", false, None, Spanned::new(10, 20, ExprKind::Closure(Box::new(closure))))),
+            stmt(StmtKind::Expr(call("add", vec![ int(1) ]))),
+            stmt(StmtKind::Expr(call("add", vec![ int(2) ]))),
+            stmt(StmtKind::Return(Some(ident("total")))),
+        ]);
+
+        let program = compile_module("main", &[ counter ]).unwrap();
+
+        assert_eq!(Vm::new(&program).call("counter", &[]).unwrap(), Some(Value::Int(13)));
+    }
+
+    #[test]
+    fn closure_environments_are_reported_with_the_leaks() {
+        // fn scaled(x: i32) -> i32 { let k = 3; let f = fn(y: i32) -> i32 { return y * k; }; return f(x); }
+        let closure = ClosureExpr {
+            by_ref: vec![],
+            params: vec![ param("y", i32_type()) ],
+            ret: Some(i32_type()),
+            body: vec![ stmt(StmtKind::Return(Some(binary(ident("y"), BinaryOp::Mul, ident("k"))))) ],
+        };
+
+        let scaled = function("scaled", vec![ param("x", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Let("k", false, None, int(3))),
+            stmt(StmtKind::Let("f", false, None, Spanned::new(10, 20, ExprKind::Closure(Box::new(closure))))),
+            stmt(StmtKind::Return(Some(call("f", vec![ ident("x") ])))),
+        ]);
+
+        let program = compile_module("main", &[ scaled ]).unwrap();
+        let mut vm = Vm::new(&program).with_debug_heap();
+
+        assert_eq!(vm.call("scaled", &[ Value::Int(4) ]).unwrap(), Some(Value::Int(12)));
+        assert_eq!(vm.leak_report().unwrap(), "leaked 4 bytes in 1 block\n    10:20: 4 bytes in 1 closure environment");
+
+        // only a debug heap keeps track of them
+        let mut vm = Vm::new(&program);
+
+        vm.call("scaled", &[ Value::Int(4) ]).unwrap();
+
+        assert_eq!(vm.leak_report(), None);
+    }
+
+    #[test]
+    fn generic_calls_run_the_instance_their_arguments_pick() {
+        // trait Twice { abstract fn twice(self: *Self) -> i32; }  impl Twice for i32 { fn twice(self: *Self) -> i32 { return *self * 2; } }
+        let self_param = param("self", Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::SelfType)))));
+        let twice = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "twice"),
+            generics: vec![],
+            params: vec![ self_param ],
+            ret: Some(i32_type()),
+            body: Some(vec![ stmt(StmtKind::Return(Some(binary(deref(ident("self")), BinaryOp::Mul, int(2))))) ]),
+        };
+        let trait_decl = Item::Trait(TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Twice"),
+            generics: vec![],
+            methods: vec![ TraitMethod { is_abstract: true, decl: FnDecl { body: None, ..twice.clone() } } ],
+        });
+        let impl_decl = Item::Impl(ImplDecl { trait_name: Spanned::new(0, 0, "Twice"), trait_args: vec![], for_type: i32_type(), methods: vec![ twice ] });
+
+        // fn pick<T>(a: T, b: T) -> T { return b; }  fn doubled<T: Twice>(x: *T) -> i32 { return x.twice(); }
+        let pick = generic_function("pick", None, vec![ param("a", named("T")), param("b", named("T")) ], named("T"), ident("b"));
+        let pointer = Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(named("T"))));
+        let method_call = Spanned::new(0, 0, ExprKind::MethodCall(Box::new(ident("x")), Spanned::new(0, 0, "twice"), vec![]));
+        let doubled = generic_function("doubled", Some("Twice"), vec![ param("x", pointer) ], i32_type(), method_call);
+
+        // fn main() -> i32 { let n = pick(1, 20); return doubled(&n) + pick(1, 2); }
+        let address = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::AddressOf, Box::new(ident("n"))));
+        let main = function("main", vec![], Some(i32_type()), vec![
+            stmt(StmtKind::Let("n", false, None, call("pick", vec![ int(1), int(20) ]))),
+            stmt(StmtKind::Return(Some(binary(call("doubled", vec![ address ]), BinaryOp::Add, call("pick", vec![ int(1), int(2) ]))))),
+        ]);
+
+        let program = compile_module("main", &[ trait_decl, impl_decl, pick, doubled, main ]).unwrap();
+        let mut names: Vec<&str> = program.functions.iter().map(|function| function.name).collect();
+
+        names.sort();
+
+        assert_eq!(names, vec![ "<i32 as Twice>.twice", "doubled<i32>", "main", "pick<i32>" ]);
+        assert_eq!(Vm::new(&program).call("main", &[]).unwrap(), Some(Value::Int(42)));
+    }
+
+    #[test]
+    fn functions_are_called_by_value() {
+        // fn twice(x: i32) -> i32 { return x * 2; }  fn apply(x: i32) -> i32 { let f = twice; return f(f(x)); }
+        let twice = function("twice", vec![ param("x", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Return(Some(binary(ident("x"), BinaryOp::Mul, int(2))))),
+        ]);
+
+        let apply = function("apply", vec![ param("x", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Let("f", false, None, ident("twice"))),
+            stmt(StmtKind::Return(Some(call("f", vec![ call("f", vec![ ident("x") ]) ])))),
+        ]);
+
+        let program = compile_module("main", &[ twice, apply ]).unwrap();
+
+        assert_eq!(Vm::new(&program).call("apply", &[ Value::Int(3) ]).unwrap(), Some(Value::Int(12)));
+    }
+}
@@ -0,0 +1,383 @@
+use super::{ * };
+
+impl Compiler<'_> {
+    // pushes the value of `expr`, or the address of its first byte for an aggregate, and returns its type
+    pub(super) fn compile_expr(&mut self, expr: &Expr, expected: Option<Type>) -> Compiled<Type> {
+        let ty = self.checker.check_expr(expr, expected)?;
+        let span = expr.span;
+
+        match &expr.node {
+            ExprKind::Literal(literal) => self.compile_literal(*literal, ty, false, span)?,
+
+            ExprKind::Identifier(name) => self.compile_identifier(name, ty, span)?,
+
+            // `-128i8` is folded into a single constant
+            ExprKind::Unary(UnaryOp::Negate, operand) if matches!(operand.node, ExprKind::Literal(Literal::Int(..))) => {
+                let ExprKind::Literal(literal) = operand.node else {
+                    unreachable!("matched above")
+                };
+
+                self.compile_literal(literal, ty, true, span)?;
+            },
+
+            ExprKind::Unary(op, operand) => self.compile_unary(*op, operand, ty, expected, span)?,
+
+            ExprKind::Binary(lhs, op, rhs) => self.compile_binary(lhs, *op, rhs, expected, span)?,
+
+            ExprKind::Cast(value, _) => {
+                let from = self.compile_expr(value, None)?;
+
+                self.compile_cast(from, ty, span)?;
+            },
+
+            // zeroed like every other block, sized by the layout of the allocated type
+            ExprKind::New(allocated) => {
+                let allocated = self.checker.resolve_type(allocated)?;
+                let size = self.checker.layout_of(allocated, span)?.size;
+
+                self.emit(Instruction::New(size as u32), span);
+            },
+
+            ExprKind::Sizeof(operand) => {
+                let size = self.checker.eval_sizeof(operand, span)?;
+
+                self.constant(Constant::Uint(UnsignedInteger::new(UnsignedIntegerTypes::Uint64, size)), span);
+            },
+
+            ExprKind::Field(base, field) => {
+                self.compile_field_address(base, field, span)?;
+                self.read(ty, span)?;
+            },
+
+            ExprKind::Variant(_, variant, args) => self.compile_variant(ty, variant, args, span)?,
+
+            ExprKind::Match(scrutinee, arms) => self.compile_match(scrutinee, arms, ty, span)?,
+
+            ExprKind::Call(callee, args) => self.compile_call(callee, args, span)?,
+
+            ExprKind::MethodCall(receiver, method, args) => self.compile_method_call(receiver, *method, args, span)?,
+
+            ExprKind::Closure(closure) => self.compile_closure(closure, span)?,
+
+            ExprKind::Unwrap(pointer) => {
+                self.compile_expr(pointer, expected.map(Typechecker::nullable))?;
+                self.emit(Instruction::NonNull, span);
+            },
+        }
+
+        Ok(ty)
+    }
+
+    // compiles `expr` as a value of type `to`, applying the implicit conversions `can_coerce` allows
+    pub(super) fn compile_coerced(&mut self, expr: &Expr, to: Type) -> Compiled<()> {
+        let from = self.compile_expr(expr, Some(to))?;
+
+        self.coerce(from, to, expr.span)
+    }
+
+    fn coerce(&mut self, from: Type, to: Type, span: Span) -> Compiled<()> {
+        match (from, to) {
+            (Type::Complex(ComplexType::Function(function)), Type::Complex(ComplexType::Function(FunctionType { convention: CallingConvention::Closure, .. }))) if function.convention != CallingConvention::Closure => {
+                self.compile_adapter(function, span)
+            },
+
+            (_, Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::TraitObject(trait_name), .. }))) => match from.pointee() {
+                Some(Type::Simple(SimpleType::TraitObject(_))) | None => Ok(()),
+                Some(pointee) => self.compile_unsize(pointee, trait_name, span),
+            },
+
+            // the other coercions only change what the checker allows
+            _ => Ok(()),
+        }
+    }
+
+    // pushes the address of the memory `expr` denotes and returns its type
+    pub(super) fn compile_place(&mut self, expr: &Expr) -> Compiled<Type> {
+        let span = expr.span;
+
+        match &expr.node {
+            ExprKind::Identifier(name) if self.lookup(name).is_some() => {
+                let ty = self.checker.check_expr(expr, None)?;
+
+                self.variable_address(name, span)?;
+
+                Ok(ty)
+            },
+
+            ExprKind::Unary(UnaryOp::Deref, pointer) => {
+                let ty = self.checker.check_expr(expr, None)?;
+
+                self.compile_expr(pointer, None)?;
+
+                Ok(ty)
+            },
+
+            ExprKind::Field(base, field) => {
+                let ty = self.checker.check_expr(expr, None)?;
+
+                self.compile_field_address(base, field, span)?;
+
+                Ok(ty)
+            },
+
+            // anything else is stored in a temporary, like the result of `&f()`
+            _ => {
+                let ty = self.compile_expr(expr, None)?;
+
+                match self.repr(ty, span)? {
+                    Repr::Scalar(value_type) => {
+                        let value = self.temporary(value_type)?;
+
+                        let memory = self.stack_block(value_type.size(), span)?;
+
+                        self.emit(Instruction::Store(value), span);
+                        self.emit(Instruction::Load(memory), span);
+                        self.emit(Instruction::Dup, span);
+                        self.emit(Instruction::Load(value), span);
+                        self.emit(Instruction::Write(value_type), span);
+                    },
+
+                    Repr::Aggregate(_) => {},
+                    Repr::Void => return Err(Error::from_span(span, ErrorType::TypeError, "cannot take the address of a value of type `void`")),
+                }
+
+                Ok(ty)
+            },
+        }
+    }
+
+    // a struct value is its address already, and a pointer to one points at it
+    fn compile_field_address(&mut self, base: &Expr, field: &str, span: Span) -> Compiled<()> {
+        let name = match self.compile_expr(base, None)? {
+            Type::Simple(SimpleType::UserType(name)) | Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::UserType(name), .. })) => name,
+            ty => return Err(Error::from_span(span, ErrorType::InternalError, &format!("type `{}` has no fields", ty))),
+        };
+
+        let (_, offsets) = self.checker.struct_layout(name, span)?;
+        let index = self.checker.struct_type(name).and_then(|decl| decl.get_field(field)).map(|(index, _)| index).ok_or_else(|| {
+            Error::from_span(span, ErrorType::InternalError, &format!("struct `{}` has no field named `{}`", name, field))
+        })?;
+
+        self.emit(Instruction::Offset(offsets[index] as u32), span);
+
+        Ok(())
+    }
+
+    pub(super) fn compile_literal(&mut self, literal: Literal, ty: Type, is_negated: bool, span: Span) -> Compiled<()> {
+        let constant = match (literal, ty) {
+            (Literal::Int(value, _), Type::Simple(SimpleType::Int(int))) => {
+                let value = if is_negated { -(value as i128) } else { value as i128 };
+
+                Constant::Int(SignedInteger::new(int.base_type, value as i64))
+            },
+
+            (Literal::Int(value, _), Type::Simple(SimpleType::Uint(uint))) => Constant::Uint(UnsignedInteger::new(uint.base_type, value)),
+            (Literal::Float(value, _), Type::Simple(SimpleType::Fp(fp))) => Constant::Float(FloatingPoint::new(fp.base_type, value)),
+            (Literal::Bool(value), _) => Constant::Bool(value),
+            (Literal::Char(value), _) => Constant::Char(value),
+
+            (Literal::Null, _) => {
+                self.emit(Instruction::Null, span);
+
+                return Ok(());
+            },
+
+            // a string is a pointer to its bytes followed by their number
+            (Literal::String(value), _) => {
+                let string = self.stack_block(16, span)?;
+                let length = ValueType::Uint(UnsignedIntegerTypes::Uint64);
+
+                self.emit(Instruction::Load(string), span);
+                self.constant(Constant::String(value), span);
+                self.emit(Instruction::Write(ValueType::Pointer), span);
+                self.emit(Instruction::Load(string), span);
+                self.emit(Instruction::Offset(8), span);
+                self.constant(Constant::Uint(UnsignedInteger::new(UnsignedIntegerTypes::Uint64, value.len() as u64)), span);
+                self.emit(Instruction::Write(length), span);
+                self.emit(Instruction::Load(string), span);
+
+                return Ok(());
+            },
+
+            _ => return Err(Error::from_span(span, ErrorType::InternalError, &format!("literal {:?} cannot have type `{}`", literal, ty))),
+        };
+
+        self.constant(constant, span);
+
+        Ok(())
+    }
+
+    // a named function used as a value decays to a reference to it
+    fn compile_identifier(&mut self, name: &'static str, ty: Type, span: Span) -> Compiled<()> {
+        if self.lookup(name).is_some() {
+            return self.load_variable(name, ty, span);
+        }
+
+        if let Some(value) = self.checker.static_value(name) {
+            self.constant(Constant::from(value), span);
+
+            return Ok(());
+        }
+
+        let index = self.function_index(name, span)?;
+
+        self.emit(Instruction::Function(index), span);
+
+        Ok(())
+    }
+
+    fn compile_unary(&mut self, op: UnaryOp, operand: &Expr, ty: Type, expected: Option<Type>, span: Span) -> Compiled<()> {
+        match op {
+            UnaryOp::Negate => {
+                self.compile_expr(operand, expected)?;
+                self.emit(Instruction::Neg(numeric_type(ty, span)?), span);
+            },
+
+            UnaryOp::Not => {
+                self.compile_expr(operand, expected)?;
+
+                match ty {
+                    Type::Simple(SimpleType::Bool) => self.emit(Instruction::Not, span),
+                    _ => self.emit(Instruction::BitNot(numeric_type(ty, span)?), span),
+                };
+            },
+
+            UnaryOp::Deref => {
+                self.compile_expr(operand, None)?;
+                self.read(ty, span)?;
+            },
+
+            UnaryOp::AddressOf | UnaryOp::AddressOfMut => {
+                self.compile_place(operand)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn compile_binary(&mut self, lhs: &Expr, op: BinaryOp, rhs: &Expr, expected: Option<Type>, span: Span) -> Compiled<()> {
+        let (lhs_type, rhs_type) = self.checker.check_operands(lhs, op, rhs, expected)?;
+
+        // the right operand is only evaluated if the left one does not decide the result
+        if op.is_logical() {
+            self.compile_expr(lhs, Some(lhs_type))?;
+            self.emit(Instruction::Dup, span);
+
+            let decided = match op {
+                BinaryOp::And => self.emit(Instruction::JumpIfFalse(0), span),
+                _ => self.emit(Instruction::JumpIfTrue(0), span),
+            };
+
+            self.emit(Instruction::Pop, span);
+            self.compile_expr(rhs, Some(rhs_type))?;
+            self.patch(decided);
+
+            return Ok(());
+        }
+
+        if op == BinaryOp::Equals || op == BinaryOp::NotEquals {
+            let ty = match self.repr(lhs_type, span)? {
+                Repr::Scalar(ty) => ty,
+                _ => return Err(unsupported(&format!("comparing values of type `{}` is", lhs_type), span)),
+            };
+
+            self.compile_expr(lhs, Some(lhs_type))?;
+            self.compile_expr(rhs, Some(rhs_type))?;
+
+            match op {
+                BinaryOp::Equals => self.emit(Instruction::Eq(ty), span),
+                _ => self.emit(Instruction::Ne(ty), span),
+            };
+
+            return Ok(());
+        }
+
+        // `bool`s and `char`s are promoted to integers for arithmetic and ordering
+        let promoted = match lhs_type {
+            Type::Simple(SimpleType::Bool) => Some((ValueType::Bool, UnsignedIntegerTypes::Uint8)),
+            Type::Simple(SimpleType::Char) => Some((ValueType::Char, UnsignedIntegerTypes::Uint32)),
+            _ => None,
+        };
+
+        let ty = match promoted {
+            Some((_, uint)) => NumericType::Uint(uint),
+            None => numeric_type(lhs_type, span)?,
+        };
+
+        for (operand, operand_type) in [ (lhs, lhs_type), (rhs, rhs_type) ] {
+            self.compile_expr(operand, Some(operand_type))?;
+
+            if let Some((from, _)) = promoted {
+                self.emit(Instruction::Cast(from, ty.into()), operand.span);
+            }
+        }
+
+        let instruction = match op {
+            BinaryOp::Add => Instruction::Add(ty),
+            BinaryOp::Sub => Instruction::Sub(ty),
+            BinaryOp::Mul => Instruction::Mul(ty),
+            BinaryOp::Div => Instruction::Div(ty),
+            BinaryOp::Mod => Instruction::Rem(ty),
+            BinaryOp::BitAnd => Instruction::BitAnd(ty),
+            BinaryOp::BitOr => Instruction::BitOr(ty),
+            BinaryOp::Smaller => Instruction::Lt(ty),
+            BinaryOp::Greater => Instruction::Gt(ty),
+            BinaryOp::SmallerEquals => Instruction::Le(ty),
+            BinaryOp::GreaterEquals => Instruction::Ge(ty),
+            BinaryOp::Equals | BinaryOp::NotEquals | BinaryOp::And | BinaryOp::Or => unreachable!("handled above"),
+        };
+
+        self.emit(instruction, span);
+
+        // `a & b` on `bool`s is a `bool` again
+        if let (Some((ValueType::Bool, _)), true) = (promoted, op.is_arithmetic()) {
+            self.emit(Instruction::Cast(ty.into(), ValueType::Bool), span);
+        }
+
+        Ok(())
+    }
+
+    // converting between pointers only changes what the checker allows
+    fn compile_cast(&mut self, from: Type, to: Type, span: Span) -> Compiled<()> {
+        match (self.repr(from, span)?, self.repr(to, span)?) {
+            (Repr::Scalar(from), Repr::Scalar(to)) if from == to => {},
+
+            (Repr::Scalar(from), Repr::Scalar(to)) => {
+                self.emit(Instruction::Cast(from, to), span);
+            },
+
+            _ => return Err(unsupported(&format!("casting `{}` to `{}` is", from, to), span)),
+        }
+
+        Ok(())
+    }
+
+    // a `*T` used as a `*dyn Trait` is paired with the vtable of `T`'s impl
+    fn compile_unsize(&mut self, pointee: Type, trait_name: &'static str, span: Span) -> Compiled<()> {
+        let vtable = self.checker.vtable(trait_name, pointee, span)?;
+        let methods = vtable.methods.iter().map(|symbol| self.function_index(symbol, span)).collect::<Compiled<Vec<u32>>>()?;
+        let data = self.temporary(ValueType::Pointer)?;
+        let object = self.stack_block(16, span)?;
+
+        self.emit(Instruction::Store(data), span);
+        self.emit(Instruction::Load(object), span);
+        self.emit(Instruction::Load(data), span);
+        self.emit(Instruction::Write(ValueType::Pointer), span);
+        self.emit(Instruction::Load(object), span);
+        self.emit(Instruction::Offset(8), span);
+        self.constant(Constant::VTable { size: vtable.type_layout.size, align: vtable.type_layout.align, methods }, span);
+        self.emit(Instruction::Write(ValueType::Pointer), span);
+        self.emit(Instruction::Load(object), span);
+
+        Ok(())
+    }
+}
+
+pub(super) fn numeric_type(ty: Type, span: Span) -> Compiled<NumericType> {
+    match ty {
+        Type::Simple(SimpleType::Int(v)) => Ok(NumericType::Int(v.base_type)),
+        Type::Simple(SimpleType::Uint(v)) => Ok(NumericType::Uint(v.base_type)),
+        Type::Simple(SimpleType::Fp(v)) => Ok(NumericType::Float(v.base_type)),
+        _ => Err(unsupported(&format!("arithmetic on values of type `{}` is", ty), span)),
+    }
+}
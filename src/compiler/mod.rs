@@ -0,0 +1,926 @@
+use std::{ collections::{ HashMap, HashSet } };
+
+use crate::{ ast::{ * }, bytecode::{ * }, error::{ * }, lexer::{ Span }, types::{ *, closures::{ CaptureMode, ClosureType }, functions::{ FnSignature }, mono::{ MonoFunction }, traits::{ Dispatch, ResolvedMethod, TraitImpl } } };
+
+pub mod expr;
+pub mod calls;
+pub mod patterns;
+pub mod pipeline;
+
+type Compiled<T> = std::result::Result<T, Error>;
+
+// how the VM holds a value: scalars live on the operand stack and in local slots, everything else
+// in memory, handled through a pointer to its first byte
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Repr {
+    Void,
+    Scalar(ValueType),
+    Aggregate(u32),
+}
+
+impl Repr {
+    // the type of the operand stack value standing for it
+    pub fn value_type(&self) -> Option<ValueType> {
+        match self {
+            Self::Void => None,
+            Self::Scalar(ty) => Some(*ty),
+            Self::Aggregate(_) => Some(ValueType::Pointer),
+        }
+    }
+}
+
+// where a variable lives
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Storage {
+    // a scalar held directly in a local slot
+    Slot(u16),
+    // a local slot holding the address of the value
+    Memory(u16),
+    // a slot of the closure environment, holding the value or, if captured by reference, its address
+    Captured(u32),
+    CapturedRef(u32),
+}
+
+struct FunctionBuilder {
+    index: u32,
+    ret: Type,
+    // the hidden parameters holding where an aggregate result is written and the closure environment
+    out: Option<u16>,
+    env: Option<u16>,
+    scopes: Vec<HashMap<&'static str, Storage>>,
+    // variables whose address is taken somewhere in the body, which are kept in memory
+    addressed: HashSet<&'static str>,
+    // the stack blocks the body uses and the slots holding their addresses, allocated once on entry
+    blocks: Vec<(u16, u32, Span)>,
+}
+
+// a function or method to compile once every callee has an index, with the module, impl and type arguments it is compiled under
+struct Body {
+    index: u32,
+    file: &'static str,
+    decl: FnDecl,
+    sig: FnSignature,
+    imp: Option<TraitImpl>,
+    type_args: Vec<(&'static str, Type)>,
+}
+
+// lowers checked items to bytecode; the checker's scopes are kept in step with the code being
+// compiled so that it can be asked for the type of every expression
+pub struct Compiler<'a> {
+    checker: &'a mut Typechecker,
+    program: Program,
+    // by name, so that calls can be emitted before their callee is compiled
+    indices: HashMap<String, u32>,
+    // the functions being compiled, a closure's body last
+    builders: Vec<FunctionBuilder>,
+    // the instantiations of generic functions the checker collected, each compiled under its symbol
+    instances: Vec<MonoFunction>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(checker: &'a mut Typechecker) -> Self {
+        Self {
+            checker,
+            program: Program::new(),
+            indices: HashMap::new(),
+            builders: vec![],
+            instances: vec![],
+        }
+    }
+
+    pub fn with_instances(self, instances: Vec<MonoFunction>) -> Self {
+        Self {
+            instances,
+            ..self
+        }
+    }
+
+    // compiles every function and impl method of `items`, which the checker must have declared and checked;
+    // generic functions are compiled once per instance given to `with_instances`, and `extern` functions
+    // without a body are left out
+    pub fn compile(self, items: &[Item]) -> Compiled<Program> {
+        let file = self.checker.file;
+
+        self.compile_modules(&[ (file, items) ])
+    }
+
+    // like `compile`, for the items of every module of a program, each under the name of its module
+    pub fn compile_modules(mut self, modules: &[ (&'static str, &[Item]) ]) -> Compiled<Program> {
+        let mut bodies: Vec<Body> = vec![];
+        let file = self.checker.file;
+
+        for (module, items) in modules {
+            self.checker.switch_file(module);
+            self.declare_items(module, items, &mut bodies)?;
+        }
+
+        for instance in std::mem::take(&mut self.instances) {
+            self.checker.switch_file(instance.file);
+
+            bodies.push(Body {
+                index: self.declare(&instance.symbol, &instance.sig, instance.decl.name.span)?,
+                file: instance.file,
+                decl: instance.decl,
+                sig: instance.sig,
+                imp: None,
+                type_args: instance.type_args,
+            });
+        }
+
+        for Body { index, file, decl, sig, imp, type_args } in bodies {
+            self.checker.switch_file(file);
+
+            let state = imp.map(|imp| self.checker.enter_impl(&imp));
+            let depth = self.checker.bind_type_params(&type_args);
+            let body = decl.body.as_ref().expect("only functions with a body are compiled");
+            let compiled = self.compile_function(index, &decl.params, body, &sig, None, decl.name.span);
+
+            self.checker.unbind_type_params(depth);
+
+            if let Some(state) = state {
+                self.checker.leave_impl(state);
+            }
+
+            compiled?;
+        }
+
+        self.checker.switch_file(file);
+
+        Ok(self.program)
+    }
+
+    // gives every function and impl method of `items` an index, so that calls can be emitted before their callee is compiled
+    fn declare_items(&mut self, file: &'static str, items: &[Item], bodies: &mut Vec<Body>) -> Compiled<()> {
+        for item in items {
+            match item {
+                Item::Fn(decl) | Item::ExternFn(decl) if decl.generics.is_empty() && decl.body.is_some() => {
+                    let sig = self.checker.function_signature(decl.name.node).cloned().ok_or_else(|| {
+                        Error::from_span(decl.name.span, ErrorType::InternalError, &format!("function `{}` has not been checked", decl.name.node))
+                    })?;
+
+                    bodies.push(Body {
+                        index: self.declare(decl.name.node, &sig, decl.name.span)?,
+                        file,
+                        decl: decl.clone(),
+                        sig,
+                        imp: None,
+                        type_args: vec![],
+                    });
+                },
+
+                Item::Impl(decl) => {
+                    let trait_name = self.checker.resolve_trait_ref(decl.trait_name, &decl.trait_args)?;
+                    let for_type = self.checker.resolve_type(&decl.for_type)?;
+                    let imp = self.checker.trait_impls().iter().find(|imp| imp.trait_name == trait_name && imp.for_type == for_type).cloned().ok_or_else(|| {
+                        Error::from_span(decl.for_type.span, ErrorType::InternalError, &format!("the impl of `{}` for `{}` has not been checked", trait_name, for_type))
+                    })?;
+
+                    let defaults = self.checker.trait_type(trait_name).map(|trait_type| trait_type.decl.methods.clone()).unwrap_or_default();
+
+                    for (name, sig) in &imp.methods {
+                        // inherited default methods are compiled once for every implementing type
+                        let method = decl.methods.iter().find(|method| method.name.node == *name)
+                            .or_else(|| defaults.iter().find(|method| method.decl.name.node == *name).map(|method| &method.decl))
+                            .expect("every method of an impl has a body");
+
+                        let symbol = ResolvedMethod {
+                            trait_name,
+                            for_type,
+                            name,
+                            sig: sig.clone(),
+                            dispatch: Dispatch::Static,
+                        }.symbol();
+
+                        bodies.push(Body {
+                            index: self.declare(&symbol, sig, method.name.span)?,
+                            file,
+                            decl: method.clone(),
+                            sig: sig.clone(),
+                            imp: Some(imp.clone()),
+                            type_args: vec![],
+                        });
+                    }
+                },
+
+                _ => {},
+            }
+        }
+
+        Ok(())
+    }
+
+    // adds an empty function; an aggregate result is written through a hidden first parameter and a
+    // closure's environment is passed after it
+    fn declare(&mut self, name: &str, sig: &FnSignature, span: Span) -> Compiled<u32> {
+        let ret = self.repr(sig.ret, span)?;
+        let mut params = vec![];
+
+        if let Repr::Aggregate(_) = ret {
+            params.push(ValueType::Pointer);
+        }
+
+        if sig.convention == CallingConvention::Closure {
+            params.push(ValueType::Pointer);
+        }
+
+        for ty in &sig.params {
+            params.push(self.repr(*ty, span)?.value_type().expect("parameters are never `void`"));
+        }
+
+        let name: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        let index = self.program.add_function(Function::new(name, params, ret.value_type()));
+
+        self.indices.insert(name.to_owned(), index);
+
+        Ok(index)
+    }
+
+    fn compile_function(&mut self, index: u32, params: &[Param], body: &Block, sig: &FnSignature, closure: Option<&ClosureType>, span: Span) -> Compiled<()> {
+        let mut addressed = HashSet::new();
+
+        addressed_in_block(body, &mut addressed);
+
+        self.builders.push(FunctionBuilder {
+            index,
+            ret: sig.ret,
+            out: None,
+            env: None,
+            scopes: vec![ HashMap::new() ],
+            addressed,
+            blocks: vec![],
+        });
+
+        self.checker.push_scope();
+
+        let compiled = self.compile_body(params, body, sig, closure, span);
+
+        self.checker.pop_scope();
+        self.builders.pop();
+
+        compiled
+    }
+
+    fn compile_body(&mut self, params: &[Param], body: &Block, sig: &FnSignature, closure: Option<&ClosureType>, span: Span) -> Compiled<()> {
+        if let Repr::Aggregate(_) = self.repr(sig.ret, span)? {
+            self.builder().out = Some(self.local("$ret", ValueType::Pointer)?);
+        }
+
+        if let Some(closure) = closure {
+            self.builder().env = Some(self.local("$env", ValueType::Pointer)?);
+
+            for (capture, offset) in closure.captures.iter().zip(&closure.offsets) {
+                let storage = match capture.mode {
+                    CaptureMode::Value => Storage::Captured(*offset as u32),
+                    CaptureMode::Ref => Storage::CapturedRef(*offset as u32),
+                };
+
+                self.declare_variable(capture.name, storage);
+            }
+        }
+
+        let slots: Vec<u16> = params.iter().zip(&sig.params).map(|(param, ty)| {
+            let ty = self.repr(*ty, param.name.span)?.value_type().expect("parameters are never `void`");
+
+            self.local(param.name.node, ty)
+        }).collect::<Compiled<Vec<u16>>>()?;
+
+        // parameters are passed by value: aggregates are copied and scalars whose address is taken moved to memory
+        for ((param, ty), slot) in params.iter().zip(&sig.params).zip(slots) {
+            let span = param.name.span;
+            let storage = match self.repr(*ty, span)? {
+                Repr::Aggregate(size) => {
+                    self.emit(Instruction::StackAlloc(size), span);
+                    self.emit(Instruction::Dup, span);
+                    self.emit(Instruction::Load(slot), span);
+                    self.emit(Instruction::Copy(size), span);
+                    self.emit(Instruction::Store(slot), span);
+
+                    Storage::Memory(slot)
+                },
+
+                Repr::Scalar(_) if self.builder().addressed.contains(param.name.node) => {
+                    let storage = self.allocate_variable(param.name.node, *ty, span)?;
+
+                    self.emit(Instruction::Load(slot), span);
+                    self.initialize(storage, *ty, span)?;
+
+                    storage
+                },
+
+                _ => Storage::Slot(slot),
+            };
+
+            self.declare_variable(param.name.node, storage);
+            self.checker.declare_variable(param.name.node, *ty);
+        }
+
+        self.compile_block(body)?;
+
+        // a function with a result returns on every path, so its end is never reached
+        match sig.ret {
+            Type::Simple(SimpleType::Void) => self.emit(Instruction::ReturnVoid, span),
+            _ => self.trap("reached the end of a function without returning a value", span),
+        };
+
+        let mut prologue = Chunk::new();
+
+        for (slot, size, span) in std::mem::take(&mut self.builder().blocks) {
+            prologue.push(Instruction::StackAlloc(size), span);
+            prologue.push(Instruction::Store(slot), span);
+        }
+
+        self.function().chunk.prepend(prologue);
+
+        Ok(())
+    }
+
+    fn compile_block(&mut self, block: &Block) -> Compiled<()> {
+        self.push_scope();
+
+        let compiled = block.iter().try_for_each(|stmt| self.compile_stmt(stmt));
+
+        self.pop_scope();
+
+        compiled
+    }
+
+    // a block run only when `condition` evaluated to `outcome`, with the variables it proves non-null narrowed
+    fn compile_narrowed_block(&mut self, block: &Block, condition: &Expr, outcome: bool) -> Compiled<()> {
+        self.push_scope();
+        self.checker.narrow(condition, outcome);
+
+        let compiled = self.compile_block(block);
+
+        self.pop_scope();
+
+        compiled
+    }
+
+    fn compile_stmt(&mut self, stmt: &Stmt) -> Compiled<()> {
+        let span = stmt.span;
+
+        match &stmt.node {
+            StmtKind::Let(name, is_mutable, ty, value) => {
+                let declared = match ty {
+                    Some(ty) => Some(self.checker.resolve_type(ty)?),
+                    None => None,
+                };
+
+                let ty = match declared {
+                    Some(ty) => ty,
+                    None => self.checker.check_expr(value, None)?,
+                };
+
+                let storage = self.allocate_variable(name, ty, span)?;
+
+                self.compile_coerced(value, ty)?;
+                self.initialize(storage, ty, span)?;
+                self.declare_variable(name, storage);
+                self.checker.declare_binding(name, ty, *is_mutable);
+            },
+
+            StmtKind::Assign(place, value) => {
+                let mut ty = self.checker.check_expr(place, None)?;
+
+                // a narrowed variable may be given any value of its declared type
+                if let ExprKind::Identifier(name) = place.node {
+                    if self.checker.lookup_binding(name).is_some_and(|(_, binding)| binding.is_narrowed) {
+                        ty = Typechecker::nullable(ty);
+                    }
+                }
+
+                match place.node {
+                    ExprKind::Identifier(name) if matches!(self.lookup(name), Some(Storage::Slot(_))) => {
+                        let Some(Storage::Slot(slot)) = self.lookup(name) else {
+                            unreachable!("matched above")
+                        };
+
+                        self.compile_coerced(value, ty)?;
+                        self.emit(Instruction::Store(slot), span);
+                    },
+
+                    _ => {
+                        self.compile_place(place)?;
+                        self.compile_coerced(value, ty)?;
+                        self.write(ty, span)?;
+                    },
+                }
+
+                // forgets narrowings the new value invalidates
+                self.checker.check_assign(place, value)?;
+            },
+
+            StmtKind::Expr(expr) => {
+                let ty = self.compile_expr(expr, None)?;
+
+                if self.repr(ty, span)? != Repr::Void {
+                    self.emit(Instruction::Pop, span);
+                }
+            },
+
+            StmtKind::Delete(pointer) => {
+                self.checker.check_delete(pointer)?;
+                self.compile_expr(pointer, None)?;
+                self.emit(Instruction::Delete, span);
+            },
+
+            StmtKind::Return(Some(value)) => {
+                let ret = self.builder().ret;
+
+                match self.builder().out {
+                    Some(out) => {
+                        self.emit(Instruction::Load(out), span);
+                        self.compile_coerced(value, ret)?;
+                        self.write(ret, span)?;
+                        self.emit(Instruction::Load(out), span);
+                    },
+
+                    None => self.compile_coerced(value, ret)?,
+                }
+
+                self.emit(Instruction::Return, span);
+            },
+
+            StmtKind::Return(None) => {
+                self.emit(Instruction::ReturnVoid, span);
+            },
+
+            StmtKind::If(condition, then, otherwise) => {
+                self.compile_expr(condition, Some(Type::Simple(SimpleType::Bool)))?;
+
+                let skip_then = self.emit(Instruction::JumpIfFalse(0), condition.span);
+
+                self.compile_narrowed_block(then, condition, true)?;
+
+                match otherwise {
+                    Some(otherwise) => {
+                        let skip_else = self.emit(Instruction::Jump(0), span);
+
+                        self.patch(skip_then);
+                        self.compile_narrowed_block(otherwise, condition, false)?;
+                        self.patch(skip_else);
+                    },
+
+                    None => self.patch(skip_then),
+                }
+
+                self.checker.narrow_after_if(condition, then, otherwise.as_ref());
+            },
+
+            StmtKind::While(condition, body) => {
+                let start = self.function().chunk.len();
+
+                self.compile_expr(condition, Some(Type::Simple(SimpleType::Bool)))?;
+
+                let exit = self.emit(Instruction::JumpIfFalse(0), condition.span);
+
+                self.compile_narrowed_block(body, condition, true)?;
+                self.emit(Instruction::Jump(start as u32), span);
+                self.patch(exit);
+            },
+        }
+
+        Ok(())
+    }
+
+    fn repr(&self, ty: Type, span: Span) -> Compiled<Repr> {
+        let repr = match ty {
+            Type::Simple(SimpleType::Void) => Repr::Void,
+            Type::Simple(SimpleType::Int(v)) => Repr::Scalar(ValueType::Int(v.base_type)),
+            Type::Simple(SimpleType::Uint(v)) => Repr::Scalar(ValueType::Uint(v.base_type)),
+            Type::Simple(SimpleType::Fp(v)) => Repr::Scalar(ValueType::Float(v.base_type)),
+            Type::Simple(SimpleType::Bool) => Repr::Scalar(ValueType::Bool),
+            Type::Simple(SimpleType::Char) => Repr::Scalar(ValueType::Char),
+
+            Type::Simple(SimpleType::String) | Type::Simple(SimpleType::UserType(_)) | Type::Complex(ComplexType::Array(_))
+            | Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::TraitObject(_), .. }))
+            | Type::Complex(ComplexType::Function(FunctionType { convention: CallingConvention::Closure, .. })) => Repr::Aggregate(self.checker.layout_of(ty, span)?.size as u32),
+
+            Type::Complex(ComplexType::Pointer(_)) => Repr::Scalar(ValueType::Pointer),
+            Type::Complex(ComplexType::Function(_)) => Repr::Scalar(ValueType::Function),
+
+            Type::Simple(SimpleType::Varargs) | Type::Simple(SimpleType::SelfType) | Type::Simple(SimpleType::TraitObject(_)) | Type::Simple(SimpleType::Generic(_)) => {
+                return Err(unsupported(&format!("values of type `{}` are", ty), span));
+            },
+        };
+
+        Ok(repr)
+    }
+
+    // address -> value
+    fn read(&mut self, ty: Type, span: Span) -> Compiled<()> {
+        match self.repr(ty, span)? {
+            Repr::Scalar(ty) => {
+                self.emit(Instruction::Read(ty), span);
+            },
+
+            // an aggregate is handled through its address already
+            Repr::Aggregate(_) => {},
+            Repr::Void => return Err(Error::from_span(span, ErrorType::InternalError, "cannot read a value of type `void`")),
+        }
+
+        Ok(())
+    }
+
+    // address value ->
+    fn write(&mut self, ty: Type, span: Span) -> Compiled<()> {
+        match self.repr(ty, span)? {
+            Repr::Scalar(ty) => {
+                self.emit(Instruction::Write(ty), span);
+            },
+
+            Repr::Aggregate(size) => {
+                self.emit(Instruction::Copy(size), span);
+            },
+
+            Repr::Void => return Err(Error::from_span(span, ErrorType::InternalError, "cannot write a value of type `void`")),
+        }
+
+        Ok(())
+    }
+
+    // the first half of binding a variable, emitted before its value: memory for anything that needs an address
+    fn allocate_variable(&mut self, name: &'static str, ty: Type, span: Span) -> Compiled<Storage> {
+        let size = match self.repr(ty, span)? {
+            Repr::Scalar(ty) if !self.builder().addressed.contains(name) => return Ok(Storage::Slot(self.local(name, ty)?)),
+            Repr::Scalar(ty) => ty.size(),
+            Repr::Aggregate(size) => size,
+            Repr::Void => return Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot bind `{}` to a value of type `void`", name))),
+        };
+
+        let slot = self.local(name, ValueType::Pointer)?;
+
+        self.builder().blocks.push((slot, size, span));
+        self.emit(Instruction::Load(slot), span);
+
+        Ok(Storage::Memory(slot))
+    }
+
+    // the second half, emitted after the value
+    fn initialize(&mut self, storage: Storage, ty: Type, span: Span) -> Compiled<()> {
+        match storage {
+            Storage::Slot(slot) => {
+                self.emit(Instruction::Store(slot), span);
+
+                Ok(())
+            },
+
+            _ => self.write(ty, span),
+        }
+    }
+
+    fn declare_variable(&mut self, name: &'static str, storage: Storage) {
+        self.builder().scopes.last_mut().expect("function has no scope").insert(name, storage);
+    }
+
+    fn lookup(&self, name: &str) -> Option<Storage> {
+        self.builders.last()?.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    fn load_variable(&mut self, name: &str, ty: Type, span: Span) -> Compiled<()> {
+        match self.lookup(name) {
+            Some(Storage::Slot(slot)) => {
+                self.emit(Instruction::Load(slot), span);
+
+                Ok(())
+            },
+
+            _ => {
+                self.variable_address(name, span)?;
+                self.read(ty, span)
+            },
+        }
+    }
+
+    fn variable_address(&mut self, name: &str, span: Span) -> Compiled<()> {
+        match self.lookup(name) {
+            Some(Storage::Memory(slot)) => {
+                self.emit(Instruction::Load(slot), span);
+            },
+
+            Some(Storage::Captured(offset)) | Some(Storage::CapturedRef(offset)) => {
+                let env = self.builder().env.expect("only closures capture variables");
+
+                self.emit(Instruction::Load(env), span);
+                self.emit(Instruction::Offset(offset), span);
+
+                if let Some(Storage::CapturedRef(_)) = self.lookup(name) {
+                    self.emit(Instruction::Read(ValueType::Pointer), span);
+                }
+            },
+
+            Some(Storage::Slot(_)) => return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` is kept in a local slot and has no address", name))),
+            None => return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` is not a variable", name))),
+        }
+
+        Ok(())
+    }
+
+    fn push_scope(&mut self) {
+        self.builder().scopes.push(HashMap::new());
+        self.checker.push_scope();
+    }
+
+    fn pop_scope(&mut self) {
+        self.builder().scopes.pop();
+        self.checker.pop_scope();
+    }
+
+    fn builder(&mut self) -> &mut FunctionBuilder {
+        self.builders.last_mut().expect("not compiling a function")
+    }
+
+    fn function(&mut self) -> &mut Function {
+        let index = self.builder().index;
+
+        &mut self.program.functions[index as usize]
+    }
+
+    fn function_index(&self, name: &str, span: Span) -> Compiled<u32> {
+        self.indices.get(name).copied().ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("`{}` has no body the bytecode compiler can call", name))
+        })
+    }
+
+    fn local(&mut self, name: &'static str, ty: ValueType) -> Compiled<u16> {
+        let locals = &mut self.function().locals;
+
+        if locals.len() > u16::MAX as usize {
+            return Err(Error::new(0, 0, ErrorType::InternalError, "a function cannot have more than 65536 locals"));
+        }
+
+        locals.push(Local {
+            name,
+            ty,
+        });
+
+        Ok((locals.len() - 1) as u16)
+    }
+
+    // a local holding the address of `size` bytes of stack memory for the code at `span`; they are
+    // allocated once on entry, so code run in a loop reuses them rather than allocating every iteration
+    fn stack_block(&mut self, size: u32, span: Span) -> Compiled<u16> {
+        let slot = self.local("$mem", ValueType::Pointer)?;
+
+        self.builder().blocks.push((slot, size, span));
+
+        Ok(slot)
+    }
+
+    // a local for an intermediate value, like the scrutinee of a `match`
+    fn temporary(&mut self, ty: ValueType) -> Compiled<u16> {
+        self.local("$tmp", ty)
+    }
+
+    fn emit(&mut self, instruction: Instruction, span: Span) -> usize {
+        self.function().chunk.push(instruction, span)
+    }
+
+    fn constant(&mut self, constant: Constant, span: Span) {
+        let index = self.program.constants.add(constant);
+
+        self.emit(Instruction::Const(index), span);
+    }
+
+    fn trap(&mut self, message: &'static str, span: Span) -> usize {
+        let index = self.program.constants.add(Constant::String(message));
+
+        self.emit(Instruction::Trap(index), span)
+    }
+
+    // points the jump at `at` to the next instruction emitted
+    fn patch(&mut self, at: usize) {
+        let chunk = &mut self.function().chunk;
+        let target = chunk.len();
+
+        chunk.patch_jump(at, target);
+    }
+}
+
+fn unsupported(what: &str, span: Span) -> Error {
+    Error::from_span(span, ErrorType::TypeError, &format!("{} not supported by the bytecode compiler", what))
+}
+
+fn addressed_in_block(block: &Block, names: &mut HashSet<&'static str>) {
+    for stmt in block {
+        match &stmt.node {
+            StmtKind::Let(_, _, _, value) | StmtKind::Expr(value) | StmtKind::Delete(value) | StmtKind::Return(Some(value)) => addressed_in_expr(value, names),
+            StmtKind::Return(None) => {},
+
+            StmtKind::Assign(place, value) => {
+                addressed_in_expr(place, names);
+                addressed_in_expr(value, names);
+            },
+
+            StmtKind::If(condition, then, otherwise) => {
+                addressed_in_expr(condition, names);
+                addressed_in_block(then, names);
+
+                if let Some(otherwise) = otherwise {
+                    addressed_in_block(otherwise, names);
+                }
+            },
+
+            StmtKind::While(condition, body) => {
+                addressed_in_expr(condition, names);
+                addressed_in_block(body, names);
+            },
+        }
+    }
+}
+
+// collects the variables `&`, a reference capture or a method call may take the address of
+fn addressed_in_expr(expr: &Expr, names: &mut HashSet<&'static str>) {
+    match &expr.node {
+        ExprKind::Unary(UnaryOp::AddressOf | UnaryOp::AddressOfMut, place) => {
+            names.extend(root_variable(place));
+            addressed_in_expr(place, names);
+        },
+
+        ExprKind::MethodCall(receiver, _, args) => {
+            names.extend(root_variable(receiver));
+            addressed_in_expr(receiver, names);
+            args.iter().for_each(|arg| addressed_in_expr(arg, names));
+        },
+
+        ExprKind::Closure(closure) => {
+            names.extend(closure.by_ref.iter().map(|name| name.node));
+            addressed_in_block(&closure.body, names);
+        },
+
+        ExprKind::Literal(_) | ExprKind::Identifier(_) | ExprKind::Sizeof(_) | ExprKind::New(_) => {},
+        ExprKind::Unary(_, operand) | ExprKind::Cast(operand, _) | ExprKind::Field(operand, _) | ExprKind::Unwrap(operand) => addressed_in_expr(operand, names),
+
+        ExprKind::Binary(lhs, _, rhs) => {
+            addressed_in_expr(lhs, names);
+            addressed_in_expr(rhs, names);
+        },
+
+        ExprKind::Variant(_, _, args) => args.iter().for_each(|arg| addressed_in_expr(arg, names)),
+
+        ExprKind::Call(callee, args) => {
+            addressed_in_expr(callee, names);
+            args.iter().for_each(|arg| addressed_in_expr(arg, names));
+        },
+
+        ExprKind::Match(scrutinee, arms) => {
+            addressed_in_expr(scrutinee, names);
+            arms.iter().for_each(|arm| addressed_in_expr(&arm.body, names));
+        },
+    }
+}
+
+// the variable a place expression like `a.b.c` is part of
+fn root_variable(place: &Expr) -> Option<&'static str> {
+    match &place.node {
+        ExprKind::Identifier(name) => Some(name),
+        ExprKind::Field(base, _) => root_variable(base),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ *, pipeline::{ compile_module } };
+    use crate::{ ast::{ fixtures::{ * } }, lexer::{ Spanned }, vm::{ Vm, value::{ Value } } };
+
+    #[test]
+    fn loops_and_short_circuits_run() {
+        // fn sum(n: i32) -> i32 { let mut total = 0; let mut i = 0; while i < n && total < 100 { i = i + 1; total = total + i; } return total; }
+        let condition = binary(binary(ident("i"), BinaryOp::Smaller, ident("n")), BinaryOp::And, binary(ident("total"), BinaryOp::Smaller, int(100)));
+        let sum = function("sum", vec![ param("n", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Let("total", true, None, int(0))),
+            stmt(StmtKind::Let("i", true, None, int(0))),
+            stmt(StmtKind::While(condition, vec![
+                stmt(StmtKind::Assign(ident("i"), binary(ident("i"), BinaryOp::Add, int(1)))),
+                stmt(StmtKind::Assign(ident("total"), binary(ident("total"), BinaryOp::Add, ident("i")))),
+            ])),
+            stmt(StmtKind::Return(Some(ident("total")))),
+        ]);
+
+        let program = compile_module("main", &[ sum ]).unwrap();
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.call("sum", &[ Value::Int(10) ]).unwrap(), Some(Value::Int(55)));
+        assert_eq!(vm.call("sum", &[ Value::Int(20) ]).unwrap(), Some(Value::Int(105)));
+    }
+
+    #[test]
+    fn fields_are_read_at_their_offset_and_cast() {
+        // struct Point { x: i32, y: f16 }  fn y(p: *Point) -> f64 { return p.y as f64; }
+        let point = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Point"),
+            generics: vec![],
+            fields: vec![
+                StructField { is_pub: true, name: "x", ty: i32_type() },
+                StructField { is_pub: true, name: "y", ty: builtin(SimpleType::float(FloatingPointTypes::FP16)) },
+            ],
+        };
+
+        let pointer = Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::Named("Point", vec![])))));
+        let field = Spanned::new(0, 0, ExprKind::Field(Box::new(ident("p")), "y"));
+        let cast = Spanned::new(0, 0, ExprKind::Cast(Box::new(field), builtin(SimpleType::float(FloatingPointTypes::FP64))));
+        let y = function("y", vec![ param("p", pointer) ], Some(builtin(SimpleType::float(FloatingPointTypes::FP64))), vec![ stmt(StmtKind::Return(Some(cast))) ]);
+
+        let program = compile_module("main", &[ Item::Struct(point), y ]).unwrap();
+        let half = ValueType::Float(FloatingPointTypes::FP16);
+
+        assert_eq!(program.functions[0].chunk.code()[.. 5], [
+            Instruction::Load(0), Instruction::Offset(4), Instruction::Read(half), Instruction::Cast(half, ValueType::Float(FloatingPointTypes::FP64)), Instruction::Return,
+        ]);
+    }
+
+    #[test]
+    fn the_checker_accepts_the_casts_the_vm_performs() {
+        let mut checker = Typechecker::new("main");
+        let compiler = Compiler::new(&mut checker);
+        let span = Span::new(0, 0);
+        let pointer = Type::Complex(ComplexType::Pointer(Pointer::new(8, 0, 1, SimpleType::signed(SignedIntegerTypes::Int32))));
+        let types = [
+            Type::Simple(SimpleType::signed(SignedIntegerTypes::Int64)), Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)),
+            Type::Simple(SimpleType::float(FloatingPointTypes::FP64)), Type::Simple(SimpleType::Bool), Type::Simple(SimpleType::Char), pointer,
+        ];
+
+        for from in types {
+            for to in types {
+                let value_type = |ty| compiler.repr(ty, span).unwrap().value_type().unwrap();
+
+                assert_eq!(Typechecker::can_cast(from, to), Value::can_cast(value_type(from), value_type(to)), "{} as {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn new_allocates_by_layout_and_leaks_are_reported_by_site() {
+        // struct Point { x: i32, y: i32 }  fn leak() -> i32 { let p = new Point; let q = new i64; delete q; p.x = 7; return p.x; }
+        let point = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Point"),
+            generics: vec![],
+            fields: vec![
+                StructField { is_pub: true, name: "x", ty: i32_type() },
+                StructField { is_pub: true, name: "y", ty: i32_type() },
+            ],
+        };
+
+        let x = || Spanned::new(0, 0, ExprKind::Field(Box::new(ident("p")), "x"));
+        let leak = function("leak", vec![], Some(i32_type()), vec![
+            stmt(StmtKind::Let("p", false, None, Spanned::new(30, 38, ExprKind::New(Spanned::new(0, 0, TypeExprKind::Named("Point", vec![])))))),
+            stmt(StmtKind::Let("q", false, None, Spanned::new(50, 56, ExprKind::New(builtin(SimpleType::signed(SignedIntegerTypes::Int64)))))),
+            stmt(StmtKind::Delete(ident("q"))),
+            stmt(StmtKind::Assign(x(), int(7))),
+            stmt(StmtKind::Return(Some(x()))),
+        ]);
+
+        let program = compile_module("main", &[ Item::Struct(point), leak ]).unwrap();
+
+        assert!(program.functions[0].chunk.code().starts_with(&[ Instruction::New(8), Instruction::Store(0), Instruction::New(8) ]));
+
+        let mut vm = Vm::new(&program).with_debug_heap();
+
+        assert_eq!(vm.call("leak", &[]).unwrap(), Some(Value::Int(7)));
+        assert_eq!(vm.leak_report().unwrap(), "leaked 8 bytes in 1 block\n    30:38: 8 bytes in 1 block");
+    }
+
+    #[test]
+    fn addressed_variables_in_loops_reuse_their_memory() {
+        // fn spin(n: i32) -> i32 { let mut i = 0; while i < n { let x = i; let p = &x; i = *p + 1; } return i / (i - n); }
+        let unary = |op, operand| Spanned::new(0, 0, ExprKind::Unary(op, Box::new(operand)));
+        let spin = function("spin", vec![ param("n", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Let("i", true, None, int(0))),
+            stmt(StmtKind::While(binary(ident("i"), BinaryOp::Smaller, ident("n")), vec![
+                stmt(StmtKind::Let("x", false, None, ident("i"))),
+                stmt(StmtKind::Let("p", false, None, unary(UnaryOp::AddressOf, ident("x")))),
+                stmt(StmtKind::Assign(ident("i"), binary(unary(UnaryOp::Deref, ident("p")), BinaryOp::Add, int(1)))),
+            ])),
+            stmt(StmtKind::Return(Some(binary(ident("i"), BinaryOp::Div, binary(ident("i"), BinaryOp::Sub, ident("n")))))),
+        ]);
+
+        let program = compile_module("main", &[ spin ]).unwrap();
+        let code = program.functions[0].chunk.code();
+
+        // `x` gets one block, allocated on entry rather than on every iteration
+        assert_eq!(code.iter().filter(|instruction| matches!(instruction, Instruction::StackAlloc(_))).count(), 1);
+        assert!(matches!(code[0], Instruction::StackAlloc(4)));
+
+        let mut vm = Vm::new(&program);
+
+        // the division fails after the loop; unwinding the frame still releases its block
+        assert_eq!(vm.call("spin", &[ Value::Int(100) ]).unwrap_err().get_code(), codes::DIVISION_BY_ZERO);
+        assert_eq!(vm.memory().live_blocks(), 0);
+    }
+
+    #[test]
+    fn runtime_errors_point_at_the_source() {
+        // fn divide(a: i32, b: i32) -> i32 { return a / b; }
+        let quotient = Spanned::new(42, 46, ExprKind::Binary(Box::new(ident("a")), BinaryOp::Div, Box::new(ident("b"))));
+        let divide = function("divide", vec![ param("a", i32_type()), param("b", i32_type()) ], Some(i32_type()), vec![ stmt(StmtKind::Return(Some(quotient))) ]);
+
+        let program = compile_module("main", &[ divide ]).unwrap();
+        let error = Vm::new(&program).call("divide", &[ Value::Int(1), Value::Int(0) ]).unwrap_err();
+
+        assert_eq!(error.get_code(), codes::DIVISION_BY_ZERO);
+        assert_eq!(error.get_location(), Span::new(42, 46));
+    }
+}
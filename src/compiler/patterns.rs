@@ -0,0 +1,231 @@
+use super::{ * };
+
+// where a matched value is found: in a local slot, or at an offset from the address one holds
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Source {
+    slot: u16,
+    offset: Option<u32>,
+}
+
+impl Source {
+    fn field(&self, offset: u64) -> Self {
+        Self {
+            slot: self.slot,
+            offset: Some(self.offset.unwrap_or(0) + offset as u32),
+        }
+    }
+}
+
+impl Compiler<'_> {
+    // an enum value is its tag followed by the payload of the variant
+    pub(super) fn compile_variant(&mut self, ty: Type, variant: &str, args: &[Expr], span: Span) -> Compiled<()> {
+        let (name, tag, payload) = self.variant_of(ty, variant, span)?;
+        let layout = self.checker.enum_layout(name, span)?;
+        let value = self.stack_block(layout.layout.size as u32, span)?;
+
+        self.emit(Instruction::Load(value), span);
+        self.constant(Constant::Uint(UnsignedInteger::new(layout.tag, tag as u64)), span);
+        self.emit(Instruction::Write(ValueType::Uint(layout.tag)), span);
+
+        for ((arg, ty), offset) in args.iter().zip(payload).zip(&layout.field_offsets[tag]) {
+            self.emit(Instruction::Load(value), arg.span);
+            self.emit(Instruction::Offset(*offset as u32), arg.span);
+            self.compile_coerced(arg, ty)?;
+            self.write(ty, arg.span)?;
+        }
+
+        self.emit(Instruction::Load(value), span);
+
+        Ok(())
+    }
+
+    // every arm tests its pattern and jumps to the next arm if it does not match; the checker made sure
+    // that the last arm is never left that way
+    pub(super) fn compile_match(&mut self, scrutinee: &Expr, arms: &[MatchArm], ty: Type, span: Span) -> Compiled<()> {
+        let scrutinee_type = self.compile_expr(scrutinee, None)?;
+        let value = match self.repr(scrutinee_type, scrutinee.span)?.value_type() {
+            Some(value_type) => self.temporary(value_type)?,
+            None => return Err(Error::from_span(scrutinee.span, ErrorType::TypeError, "cannot match on a value of type `void`")),
+        };
+
+        let result = match self.repr(ty, span)?.value_type() {
+            Some(value_type) => Some(self.temporary(value_type)?),
+            None => None,
+        };
+
+        self.emit(Instruction::Store(value), scrutinee.span);
+
+        let mut ends = Vec::with_capacity(arms.len());
+
+        for arm in arms {
+            let mut failures = vec![];
+
+            self.push_scope();
+
+            let compiled = self.compile_pattern(&arm.pattern, scrutinee_type, Source { slot: value, offset: None }, &mut failures)
+                .and_then(|_| self.compile_expr(&arm.body, Some(ty)));
+
+            self.pop_scope();
+            compiled?;
+
+            if let Some(result) = result {
+                self.emit(Instruction::Store(result), arm.body.span);
+            }
+
+            ends.push(self.emit(Instruction::Jump(0), arm.body.span));
+
+            for failure in failures {
+                self.patch(failure);
+            }
+        }
+
+        self.trap("no match arm applies to the value", span);
+
+        for end in ends {
+            self.patch(end);
+        }
+
+        if let Some(result) = result {
+            self.emit(Instruction::Load(result), span);
+        }
+
+        Ok(())
+    }
+
+    // emits the tests of `pattern`, with a jump to be patched to the next arm for each, and binds its variables
+    fn compile_pattern(&mut self, pattern: &Pattern, ty: Type, source: Source, failures: &mut Vec<usize>) -> Compiled<()> {
+        let span = pattern.span;
+
+        match &pattern.node {
+            PatternKind::Wildcard => {},
+
+            // a bare name refers to a payload-less variant of the matched enum if there is one
+            PatternKind::Binding(name) if self.is_variant(ty, name) => self.compile_variant_pattern(ty, name, &[], source, failures, span)?,
+
+            PatternKind::Binding(name) => {
+                let storage = self.allocate_variable(name, ty, span)?;
+
+                self.load_source(source, ty, span)?;
+                self.initialize(storage, ty, span)?;
+                self.declare_variable(name, storage);
+                self.checker.declare_variable(name, ty);
+            },
+
+            PatternKind::Literal(literal) => {
+                let value_type = self.repr(ty, span)?.value_type().expect("literal patterns are scalars");
+
+                self.load_source(source, ty, span)?;
+                self.compile_literal(*literal, ty, false, span)?;
+                self.emit(Instruction::Eq(value_type), span);
+                failures.push(self.emit(Instruction::JumpIfFalse(0), span));
+            },
+
+            PatternKind::Variant(name, fields) => self.compile_variant_pattern(ty, name, fields, source, failures, span)?,
+        }
+
+        Ok(())
+    }
+
+    fn compile_variant_pattern(&mut self, ty: Type, variant: &str, fields: &[Pattern], source: Source, failures: &mut Vec<usize>, span: Span) -> Compiled<()> {
+        let (name, tag, payload) = self.variant_of(ty, variant, span)?;
+        let layout = self.checker.enum_layout(name, span)?;
+        let tag_type = ValueType::Uint(layout.tag);
+
+        self.load_source(source, ty, span)?;
+        self.emit(Instruction::Read(tag_type), span);
+        self.constant(Constant::Uint(UnsignedInteger::new(layout.tag, tag as u64)), span);
+        self.emit(Instruction::Eq(tag_type), span);
+        failures.push(self.emit(Instruction::JumpIfFalse(0), span));
+
+        for ((field, ty), offset) in fields.iter().zip(payload).zip(&layout.field_offsets[tag]) {
+            self.compile_pattern(field, ty, source.field(*offset), failures)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_source(&mut self, source: Source, ty: Type, span: Span) -> Compiled<()> {
+        self.emit(Instruction::Load(source.slot), span);
+
+        match source.offset {
+            Some(offset) => {
+                self.emit(Instruction::Offset(offset), span);
+                self.read(ty, span)
+            },
+
+            None => Ok(()),
+        }
+    }
+
+    fn is_variant(&self, ty: Type, name: &str) -> bool {
+        match ty {
+            Type::Simple(SimpleType::UserType(user_type)) => self.checker.enum_type(user_type).is_some_and(|decl| decl.get_variant(name).is_some()),
+            _ => false,
+        }
+    }
+
+    // the enum, index and payload types of the variant `name` of `ty`
+    fn variant_of(&self, ty: Type, name: &str, span: Span) -> Compiled<(UserDefinedType, usize, Vec<Type>)> {
+        let variant = match ty {
+            Type::Simple(SimpleType::UserType(user_type)) => self.checker.enum_type(user_type).and_then(|decl| decl.get_variant(name)).map(|(index, variant)| (user_type, index, variant.payload.clone())),
+            _ => None,
+        };
+
+        variant.ok_or_else(|| Error::from_span(span, ErrorType::InternalError, &format!("type `{}` has no variant named `{}`", ty, name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, compiler::pipeline::{ compile_module }, lexer::{ Spanned }, vm::{ Vm, value::{ Value } } };
+
+    fn pattern(kind: PatternKind) -> Pattern {
+        Spanned::new(0, 0, kind)
+    }
+
+    #[test]
+    fn matches_test_tags_and_bind_payloads() {
+        // enum Shape { Empty, Square(i32), Rect(i32, i32) }
+        // fn area(n: i32) -> i32 { let mut shape = ...picked by n...; return match shape { Empty => 0, Square(s) => s * s, Rect(w, 2) => w * 2, _ => 1 }; }
+        let shape = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Shape"),
+            generics: vec![],
+            variants: vec![
+                EnumVariantDecl { name: Spanned::new(0, 0, "Empty"), payload: vec![] },
+                EnumVariantDecl { name: Spanned::new(0, 0, "Square"), payload: vec![ i32_type() ] },
+                EnumVariantDecl { name: Spanned::new(0, 0, "Rect"), payload: vec![ i32_type(), i32_type() ] },
+            ],
+        };
+
+        let shape_type = Spanned::new(0, 0, TypeExprKind::Named("Shape", vec![]));
+        let arms = vec![
+            MatchArm { pattern: pattern(PatternKind::Binding("Empty")), body: int(0) },
+            MatchArm {
+                pattern: pattern(PatternKind::Variant("Square", vec![ pattern(PatternKind::Binding("s")) ])),
+                body: binary(ident("s"), BinaryOp::Mul, ident("s")),
+            },
+            MatchArm {
+                pattern: pattern(PatternKind::Variant("Rect", vec![ pattern(PatternKind::Binding("w")), pattern(PatternKind::Literal(Literal::Int(2, None))) ])),
+                body: binary(ident("w"), BinaryOp::Mul, int(2)),
+            },
+            MatchArm { pattern: pattern(PatternKind::Wildcard), body: int(1) },
+        ];
+
+        let variant = |name, args| Spanned::new(0, 0, ExprKind::Variant("Shape", name, args));
+        let is = |value| binary(ident("n"), BinaryOp::Equals, int(value));
+        let area = function("area", vec![ param("n", i32_type()) ], Some(i32_type()), vec![
+            stmt(StmtKind::Let("shape", true, Some(shape_type), variant("Empty", vec![]))),
+            stmt(StmtKind::If(is(1), vec![ stmt(StmtKind::Assign(ident("shape"), variant("Square", vec![ int(3) ]))) ], None)),
+            stmt(StmtKind::If(is(2), vec![ stmt(StmtKind::Assign(ident("shape"), variant("Rect", vec![ int(4), int(2) ]))) ], None)),
+            stmt(StmtKind::If(is(3), vec![ stmt(StmtKind::Assign(ident("shape"), variant("Rect", vec![ int(4), int(5) ]))) ], None)),
+            stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Match(Box::new(ident("shape")), arms))))),
+        ]);
+
+        let program = compile_module("main", &[ Item::Enum(shape), area ]).unwrap();
+        let mut vm = Vm::new(&program);
+        let areas: Vec<_> = (0 .. 4).map(|n| vm.call("area", &[ Value::Int(n) ]).unwrap()).collect();
+
+        assert_eq!(areas, [ Some(Value::Int(0)), Some(Value::Int(9)), Some(Value::Int(8)), Some(Value::Int(1)) ]);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{ collections::{ HashMap }, path::{ Path } };
+
+use super::{ * };
+use crate::{ modules::{ ModuleGraph }, parser::{ parse_module }, resolve::{ Resolver, ResolvedProgram, Scope } };
+
+// the items of one module by kind, in the form the checker takes them
+#[derive(Default)]
+struct ModuleItems {
+    aliases: Vec<TypeAliasDecl>,
+    structs: Vec<StructDecl>,
+    enums: Vec<EnumDecl>,
+    traits: Vec<TraitDecl>,
+    impls: Vec<ImplDecl>,
+    functions: Vec<FnDecl>,
+    externs: Vec<FnDecl>,
+    statics: Vec<StaticDecl>,
+}
+
+impl ModuleItems {
+    fn new(items: &[Item]) -> Self {
+        let mut sorted = Self::default();
+
+        for item in items {
+            match item {
+                Item::Import(_) => {},
+                Item::TypeAlias(decl) => sorted.aliases.push(decl.clone()),
+                Item::Struct(decl) => sorted.structs.push(decl.clone()),
+                Item::Enum(decl) => sorted.enums.push(decl.clone()),
+                Item::Trait(decl) => sorted.traits.push(decl.clone()),
+                Item::Impl(decl) => sorted.impls.push(decl.clone()),
+                Item::Fn(decl) => sorted.functions.push(decl.clone()),
+                Item::ExternFn(decl) => sorted.externs.push(decl.clone()),
+                Item::Static(decl) => sorted.statics.push(decl.clone()),
+            }
+        }
+
+        sorted
+    }
+
+    // every name a module declares is known before anything that may use it is resolved or checked:
+    // aliases before the fields spelled with them, signatures and statics before any body, and
+    // statics are evaluated last so that their initialisers may call any function
+    fn check(&self, checker: &mut Typechecker) -> Compiled<()> {
+        checker.register_aliases(&self.aliases)?;
+        checker.declare_types(&self.structs, &self.enums)?;
+        checker.check_aliases(&self.aliases)?;
+        checker.declare_traits(&self.traits)?;
+        checker.register_impls(&self.impls)?;
+        checker.register_functions(&self.functions)?;
+        checker.register_extern_functions(&self.externs)?;
+        checker.declare_statics(&self.statics)?;
+        checker.check_impls(&self.impls)?;
+        checker.check_functions(&self.functions)?;
+        checker.check_extern_functions(&self.externs)?;
+        checker.eval_statics()
+    }
+}
+
+// resolves, checks and compiles a program made of the single module `name`
+pub fn compile_module(name: &'static str, items: &[Item]) -> Compiled<Program> {
+    let mut resolver = Resolver::new(name);
+
+    resolver.declare_items(items)?;
+    resolver.resolve_items(items)?;
+
+    compile_resolved(&[ (name, resolver.imported(), items) ])
+}
+
+// resolves, checks and compiles every module of `graph` into one program, each module after those it imports
+pub fn compile_program(graph: &ModuleGraph, items: &HashMap<&'static str, Vec<Item>>) -> Compiled<Program> {
+    let resolved = ResolvedProgram::resolve(graph, items)?;
+    let mut modules: Vec<(&'static str, &Scope, &[Item])> = vec![];
+
+    for module in graph.modules() {
+        let resolver = resolved.module(module.name).expect("every module of the graph is resolved");
+        let items = items.get(module.name).map_or(&[][..], |items| &items[..]);
+
+        modules.push((module.name, resolver.imported(), items));
+    }
+
+    compile_resolved(&modules)
+}
+
+// loads the module `entry` of the project at `root` with everything it imports, then parses and compiles it
+pub fn compile_files(root: &Path, entry: &str) -> Compiled<Program> {
+    let graph = ModuleGraph::load(root, entry)?;
+    let mut items = HashMap::new();
+
+    for module in graph.modules() {
+        items.insert(module.name, parse_module(module.source)?);
+    }
+
+    compile_program(&graph, &items)
+}
+
+// the generic functions the modules call are instantiated once every module is checked
+fn compile_resolved(modules: &[ (&'static str, &Scope, &[Item]) ]) -> Compiled<Program> {
+    let mut checker = Typechecker::new(modules.first().map_or("main", |(name, _, _)| name));
+
+    for (name, imported, items) in modules {
+        checker.enter_module(name, imported);
+        ModuleItems::new(items).check(&mut checker)?;
+    }
+
+    let instances = checker.monomorphize()?;
+    let items: Vec<(&'static str, &[Item])> = modules.iter().map(|(name, _, items)| (*name, *items)).collect();
+
+    Compiler::new(&mut checker).with_instances(instances).compile_modules(&items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ ast::{ fixtures::{ * } }, bytecode::{ file }, lexer::{ Spanned }, modules::{ fixtures::{ TempDir } }, vm::{ Vm, value::{ Value } } };
+
+    #[test]
+    fn every_kind_of_item_goes_through_the_pipeline() {
+        // type Meters = i32;  struct Span { length: Meters }  static LIMIT: Meters = triple(4);
+        // fn triple(x: Meters) -> Meters { return x * 3; }  fn main() -> i32 { return LIMIT + 1; }
+        let meters = Item::TypeAlias(TypeAliasDecl { is_pub: false, name: Spanned::new(0, 0, "Meters"), ty: i32_type() });
+        let length = Item::Struct(StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Span"),
+            generics: vec![],
+            fields: vec![ StructField { is_pub: true, name: "length", ty: named("Meters") } ],
+        });
+        let limit = Item::Static(StaticDecl { is_pub: false, name: Spanned::new(1, 1, "LIMIT"), ty: named("Meters"), value: call("triple", vec![ int(4) ]) });
+        let triple = function("triple", vec![ param("x", named("Meters")) ], Some(named("Meters")), vec![
+            stmt(StmtKind::Return(Some(binary(ident("x"), BinaryOp::Mul, int(3))))),
+        ]);
+        let main = function("main", vec![], Some(i32_type()), vec![ stmt(StmtKind::Return(Some(binary(ident("LIMIT"), BinaryOp::Add, int(1))))) ]);
+
+        let program = compile_module("main", &[ meters, length, limit, triple, main ]).unwrap();
+
+        assert_eq!(Vm::new(&program).call("main", &[]).unwrap(), Some(Value::Int(13)));
+    }
+
+    #[test]
+    fn undefined_names_are_reported_by_the_resolver() {
+        let main = function("main", vec![], Some(i32_type()), vec![ stmt(StmtKind::Return(Some(ident("missing")))) ]);
+
+        assert_eq!(compile_module("main", &[ main ]).unwrap_err().get_message(), "cannot find value `missing` in this scope");
+    }
+
+    #[test]
+    fn modules_are_compiled_into_one_program() {
+        let root = TempDir::new("pipeline").with_modules(&[
+            ("main", "import double from \"math\";"),
+            ("math", ""),
+        ]);
+        let graph = ModuleGraph::load(root.path(), "main").unwrap();
+
+        // pub fn double(x: i32) -> i32 { return x * 2; }  fn main() -> i32 { return double(21); }
+        let double = Item::Fn(FnDecl {
+            is_pub: true,
+            name: Spanned::new(0, 0, "double"),
+            generics: vec![],
+            params: vec![ param("x", i32_type()) ],
+            ret: Some(i32_type()),
+            body: Some(vec![ stmt(StmtKind::Return(Some(binary(ident("x"), BinaryOp::Mul, int(2))))) ]),
+        });
+        let main = function("main", vec![], Some(i32_type()), vec![ stmt(StmtKind::Return(Some(call("double", vec![ int(21) ])))) ]);
+        let items = HashMap::from([ ("math", vec![ double ]), ("main", vec![ main ]) ]);
+
+        let program = compile_program(&graph, &items).unwrap();
+
+        assert_eq!(Vm::new(&program).call("main", &[]).unwrap(), Some(Value::Int(42)));
+    }
+
+    #[test]
+    fn source_files_compile_to_a_module_that_loads_back() {
+        let root = TempDir::new("compile").with_modules(&[
+            ("main", "import Shape, area from \"shapes\";\n\nfn main() -> i32 {\n    let mut total = 0;\n    let mut i = 1;\n\n    while i <= 3 {\n        total = total + area(Shape.Square(i));\n        i = i + 1;\n    }\n\n    return total + area(Shape.Empty);\n}"),
+            ("shapes", "pub enum Shape { Square(i32), Empty }\n\npub fn area(shape: Shape) -> i32 {\n    return match shape { Square(side) => side * side, Empty => 0, };\n}"),
+        ]);
+
+        let program = compile_files(root.path(), "main").unwrap();
+        let module = root.path().join("main.synb");
+
+        file::save(&program, &module).unwrap();
+
+        assert_eq!(Vm::new(&file::load(&module).unwrap()).call("main", &[]).unwrap(), Some(Value::Int(14)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{ fmt::{ Display, Formatter, Result } };
+use std::{ fmt::{ Display, Formatter, Result }, ops::{ Range } };
 
 use crate::lexer::{ Span };
 
@@ -8,6 +8,74 @@ pub enum ErrorType {
     TypeError,
     LexError,
     ParseError,
+    // a name that does not resolve, is declared twice or is not visible
+    NameError,
+    // a module that cannot be found, loaded or imported from
+    ImportError,
+    // a trap raised while running a program
+    RuntimeError,
+    IoError,
+}
+
+impl ErrorType {
+    // every code of a category lies in its range; the first one is the category's generic code
+    pub fn code_range(&self) -> Range<u16> {
+        match self {
+            Self::InternalError => 0 .. 1000,
+            Self::LexError => 1000 .. 2000,
+            Self::ParseError => 2000 .. 3000,
+            Self::TypeError => 3000 .. 4000,
+            Self::NameError => 4000 .. 5000,
+            Self::ImportError => 5000 .. 6000,
+            Self::RuntimeError => 6000 .. 7000,
+            Self::IoError => 7000 .. 8000,
+        }
+    }
+
+    // the process exit code of a failure of this kind, distinct per category so that scripts can tell them apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::LexError => 2,
+            Self::ParseError => 3,
+            Self::TypeError => 4,
+            Self::NameError => 5,
+            Self::ImportError => 6,
+            Self::RuntimeError => 7,
+            Self::IoError => 8,
+            Self::InternalError => 101,
+        }
+    }
+}
+
+// the specific codes in use, grouped by category
+pub mod codes {
+    pub const UNDEFINED_NAME: u16 = 4001;
+    pub const DUPLICATE_DEFINITION: u16 = 4002;
+    pub const PRIVATE_ITEM: u16 = 4003;
+    // a name that resolves to the wrong kind of item, like a struct used as a trait
+    pub const WRONG_KIND: u16 = 4004;
+
+    pub const MODULE_NOT_FOUND: u16 = 5001;
+    pub const IMPORT_CYCLE: u16 = 5002;
+    pub const INVALID_IMPORT_PATH: u16 = 5003;
+    pub const UNKNOWN_IMPORTED_ITEM: u16 = 5004;
+    pub const PRIVATE_IMPORT: u16 = 5005;
+    pub const CONFLICTING_IMPORT: u16 = 5006;
+
+    pub const OUT_OF_BOUNDS: u16 = 6001;
+    pub const NULL_DEREFERENCE: u16 = 6002;
+    pub const DIVISION_BY_ZERO: u16 = 6003;
+    pub const STACK_OVERFLOW: u16 = 6004;
+    pub const USE_AFTER_FREE: u16 = 6005;
+    pub const DOUBLE_FREE: u16 = 6006;
+
+    pub const READ_FAILED: u16 = 7001;
+    pub const WRITE_FAILED: u16 = 7002;
+    // a file that does not start like a compiled module
+    pub const NOT_BYTECODE: u16 = 7003;
+    pub const BYTECODE_VERSION_MISMATCH: u16 = 7004;
+    // a compiled module whose checksum or contents are wrong
+    pub const CORRUPT_BYTECODE: u16 = 7005;
 }
 
 impl Display for ErrorType {
@@ -17,29 +85,62 @@ impl Display for ErrorType {
             Self::TypeError => write!(f, "[TypeError]"),
             Self::LexError => write!(f, "[LexError]"),
             Self::ParseError => write!(f, "[ParseError]"),
+            Self::NameError => write!(f, "[NameError]"),
+            Self::ImportError => write!(f, "[ImportError]"),
+            Self::RuntimeError => write!(f, "[RuntimeError]"),
+            Self::IoError => write!(f, "[IoError]"),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     pub location: Span,
     pub error_type: ErrorType,
-    pub message: &'static str,
+    pub message: String,
+    pub code: u16,
 }
 
 impl Error {
-    pub fn new(start: usize, end: usize, error_type: ErrorType, message: &'static str) -> Self {
+    pub fn new(start: usize, end: usize, error_type: ErrorType, message: &str) -> Self {
         Self {
             location: Span {
                 start,
                 end,
             },
             error_type,
-            message,
+            message: message.to_owned(),
+            code: error_type.code_range().start,
+        }
+    }
+
+    pub fn from_span(location: Span, error_type: ErrorType, message: &str) -> Self {
+        Self {
+            location,
+            error_type,
+            message: message.to_owned(),
+            code: error_type.code_range().start,
         }
     }
 
+    // an I/O failure that has no location in any source file
+    pub fn io(code: u16, message: &str) -> Self {
+        Self::new(0, 0, ErrorType::IoError, message).with_code(code)
+    }
+
+    pub fn with_code(self, code: u16) -> Self {
+        debug_assert!(self.error_type.code_range().contains(&code), "code {} is not a {} code", code, self.error_type);
+
+        Self {
+            code,
+            ..self
+        }
+    }
+
+    #[inline] pub fn get_code(&self) -> u16 {
+        self.code
+    }
+
     #[inline] pub fn get_location(&self) -> Span {
         self.location
     }
@@ -49,14 +150,44 @@ impl Error {
     }
 
     #[inline] pub fn get_message(&self) -> String {
-        self.message.to_owned()
+        self.message.clone()
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        let msg: String = format!("{} at {} : {}", self.error_type, self.location, self.message);
+        let msg: String = format!("{} E{:04} at {} : {}", self.error_type, self.code, self.location, self.message);
 
         write!(f, "{}", msg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    const TYPES: [ErrorType; 8] = [ ErrorType::InternalError, ErrorType::TypeError, ErrorType::LexError, ErrorType::ParseError, ErrorType::NameError, ErrorType::ImportError, ErrorType::RuntimeError, ErrorType::IoError ];
+
+    #[test]
+    fn categories_have_distinct_ranges_and_exit_codes() {
+        for (i, a) in TYPES.iter().enumerate() {
+            for b in &TYPES[i + 1 ..] {
+                assert_ne!(a.exit_code(), b.exit_code());
+                assert!(a.code_range().end <= b.code_range().start || b.code_range().end <= a.code_range().start);
+            }
+        }
+
+        for (code, error_type) in [ (codes::UNDEFINED_NAME, ErrorType::NameError), (codes::IMPORT_CYCLE, ErrorType::ImportError), (codes::STACK_OVERFLOW, ErrorType::RuntimeError), (codes::READ_FAILED, ErrorType::IoError) ] {
+            assert!(error_type.code_range().contains(&code));
+        }
+    }
+
+    #[test]
+    fn errors_display_their_tag_and_code() {
+        let generic = Error::new(1, 4, ErrorType::TypeError, "expected `i32`, found `bool`");
+        let specific = Error::new(2, 2, ErrorType::NameError, "cannot find value `x` in this scope").with_code(codes::UNDEFINED_NAME);
+
+        assert_eq!(generic.to_string(), "[TypeError] E3000 at 1:4 : expected `i32`, found `bool`");
+        assert_eq!(specific.to_string(), "[NameError] E4001 at 2:2 : cannot find value `x` in this scope");
+    }
+}
@@ -1,7 +1,7 @@
-use std::{ iter::{ Peekable }, str::{ CharIndices }, fmt::{ Display, Formatter } };
+use std::{ iter::{ Peekable }, str::{ CharIndices } };
 
-use super::{ Span, Spanned, Source };
-use crate::{ error::{ * }, types::{ * }, lexer::{ token::{ Token, TokenType } } };
+use super::{ Spanned, Source };
+use crate::{ error::{ * }, lexer::{ token::{ Token, TokenType } } };
 
 type Scanned = Result<Spanned<Token>, Spanned<ErrorType>>;
 
@@ -71,5 +71,288 @@ impl Lexer {
         Spanned::new(start, self.pos() - self.previous.map_or(0, char::len_utf8), t)
     }
 
+    fn advance(&mut self) -> Option<char> {
+        let current = self.current?;
 
+        self.previous = Some(current.val);
+        self.current = InputPosition::new_opt(self.chars.next());
+
+        Some(current.val)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.current.map(|current| current.val)
+    }
+
+    fn peek_next(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, val)| *val)
+    }
+
+    fn matches(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+
+            return true;
+        }
+
+        false
+    }
+
+    fn advance_while(&mut self, predicate: fn(char) -> bool) {
+        while self.peek().is_some_and(predicate) {
+            self.advance();
+        }
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(val) if val.is_whitespace() => {
+                    self.advance();
+                },
+
+                Some('/') if self.peek_next() == Some('/') => self.advance_while(|val| val != '\n'),
+
+                _ => return,
+            }
+        }
+    }
+
+    fn token(&self, start: usize, token_type: TokenType) -> Scanned {
+        let text = self.slice(start, self.pos());
+        let Spanned { span, .. } = self.spanned(start, ());
+
+        Ok(Spanned::from_span(span, Token::new(token_type, span, text)))
+    }
+
+    fn error(&self, start: usize) -> Scanned {
+        Err(self.spanned(start, ErrorType::LexError))
+    }
+
+    fn word(&mut self, start: usize) -> Scanned {
+        self.advance_while(|val| val.is_alphanumeric() || val == '_');
+
+        let word = self.slice(start, self.pos());
+
+        self.token(start, TokenType::keyword(word).unwrap_or(TokenType::Identifier(word)))
+    }
+
+    // `1_000`, `2.5e-3` and `0.1f16`; the text keeps its suffix for the parser
+    fn number(&mut self, start: usize) -> Scanned {
+        let is_digit = |val: char| val.is_ascii_digit() || val == '_';
+        let mut is_float = false;
+
+        self.advance_while(is_digit);
+
+        if self.peek() == Some('.') && self.peek_next().is_some_and(|val| val.is_ascii_digit()) {
+            is_float = true;
+            self.advance();
+            self.advance_while(is_digit);
+        }
+
+        if self.matches('e') || self.matches('E') {
+            is_float = true;
+
+            if !self.matches('+') {
+                self.matches('-');
+            }
+
+            if !self.peek().is_some_and(|val| val.is_ascii_digit()) {
+                return self.error(start);
+            }
+
+            self.advance_while(is_digit);
+        }
+
+        let suffix_start = self.pos();
+
+        self.advance_while(char::is_alphanumeric);
+
+        match self.slice(suffix_start, self.pos()) {
+            "" => {},
+            "f16" | "f32" | "f64" => is_float = true,
+            "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" if !is_float => {},
+            _ => return self.error(start),
+        }
+
+        let text = self.slice(start, self.pos());
+
+        self.token(start, if is_float { TokenType::FloatLit(text) } else { TokenType::IntLit(text) })
+    }
+
+    // string and char literals keep their escapes; the text is what lies between the quotes
+    fn quoted(&mut self, start: usize, quote: char) -> Scanned {
+        loop {
+            match self.advance() {
+                None => return self.error(start),
+                Some('\\') if self.advance().is_none() => return self.error(start),
+                Some(val) if val == quote => break,
+                _ => {},
+            }
+        }
+
+        let text = self.slice(start + 1, self.pos() - 1);
+
+        match quote {
+            '"' => self.token(start, TokenType::StringLit(text)),
+            _ if !Self::is_one_char(text) => self.error(start),
+            _ => self.token(start, TokenType::Char(text)),
+        }
+    }
+
+    // a char literal holds a single character or a single escape: `\n`, `\t`, `\r`, `\0`, `\\`, a quote or `\u{...}`
+    fn is_one_char(text: &str) -> bool {
+        let mut chars = text.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some('\\'), Some('n' | 't' | 'r' | '0' | '\\' | '\'' | '"')) => chars.next().is_none(),
+
+            (Some('\\'), Some('u')) => {
+                let digits = chars.as_str().strip_prefix('{').and_then(|rest| rest.strip_suffix('}'));
+
+                digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()).and_then(char::from_u32).is_some()
+            },
+
+            (Some('\\'), _) => false,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Iterator for Lexer {
+    type Item = Scanned;
+
+    fn next(&mut self) -> Option<Scanned> {
+        self.skip_trivia();
+
+        let start = self.pos();
+        let token_type = match self.advance()? {
+            '(' => TokenType::LeftParen,
+            ')' => TokenType::RightParen,
+            '{' => TokenType::LeftBrace,
+            '}' => TokenType::RightBrace,
+            '[' => TokenType::LeftBracket,
+            ']' => TokenType::RightBracket,
+
+            '+' => TokenType::Plus,
+            '-' if self.matches('>') => TokenType::ThinArrow,
+            '-' => TokenType::Minus,
+            '*' => TokenType::Star,
+            '/' => TokenType::Slash,
+            '%' => TokenType::Modulo,
+            ':' => TokenType::Colon,
+            ';' => TokenType::Semicolon,
+            ',' => TokenType::Comma,
+            '?' => TokenType::Question,
+
+            '!' if self.matches('=') => TokenType::BangEquals,
+            '!' => TokenType::Bang,
+            '=' if self.matches('=') => TokenType::EqualsEquals,
+            '=' if self.matches('>') => TokenType::Arrow,
+            '=' => TokenType::Equals,
+            '<' if self.matches('=') => TokenType::SmallerEquals,
+            '<' => TokenType::Smaller,
+            '>' if self.matches('=') => TokenType::GreaterEquals,
+            '>' => TokenType::Greater,
+            '&' if self.matches('&') => TokenType::AmpersandAmpersand,
+            '&' => TokenType::Ampersand,
+            '|' if self.matches('|') => TokenType::PipePipe,
+            '|' => TokenType::Pipe,
+
+            '.' if self.peek() == Some('.') && self.peek_next() == Some('.') => {
+                self.advance();
+                self.advance();
+
+                TokenType::TripleDot
+            },
+
+            '.' => TokenType::Dot,
+
+            quote @ ('"' | '\'') => return Some(self.quoted(start, quote)),
+            val if val.is_ascii_digit() => return Some(self.number(start)),
+            val if val.is_alphabetic() || val == '_' => return Some(self.word(start)),
+
+            _ => return Some(self.error(start)),
+        };
+
+        Some(self.token(start, token_type))
+    }
+}
+
+impl Scanner for Lexer {
+    fn source(&self) -> &'static Source {
+        self.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ lexer::{ token::{ TokenType } }, types::{ * } };
+
+    fn lex(code: &str) -> Vec<Scanned> {
+        Lexer::new(Box::leak(Box::new(Source::new("test", code)))).collect()
+    }
+
+    fn types(code: &str) -> Vec<TokenType> {
+        lex(code).into_iter().map(|token| token.unwrap().node.get_type()).collect()
+    }
+
+    #[test]
+    fn tokens_are_scanned_with_their_spans() {
+        let tokens = lex("import Vec from \"collections/vec\"; // the rest is a comment");
+        let tokens: Vec<(TokenType, usize, usize)> = tokens.into_iter().map(|token| token.unwrap()).map(|token| (token.node.get_type(), token.span.start, token.span.end)).collect();
+
+        assert_eq!(tokens, vec![
+            (TokenType::Import, 0, 5),
+            (TokenType::Identifier("Vec"), 7, 9),
+            (TokenType::From, 11, 14),
+            (TokenType::StringLit("collections/vec"), 16, 32),
+            (TokenType::Semicolon, 33, 33),
+        ]);
+    }
+
+    #[test]
+    fn operators_and_literals() {
+        assert_eq!(types("a != b => ... .. 1_000u8 2.5e-3 0.1f16 'c' null i32"), vec![
+            TokenType::Identifier("a"),
+            TokenType::BangEquals,
+            TokenType::Identifier("b"),
+            TokenType::Arrow,
+            TokenType::TripleDot,
+            TokenType::Dot,
+            TokenType::Dot,
+            TokenType::IntLit("1_000u8"),
+            TokenType::FloatLit("2.5e-3"),
+            TokenType::FloatLit("0.1f16"),
+            TokenType::Char("c"),
+            TokenType::NullLit,
+            TokenType::TypeIdentifier(SimpleType::signed(SignedIntegerTypes::Int32)),
+        ]);
+    }
+
+    #[test]
+    fn keywords_and_escaped_chars() {
+        assert_eq!(types("impl Show for dyn T match true false -> '\\n' '\\u{1F980}'"), vec![
+            TokenType::Impl,
+            TokenType::Identifier("Show"),
+            TokenType::For,
+            TokenType::Dyn,
+            TokenType::Identifier("T"),
+            TokenType::Match,
+            TokenType::BoolLit(true),
+            TokenType::BoolLit(false),
+            TokenType::ThinArrow,
+            TokenType::Char("\\n"),
+            TokenType::Char("\\u{1F980}"),
+        ]);
+    }
+
+    #[test]
+    fn malformed_literals_are_lex_errors() {
+        for code in [ "\"unterminated", "''", "'ab'", "'\\q'", "1.5u8", "3e", "#" ] {
+            assert!(lex(code).iter().any(|token| token.is_err()), "{}", code);
+        }
+    }
 }
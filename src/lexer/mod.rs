@@ -1,5 +1,6 @@
 use std::{ fmt::{ Display, Formatter, Result }, borrow::{ Borrow }, hash::{ Hash, Hasher } };
 
+#[allow(clippy::module_inception)]
 pub mod lexer;
 pub mod token;
 
@@ -73,6 +74,20 @@ impl Source {
     pub fn slice(&self, location: Span) -> &str {
         &self.code[ location.start ..= location.end ]
     }
+
+    // the 1-based number and the text of the line `offset` is on
+    pub fn line_at(&self, offset: usize) -> (usize, &str) {
+        let mut offset = offset.min(self.code.len());
+
+        while !self.code.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let start = self.code[.. offset].rfind('\n').map_or(0, |newline| newline + 1);
+        let end = self.code[offset ..].find('\n').map_or(self.code.len(), |newline| offset + newline);
+
+        (self.code[.. start].matches('\n').count() + 1, &self.code[start .. end])
+    }
 }
 
 impl Hash for Source {
@@ -12,6 +12,28 @@ pub struct Token {
 
 }
 
+impl Token {
+    pub fn new(token_type: TokenType, location: Span, text: &'static str) -> Self {
+        Self {
+            token_type,
+            location,
+            text,
+        }
+    }
+
+    #[inline] pub fn get_type(&self) -> TokenType {
+        self.token_type
+    }
+
+    #[inline] pub fn get_location(&self) -> Span {
+        self.location
+    }
+
+    #[inline] pub fn get_text(&self) -> &'static str {
+        self.text
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TokenType {
     LeftParen,              // (
@@ -42,6 +64,11 @@ pub enum TokenType {
     Inline,                 // inline
     Abstract,               // abstract
     Mut,                    // mut
+    Pub,                    // pub
+    Impl,                   // impl
+    For,                    // for
+    Match,                  // match
+    Dyn,                    // dyn
 
     Bang,                   // !
     Equals,                 // =
@@ -70,8 +97,10 @@ pub enum TokenType {
     AmpersandAmpersand,     // &&
     PipePipe,               // ||
     Arrow,                  // =>
+    ThinArrow,              // ->
 
     NullLit,
+    BoolLit(bool),
     Identifier(&'static str),
     IntLit(&'static str),
     FloatLit(&'static str),
@@ -80,6 +109,63 @@ pub enum TokenType {
     TypeIdentifier(SimpleType),
 }
 
+impl TokenType {
+    // the keyword, builtin type, `null`, `true` or `false` spelled by an identifier-like word
+    pub fn keyword(word: &str) -> Option<Self> {
+        let token_type = match word {
+            "let" => Self::Let,
+            "fn" => Self::Fn,
+            "if" => Self::If,
+            "else" => Self::Else,
+            "import" => Self::Import,
+            "from" => Self::From,
+            "return" => Self::Return,
+            "extern" => Self::Extern,
+            "while" => Self::While,
+            "type" => Self::Type,
+            "struct" => Self::Struct,
+            "trait" => Self::Trait,
+            "enum" => Self::Enum,
+            "new" => Self::New,
+            "delete" => Self::Delete,
+            "sizeof" => Self::Sizeof,
+            "as" => Self::As,
+            "static" => Self::Static,
+            "inline" => Self::Inline,
+            "abstract" => Self::Abstract,
+            "mut" => Self::Mut,
+            "pub" => Self::Pub,
+            "impl" => Self::Impl,
+            "for" => Self::For,
+            "match" => Self::Match,
+            "dyn" => Self::Dyn,
+            "null" => Self::NullLit,
+            "true" => Self::BoolLit(true),
+            "false" => Self::BoolLit(false),
+
+            "i8" => Self::TypeIdentifier(SimpleType::signed(SignedIntegerTypes::Int8)),
+            "i16" => Self::TypeIdentifier(SimpleType::signed(SignedIntegerTypes::Int16)),
+            "i32" => Self::TypeIdentifier(SimpleType::signed(SignedIntegerTypes::Int32)),
+            "i64" => Self::TypeIdentifier(SimpleType::signed(SignedIntegerTypes::Int64)),
+            "u8" => Self::TypeIdentifier(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)),
+            "u16" => Self::TypeIdentifier(SimpleType::unsigned(UnsignedIntegerTypes::Uint16)),
+            "u32" => Self::TypeIdentifier(SimpleType::unsigned(UnsignedIntegerTypes::Uint32)),
+            "u64" => Self::TypeIdentifier(SimpleType::unsigned(UnsignedIntegerTypes::Uint64)),
+            "f16" => Self::TypeIdentifier(SimpleType::float(FloatingPointTypes::FP16)),
+            "f32" => Self::TypeIdentifier(SimpleType::float(FloatingPointTypes::FP32)),
+            "f64" => Self::TypeIdentifier(SimpleType::float(FloatingPointTypes::FP64)),
+            "char" => Self::TypeIdentifier(SimpleType::Char),
+            "bool" => Self::TypeIdentifier(SimpleType::Bool),
+            "string" => Self::TypeIdentifier(SimpleType::String),
+            "void" => Self::TypeIdentifier(SimpleType::Void),
+
+            _ => return None,
+        };
+
+        Some(token_type)
+    }
+}
+
 impl Display for TokenType {
     fn fmt(&self, f: &mut Formatter) -> Result {
         match self {
@@ -111,6 +197,11 @@ impl Display for TokenType {
             Self::Inline => write!(f, "inline"),
             Self::Abstract => write!(f, "abstract"),
             Self::Mut => write!(f, "mut"),
+            Self::Pub => write!(f, "pub"),
+            Self::Impl => write!(f, "impl"),
+            Self::For => write!(f, "for"),
+            Self::Match => write!(f, "match"),
+            Self::Dyn => write!(f, "dyn"),
 
             Self::Bang => write!(f, "!"),
             Self::Equals => write!(f, "="),
@@ -139,8 +230,10 @@ impl Display for TokenType {
             Self::AmpersandAmpersand => write!(f, "&&"),
             Self::PipePipe => write!(f, "||"),
             Self::Arrow => write!(f, "=>"),
+            Self::ThinArrow => write!(f, "->"),
 
             Self::NullLit => write!(f, "null"),
+            Self::BoolLit(value) => write!(f, "{}", value),
             Self::Identifier(ref ident) => write!(f, "{}", ident),
             Self::IntLit(lit) | Self::FloatLit(lit) => write!(f, "{}", lit),
             Self::StringLit(ref string) => write!(f, "{}", string),
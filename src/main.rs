@@ -1,11 +1,135 @@
 pub mod types;
 pub mod lexer;
 pub mod error;
+pub mod ast;
+pub mod parser;
+pub mod modules;
+pub mod resolve;
+pub mod bytecode;
+pub mod vm;
+pub mod compiler;
+
+use std::{ path::{ Path } };
 
 use types::*;
 use error::*;
+use bytecode::{ disasm::{ Disassembler }, file };
+use compiler::{ pipeline };
+use lexer::{ Source };
+use modules::{ SOURCE_EXTENSION };
+use vm::{ Vm };
+
+const USAGE: &str = "usage: vm compile <main.syn> -o <module.synb>\n       vm disasm <module.synb> [--source <file>]\n       vm run <module.synb> [--debug-heap]";
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("compile") => compile(&args[1 ..]),
+        Some("disasm") => disasm(&args[1 ..]),
+        Some("run") => run(&args[1 ..]),
+        Some(_) => usage(),
+        None => {
+            demo();
+            Ok(())
+        },
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(error.get_error_type().exit_code());
+    }
+}
+
+fn usage() -> std::result::Result<(), Error> {
+    eprintln!("{}", USAGE);
+    std::process::exit(1);
+}
+
+// compiles the module in `main.syn` and every module it imports, which are looked up next to it, into one
+// bytecode module
+fn compile(args: &[String]) -> std::result::Result<(), Error> {
+    let (source, output) = match args {
+        [ source, flag, output ] if flag == "-o" => (Path::new(source), Path::new(output)),
+        _ => return usage(),
+    };
+
+    let entry = match (source.extension(), source.file_stem().and_then(|stem| stem.to_str())) {
+        (Some(extension), Some(entry)) if extension == SOURCE_EXTENSION => entry,
+        _ => return Err(Error::io(codes::READ_FAILED, &format!("`{}` is not a `.{}` source file", source.display(), SOURCE_EXTENSION))),
+    };
+
+    let root = source.parent().unwrap_or(Path::new(""));
+    let program = pipeline::compile_files(root, entry)?;
+
+    file::save(&program, output)
+}
+
+// prints the listing of a compiled module, with source lines if the file it was compiled from is given
+fn disasm(args: &[String]) -> std::result::Result<(), Error> {
+    let (module, source) = match args {
+        [ module ] => (module, None),
+        [ module, flag, source ] if flag == "--source" => (module, Some(source)),
+        _ => return usage(),
+    };
+
+    let program = file::load(Path::new(module))?;
+
+    let source = match source {
+        Some(path) => {
+            let code = std::fs::read_to_string(path).map_err(|error| {
+                Error::io(codes::READ_FAILED, &format!("cannot read `{}`: {}", path, error))
+            })?;
+
+            Some(Source::new(path, &code))
+        },
+
+        None => None,
+    };
+
+    let disassembler = Disassembler::new(&program);
+
+    let listing = match &source {
+        Some(source) => disassembler.with_source(source).program(),
+        None => disassembler.program(),
+    };
+
+    print!("{}", listing);
+
+    Ok(())
+}
+
+// runs `main` of a compiled module and prints what it returns; with a debug heap, what it leaked is
+// reported once it exits
+fn run(args: &[String]) -> std::result::Result<(), Error> {
+    let (module, is_debug) = match args {
+        [ module ] => (module, false),
+        [ module, flag ] if flag == "--debug-heap" => (module, true),
+        _ => return usage(),
+    };
+
+    let program = file::load(Path::new(module))?;
+
+    let mut vm = match is_debug {
+        true => Vm::new(&program).with_debug_heap(),
+        false => Vm::new(&program),
+    };
+
+    let result = vm.call("main", &[]);
+
+    if let Ok(Some(value)) = &result {
+        println!("{}", value);
+    }
+
+    // what was left allocated is reported even when the program failed, as that is when it matters most
+    if let Some(report) = vm.leak_report() {
+        eprintln!("{}", report);
+    }
+
+    result.map(|_| ())
+}
+
+fn demo() {
     let ptr = Type::Complex(ComplexType::Pointer(Pointer::new(8, 8, 2, SimpleType::Char)));
     println!("{}", ptr);
 
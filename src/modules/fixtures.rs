@@ -0,0 +1,44 @@
+use std::{ path::{ Path, PathBuf }, sync::{ atomic::{ AtomicUsize, Ordering } } };
+
+use super::{ SOURCE_EXTENSION };
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+// a fresh directory below the system's temporary one, removed with everything in it once dropped
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("synthium-{}-{}-{}", name, std::process::id(), CREATED.fetch_add(1, Ordering::Relaxed)));
+
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self {
+            path,
+        }
+    }
+
+    // writes a source file for every module path and its code
+    pub(crate) fn with_modules(self, files: &[ (&str, &str) ]) -> Self {
+        for (path, code) in files {
+            let file = self.path.join(format!("{}.{}", path, SOURCE_EXTENSION));
+
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, code).unwrap();
+        }
+
+        self
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
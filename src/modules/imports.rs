@@ -0,0 +1,106 @@
+use std::{ iter::{ Peekable } };
+
+use crate::{ ast::{ ImportDecl }, error::{ * }, lexer::{ Span, Spanned, lexer::{ Lexer }, token::{ Token, TokenType } } };
+
+type Parsed<T> = std::result::Result<T, Error>;
+
+// reads the `import ... from "...";` declarations at the top of a file and stops at the first token that starts anything else
+pub fn parse_imports(lexer: Lexer) -> Parsed<Vec<ImportDecl>> {
+    let mut tokens = lexer.peekable();
+    let mut imports: Vec<ImportDecl> = vec![];
+
+    while let Some(token) = peek(&mut tokens)? {
+        if token.node.get_type() != TokenType::Import {
+            break;
+        }
+
+        tokens.next();
+        imports.push(parse_import(&mut tokens, token.span)?);
+    }
+
+    Ok(imports)
+}
+
+fn parse_import(tokens: &mut Peekable<Lexer>, start: Span) -> Parsed<ImportDecl> {
+    let mut names: Vec<Spanned<&'static str>> = vec![];
+
+    loop {
+        let token = expect(tokens, start, "a name to import")?;
+
+        match token.node.get_type() {
+            TokenType::Identifier(name) => names.push(Spanned::from_span(token.span, name)),
+            _ => return Err(unexpected(&token, "a name to import")),
+        }
+
+        let token = expect(tokens, start, "`,` or `from`")?;
+
+        match token.node.get_type() {
+            TokenType::Comma => continue,
+            TokenType::From => break,
+            _ => return Err(unexpected(&token, "`,` or `from`")),
+        }
+    }
+
+    let token = expect(tokens, start, "a module path")?;
+    let TokenType::StringLit(path) = token.node.get_type() else {
+        return Err(unexpected(&token, "a module path"));
+    };
+
+    let semicolon = expect(tokens, start, "`;`")?;
+
+    if semicolon.node.get_type() != TokenType::Semicolon {
+        return Err(unexpected(&semicolon, "`;`"));
+    }
+
+    Ok(ImportDecl {
+        names,
+        path: Spanned::from_span(token.span, path),
+    })
+}
+
+fn peek(tokens: &mut Peekable<Lexer>) -> Parsed<Option<Spanned<Token>>> {
+    match tokens.peek() {
+        Some(Ok(token)) => Ok(Some(*token)),
+        Some(Err(error)) => Err(Error::from_span(error.span, error.node, "unexpected character")),
+        None => Ok(None),
+    }
+}
+
+fn expect(tokens: &mut Peekable<Lexer>, start: Span, expected: &str) -> Parsed<Spanned<Token>> {
+    let token = peek(tokens)?.ok_or_else(|| Error::from_span(start, ErrorType::ParseError, &format!("expected {} but the file ended", expected)))?;
+
+    tokens.next();
+
+    Ok(token)
+}
+
+fn unexpected(token: &Spanned<Token>, expected: &str) -> Error {
+    Error::from_span(token.span, ErrorType::ParseError, &format!("expected {}, found `{}`", expected, token.node.get_type()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ lexer::{ Source } };
+
+    fn imports(code: &str) -> Parsed<Vec<ImportDecl>> {
+        parse_imports(Lexer::new(Box::leak(Box::new(Source::new("test", code)))))
+    }
+
+    #[test]
+    fn leading_imports_are_parsed() {
+        let decls = imports("import Vec, Map from \"collections\";\nimport print from \"io\";\nfn main() {}").unwrap();
+        let decls: Vec<(Vec<&str>, &str)> = decls.iter().map(|decl| (decl.names.iter().map(|name| name.node).collect(), decl.path.node)).collect();
+
+        assert_eq!(decls, vec![ (vec![ "Vec", "Map" ], "collections"), (vec![ "print" ], "io") ]);
+    }
+
+    #[test]
+    fn malformed_imports_are_parse_errors() {
+        let error = imports("import Vec \"collections\";").unwrap_err();
+
+        assert_eq!(error.get_error_type(), ErrorType::ParseError);
+        assert_eq!(error.get_message(), "expected `,` or `from`, found `collections`");
+        assert!(imports("import Vec from \"collections\"").is_err());
+    }
+}
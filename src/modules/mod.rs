@@ -0,0 +1,204 @@
+use std::{ collections::{ HashMap }, path::{ Path, PathBuf } };
+
+use crate::{ ast::{ ImportDecl }, error::{ * }, lexer::{ Span, Source, lexer::{ Lexer } } };
+
+pub mod imports;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+use imports::{ parse_imports };
+
+type Loaded<T> = std::result::Result<T, Error>;
+
+pub const SOURCE_EXTENSION: &str = "syn";
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    // the import path of the module, e.g. `collections/vec` for `<root>/collections/vec.syn`
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub source: &'static Source,
+    pub imports: Vec<ImportDecl>,
+}
+
+// a name brought into scope by an import, and the module it comes from
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ImportedName {
+    pub name: &'static str,
+    pub module: &'static str,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleGraph {
+    root: PathBuf,
+    // every module reachable from the entry, each after the modules it imports
+    modules: Vec<Module>,
+    indices: HashMap<&'static str, usize>,
+}
+
+impl ModuleGraph {
+    // loads `entry` and everything it imports, directly or not, from files below `root`
+    pub fn load(root: &Path, entry: &str) -> Loaded<Self> {
+        let mut graph = Self {
+            root: root.to_path_buf(),
+            modules: vec![],
+            indices: HashMap::new(),
+        };
+
+        let entry: &'static str = Box::leak(entry.to_owned().into_boxed_str());
+
+        graph.visit(entry, Span::new(0, 0), &mut vec![])?;
+
+        Ok(graph)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // dependencies come before the modules that import them
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.indices.get(name).map(|index| &self.modules[*index])
+    }
+
+    pub fn dependencies(&self, name: &str) -> Vec<&'static str> {
+        self.module(name).map_or_else(Vec::new, |module| module.imports.iter().map(|import| import.path.node).collect())
+    }
+
+    // the names `name` imports, for name resolution to look up next to its own declarations
+    pub fn imported_names(&self, name: &str) -> Loaded<HashMap<&'static str, ImportedName>> {
+        let mut names: HashMap<&'static str, ImportedName> = HashMap::new();
+
+        for import in self.module(name).map_or(&[][..], |module| &module.imports[..]) {
+            for imported in &import.names {
+                let previous = names.insert(imported.node, ImportedName {
+                    name: imported.node,
+                    module: import.path.node,
+                    span: imported.span,
+                });
+
+                if let Some(previous) = previous {
+                    return Err(Error::from_span(imported.span, ErrorType::ImportError, &format!("`{}` is imported more than once, first from `{}`", imported.node, previous.module)).with_code(codes::CONFLICTING_IMPORT));
+                }
+            }
+        }
+
+        Ok(names)
+    }
+
+    // depth-first, with `stack` holding the chain of imports that led to `name`
+    fn visit(&mut self, name: &'static str, span: Span, stack: &mut Vec<&'static str>) -> Loaded<()> {
+        if self.indices.contains_key(name) {
+            return Ok(());
+        }
+
+        if let Some(start) = stack.iter().position(|other| *other == name) {
+            let mut cycle: Vec<&str> = stack[start ..].to_vec();
+
+            cycle.push(name);
+
+            return Err(Error::from_span(span, ErrorType::ImportError, &format!("module `{}` imports itself: `{}`", name, cycle.join("` -> `"))).with_code(codes::IMPORT_CYCLE));
+        }
+
+        let path = self.resolve(name, span)?;
+        let code = std::fs::read_to_string(&path).map_err(|error| {
+            let message = format!("cannot load module `{}` from `{}`: {}", name, path.display(), error);
+
+            match error.kind() {
+                std::io::ErrorKind::NotFound => Error::from_span(span, ErrorType::ImportError, &message).with_code(codes::MODULE_NOT_FOUND),
+                _ => Error::io(codes::READ_FAILED, &message),
+            }
+        })?;
+
+        let source: &'static Source = Box::leak(Box::new(Source::new(name, &code)));
+        let imports = parse_imports(Lexer::new(source))?;
+
+        stack.push(name);
+
+        for import in &imports {
+            self.visit(import.path.node, import.path.span, stack)?;
+        }
+
+        stack.pop();
+
+        self.indices.insert(name, self.modules.len());
+        self.modules.push(Module {
+            name,
+            path,
+            source,
+            imports,
+        });
+
+        Ok(())
+    }
+
+    // import paths are `/`-separated and may not leave the project root
+    fn resolve(&self, name: &str, span: Span) -> Loaded<PathBuf> {
+        let is_valid = |component: &str| !component.is_empty() && component != "." && component != ".." && !component.contains('\\');
+
+        if !name.split('/').all(is_valid) {
+            return Err(Error::from_span(span, ErrorType::ImportError, &format!("invalid import path `{}`; paths are relative to the project root, like `collections/vec`", name)).with_code(codes::INVALID_IMPORT_PATH));
+        }
+
+        Ok(self.root.join(format!("{}.{}", name, SOURCE_EXTENSION)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ *, fixtures::{ TempDir } };
+
+    #[test]
+    fn modules_are_loaded_after_their_dependencies() {
+        let root = TempDir::new("graph").with_modules(&[
+            ("main", "import Vec from \"collections/vec\";\nimport print from \"io\";"),
+            ("collections/vec", "import alloc from \"memory\";"),
+            ("io", "import alloc from \"memory\";"),
+            ("memory", ""),
+        ]);
+
+        let graph = ModuleGraph::load(root.path(), "main").unwrap();
+        let order: Vec<&str> = graph.modules().iter().map(|module| module.name).collect();
+        let names = graph.imported_names("main").unwrap();
+
+        assert_eq!(order, vec![ "memory", "collections/vec", "io", "main" ]);
+        assert_eq!(graph.dependencies("main"), vec![ "collections/vec", "io" ]);
+        assert_eq!(names["Vec"].module, "collections/vec");
+        assert_eq!(names["print"].module, "io");
+    }
+
+    #[test]
+    fn import_cycles_report_the_whole_path() {
+        let root = TempDir::new("cycle").with_modules(&[
+            ("main", "import a from \"a\";"),
+            ("a", "import b from \"b\";"),
+            ("b", "import main from \"main\";"),
+        ]);
+
+        let error = ModuleGraph::load(root.path(), "main").unwrap_err();
+
+        assert_eq!(error.get_message(), "module `main` imports itself: `main` -> `a` -> `b` -> `main`");
+    }
+
+    #[test]
+    fn bad_paths_are_reported() {
+        let root = TempDir::new("paths").with_modules(&[
+            ("main", "import x from \"../outside\";"),
+            ("missing", "import x from \"nowhere\";"),
+        ]);
+
+        let outside = ModuleGraph::load(root.path(), "main").unwrap_err();
+        let missing = ModuleGraph::load(root.path(), "missing").unwrap_err();
+
+        assert!(outside.get_message().starts_with("invalid import path `../outside`"));
+        assert!(missing.get_message().starts_with("cannot load module `nowhere` from"));
+        assert_eq!((outside.get_error_type(), outside.get_code()), (ErrorType::ImportError, codes::INVALID_IMPORT_PATH));
+        assert_eq!((missing.get_error_type(), missing.get_code()), (ErrorType::ImportError, codes::MODULE_NOT_FOUND));
+    }
+}
@@ -0,0 +1,528 @@
+use super::{ *, types::{ is_capitalised } };
+use crate::{ types::{ SignedIntegerTypes, UnsignedIntegerTypes } };
+
+impl Parser {
+    pub(super) fn block(&mut self) -> Parsed<Block> {
+        let mut stmts = vec![];
+
+        self.expect(TokenType::LeftBrace, "`{`")?;
+
+        while !self.eat(TokenType::RightBrace) {
+            if self.peek().is_none() {
+                return Err(self.ended("a statement or `}`"));
+            }
+
+            stmts.push(self.statement()?);
+        }
+
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> Parsed<Stmt> {
+        let start = self.start();
+
+        let kind = match self.peek() {
+            Some(TokenType::Let) => {
+                self.position += 1;
+
+                let is_mutable = self.eat(TokenType::Mut);
+                let name = self.identifier("a variable name")?.node;
+                let ty = match self.eat(TokenType::Colon) {
+                    true => Some(self.type_expr()?),
+                    false => None,
+                };
+
+                self.expect(TokenType::Equals, "`=`")?;
+
+                let value = self.expr()?;
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                StmtKind::Let(name, is_mutable, ty, value)
+            },
+
+            Some(TokenType::Return) => {
+                self.position += 1;
+
+                let value = match self.peek() == Some(TokenType::Semicolon) {
+                    true => None,
+                    false => Some(self.expr()?),
+                };
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                StmtKind::Return(value)
+            },
+
+            Some(TokenType::If) => {
+                self.position += 1;
+                self.if_stmt()?
+            },
+
+            Some(TokenType::While) => {
+                self.position += 1;
+
+                StmtKind::While(self.expr()?, self.block()?)
+            },
+
+            Some(TokenType::Delete) => {
+                self.position += 1;
+
+                let pointer = self.expr()?;
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                StmtKind::Delete(pointer)
+            },
+
+            _ => {
+                let expr = self.expr()?;
+
+                let kind = match self.eat(TokenType::Equals) {
+                    true => StmtKind::Assign(expr, self.expr()?),
+                    false => StmtKind::Expr(expr),
+                };
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                kind
+            },
+        };
+
+        Ok(Spanned::from_span(self.span_from(start), kind))
+    }
+
+    // what follows `if`; `else if` becomes an `else` block holding the nested `if`
+    fn if_stmt(&mut self) -> Parsed<StmtKind> {
+        let condition = self.expr()?;
+        let then = self.block()?;
+
+        let otherwise = match (self.eat(TokenType::Else), self.peek()) {
+            (false, _) => None,
+
+            (true, Some(TokenType::If)) => {
+                let start = self.start();
+
+                self.position += 1;
+
+                let nested = self.if_stmt()?;
+
+                Some(vec![ Spanned::from_span(self.span_from(start), nested) ])
+            },
+
+            (true, _) => Some(self.block()?),
+        };
+
+        Ok(StmtKind::If(condition, then, otherwise))
+    }
+
+    pub(super) fn expr(&mut self) -> Parsed<Expr> {
+        self.binary(0)
+    }
+
+    // binary operators are left associative; a higher level binds tighter
+    fn binary(&mut self, min_level: u8) -> Parsed<Expr> {
+        let mut lhs = self.cast()?;
+
+        while let Some((op, level)) = self.peek().and_then(binary_op).filter(|(_, level)| *level >= min_level) {
+            self.position += 1;
+
+            let rhs = self.binary(level + 1)?;
+
+            lhs = Spanned::new(lhs.span.start, rhs.span.end, ExprKind::Binary(Box::new(lhs), op, Box::new(rhs)));
+        }
+
+        Ok(lhs)
+    }
+
+    // `as` binds tighter than every binary operator and looser than the unary ones
+    fn cast(&mut self) -> Parsed<Expr> {
+        let mut value = self.unary()?;
+
+        while self.eat(TokenType::As) {
+            let ty = self.type_expr()?;
+
+            value = Spanned::new(value.span.start, ty.span.end, ExprKind::Cast(Box::new(value), ty));
+        }
+
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Parsed<Expr> {
+        let start = self.start();
+
+        let op = match self.peek() {
+            Some(TokenType::Minus) => UnaryOp::Negate,
+            Some(TokenType::Bang) => UnaryOp::Not,
+            Some(TokenType::Star) => UnaryOp::Deref,
+
+            Some(TokenType::Ampersand) if self.peek_at(1) == Some(TokenType::Mut) => {
+                self.position += 1;
+
+                UnaryOp::AddressOfMut
+            },
+
+            Some(TokenType::Ampersand) => UnaryOp::AddressOf,
+
+            Some(TokenType::Sizeof) => {
+                self.position += 1;
+
+                return self.sizeof(start);
+            },
+
+            _ => return self.postfix(),
+        };
+
+        self.position += 1;
+
+        let operand = self.unary()?;
+
+        Ok(Spanned::from_span(self.span_from(start), ExprKind::Unary(op, Box::new(operand))))
+    }
+
+    // `sizeof(T)` when the parentheses hold a type, `sizeof expr` otherwise
+    fn sizeof(&mut self, start: Span) -> Parsed<Expr> {
+        let operand = match self.peek() == Some(TokenType::LeftParen) && self.starts_type(1) {
+            true => {
+                self.position += 1;
+
+                let ty = self.type_expr()?;
+
+                self.expect(TokenType::RightParen, "`)`")?;
+
+                SizeofOperand::Type(ty)
+            },
+
+            false => SizeofOperand::Expr(Box::new(self.unary()?)),
+        };
+
+        Ok(Spanned::from_span(self.span_from(start), ExprKind::Sizeof(operand)))
+    }
+
+    // calls, fields, method calls and `?`
+    fn postfix(&mut self) -> Parsed<Expr> {
+        let mut value = self.primary()?;
+
+        loop {
+            let start = value.span;
+
+            let kind = match self.peek() {
+                Some(TokenType::LeftParen) => {
+                    self.position += 1;
+
+                    ExprKind::Call(Box::new(value), self.separated(TokenType::RightParen, Self::expr)?)
+                },
+
+                Some(TokenType::Dot) => {
+                    self.position += 1;
+
+                    let name = self.identifier("a field or method name")?;
+
+                    match self.eat(TokenType::LeftParen) {
+                        true => ExprKind::MethodCall(Box::new(value), name, self.separated(TokenType::RightParen, Self::expr)?),
+                        false => ExprKind::Field(Box::new(value), name.node),
+                    }
+                },
+
+                Some(TokenType::Question) => {
+                    self.position += 1;
+
+                    ExprKind::Unwrap(Box::new(value))
+                },
+
+                _ => return Ok(value),
+            };
+
+            value = Spanned::from_span(self.span_from(start), kind);
+        }
+    }
+
+    fn primary(&mut self) -> Parsed<Expr> {
+        let token = self.next("an expression")?;
+        let start = token.span;
+
+        if let Some(literal) = literal(&token)? {
+            return Ok(Spanned::from_span(start, ExprKind::Literal(literal)));
+        }
+
+        let kind = match token.node.get_type() {
+            // `Enum.Variant(payload...)`; values are not capitalised, so `Name.Name` can only name a variant
+            TokenType::Identifier(name) if is_capitalised(name) && self.peek() == Some(TokenType::Dot) && matches!(self.peek_at(1), Some(TokenType::Identifier(variant)) if is_capitalised(variant)) => {
+                self.position += 1;
+
+                let variant = self.identifier("a variant name")?.node;
+                let payload = match self.eat(TokenType::LeftParen) {
+                    true => self.separated(TokenType::RightParen, Self::expr)?,
+                    false => vec![],
+                };
+
+                ExprKind::Variant(name, variant, payload)
+            },
+
+            TokenType::Identifier(name) => ExprKind::Identifier(name),
+
+            TokenType::LeftParen => {
+                let inner = self.expr()?;
+
+                self.expect(TokenType::RightParen, "`)`")?;
+
+                inner.node
+            },
+
+            TokenType::New => ExprKind::New(self.type_expr()?),
+
+            TokenType::Match => {
+                let scrutinee = self.expr()?;
+
+                self.expect(TokenType::LeftBrace, "`{`")?;
+
+                let arms = self.separated(TokenType::RightBrace, |this| {
+                    let pattern = this.pattern()?;
+
+                    this.expect(TokenType::Arrow, "`=>`")?;
+
+                    Ok(MatchArm { pattern, body: this.expr()? })
+                })?;
+
+                ExprKind::Match(Box::new(scrutinee), arms)
+            },
+
+            TokenType::Fn => ExprKind::Closure(Box::new(self.closure()?)),
+
+            _ => return Err(unexpected(&token, "an expression")),
+        };
+
+        Ok(Spanned::from_span(self.span_from(start), kind))
+    }
+
+    // what follows `fn` in an expression: `fn[&a, &b](params) -> ret { ... }`
+    fn closure(&mut self) -> Parsed<ClosureExpr> {
+        let by_ref = match self.eat(TokenType::LeftBracket) {
+            true => self.separated(TokenType::RightBracket, |this| {
+                this.expect(TokenType::Ampersand, "`&`")?;
+                this.identifier("a variable to capture by reference")
+            })?,
+
+            false => vec![],
+        };
+
+        self.expect(TokenType::LeftParen, "`(`")?;
+
+        let params = self.params()?;
+        let ret = self.return_type()?;
+
+        Ok(ClosureExpr { by_ref, params, ret, body: self.block()? })
+    }
+
+    fn pattern(&mut self) -> Parsed<Pattern> {
+        let token = self.next("a pattern")?;
+        let start = token.span;
+
+        if let Some(literal) = literal(&token)? {
+            return Ok(Spanned::from_span(start, PatternKind::Literal(literal)));
+        }
+
+        let kind = match token.node.get_type() {
+            TokenType::Identifier("_") => PatternKind::Wildcard,
+            TokenType::Identifier(name) if self.eat(TokenType::LeftParen) => PatternKind::Variant(name, self.separated(TokenType::RightParen, Self::pattern)?),
+            // a payload-less variant is told apart from a binding by the checker, which knows the scrutinee's variants
+            TokenType::Identifier(name) => PatternKind::Binding(name),
+            _ => return Err(unexpected(&token, "a pattern")),
+        };
+
+        Ok(Spanned::from_span(self.span_from(start), kind))
+    }
+}
+
+fn binary_op(token: TokenType) -> Option<(BinaryOp, u8)> {
+    let op = match token {
+        TokenType::PipePipe => (BinaryOp::Or, 0),
+        TokenType::AmpersandAmpersand => (BinaryOp::And, 1),
+        TokenType::EqualsEquals => (BinaryOp::Equals, 2),
+        TokenType::BangEquals => (BinaryOp::NotEquals, 2),
+        TokenType::Smaller => (BinaryOp::Smaller, 2),
+        TokenType::Greater => (BinaryOp::Greater, 2),
+        TokenType::SmallerEquals => (BinaryOp::SmallerEquals, 2),
+        TokenType::GreaterEquals => (BinaryOp::GreaterEquals, 2),
+        TokenType::Pipe => (BinaryOp::BitOr, 3),
+        TokenType::Ampersand => (BinaryOp::BitAnd, 4),
+        TokenType::Plus => (BinaryOp::Add, 5),
+        TokenType::Minus => (BinaryOp::Sub, 5),
+        TokenType::Star => (BinaryOp::Mul, 6),
+        TokenType::Slash => (BinaryOp::Div, 6),
+        TokenType::Modulo => (BinaryOp::Mod, 6),
+        _ => return None,
+    };
+
+    Some(op)
+}
+
+// the literal a token spells, if it is one
+fn literal(token: &Spanned<Token>) -> Parsed<Option<Literal>> {
+    let literal = match token.node.get_type() {
+        TokenType::IntLit(text) => int_literal(text, token.span)?,
+
+        TokenType::FloatLit(text) => Literal::parse_float(text).ok_or_else(|| {
+            Error::from_span(token.span, ErrorType::ParseError, &format!("float literal `{}` is out of range", text))
+        })?,
+
+        TokenType::BoolLit(value) => Literal::Bool(value),
+        TokenType::NullLit => Literal::Null,
+        // the lexer has made sure it is a single character or escape
+        TokenType::Char(text) => Literal::Char(unescape(text, token.span)?.chars().next().unwrap_or_default()),
+        TokenType::StringLit(text) => Literal::String(Box::leak(unescape(text, token.span)?.into_boxed_str())),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(literal))
+}
+
+// `1_000u8`: digits with optional separators, then an optional suffix the lexer has checked
+fn int_literal(text: &str, span: Span) -> Parsed<Literal> {
+    let digits = text.replace('_', "");
+    let (digits, suffix) = digits.split_at(digits.find(|val: char| val.is_ascii_alphabetic()).unwrap_or(digits.len()));
+
+    let suffix = match suffix {
+        "" => None,
+        "i8" => Some(IntegerSuffix::Signed(SignedIntegerTypes::Int8)),
+        "i16" => Some(IntegerSuffix::Signed(SignedIntegerTypes::Int16)),
+        "i32" => Some(IntegerSuffix::Signed(SignedIntegerTypes::Int32)),
+        "i64" => Some(IntegerSuffix::Signed(SignedIntegerTypes::Int64)),
+        "u8" => Some(IntegerSuffix::Unsigned(UnsignedIntegerTypes::Uint8)),
+        "u16" => Some(IntegerSuffix::Unsigned(UnsignedIntegerTypes::Uint16)),
+        "u32" => Some(IntegerSuffix::Unsigned(UnsignedIntegerTypes::Uint32)),
+        "u64" => Some(IntegerSuffix::Unsigned(UnsignedIntegerTypes::Uint64)),
+        _ => return Err(Error::from_span(span, ErrorType::ParseError, &format!("invalid suffix `{}` on integer literal", suffix))),
+    };
+
+    let value = digits.parse::<u64>().map_err(|_| {
+        Error::from_span(span, ErrorType::ParseError, &format!("integer literal `{}` does not fit in 64 bits", text))
+    })?;
+
+    Ok(Literal::Int(value, suffix))
+}
+
+// the text between the quotes of a string or char literal, with its escapes replaced
+fn unescape(text: &str, span: Span) -> Parsed<String> {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(val) = chars.next() {
+        if val != '\\' {
+            value.push(val);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => Some('\n'),
+            Some('t') => Some('\t'),
+            Some('r') => Some('\r'),
+            Some('0') => Some('\0'),
+            Some(quoted @ ('\\' | '\'' | '"')) => Some(quoted),
+
+            // `\u{1F600}`
+            Some('u') => {
+                let rest = chars.as_str();
+                let close = rest.find('}').filter(|_| rest.starts_with('{'));
+                let escaped = close.and_then(|close| u32::from_str_radix(&rest[1 .. close], 16).ok()).and_then(char::from_u32);
+
+                if let Some(close) = close {
+                    chars = rest[close + 1 ..].chars();
+                }
+
+                escaped
+            },
+
+            _ => None,
+        };
+
+        match escaped {
+            Some(escaped) => value.push(escaped),
+            None => return Err(Error::from_span(span, ErrorType::ParseError, "invalid escape sequence")),
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ *, super::tests::{ parse } };
+
+    // the body of `fn f() { ... }`
+    fn body(code: &str) -> Parsed<Block> {
+        match parse(&format!("fn f() {{ {} }}", code))?.remove(0) {
+            Item::Fn(decl) => Ok(decl.body.unwrap_or_default()),
+            item => panic!("expected a function, found {:?}", item),
+        }
+    }
+
+    // an expression as a parenthesised prefix form, to check how it is grouped
+    fn shape(expr: &Expr) -> String {
+        match &expr.node {
+            ExprKind::Literal(Literal::Int(value, _)) => value.to_string(),
+            ExprKind::Literal(literal) => format!("{:?}", literal),
+            ExprKind::Identifier(name) => name.to_string(),
+            ExprKind::Unary(op, operand) => format!("({:?} {})", op, shape(operand)),
+            ExprKind::Binary(lhs, op, rhs) => format!("({} {} {})", op, shape(lhs), shape(rhs)),
+            ExprKind::Cast(value, _) => format!("(as {})", shape(value)),
+            ExprKind::Field(value, field) => format!("{}.{}", shape(value), field),
+            ExprKind::Call(callee, args) => format!("{}({})", shape(callee), args.iter().map(shape).collect::<Vec<_>>().join(", ")),
+            ExprKind::MethodCall(receiver, method, args) => format!("{}.{}({})", shape(receiver), method.node, args.iter().map(shape).collect::<Vec<_>>().join(", ")),
+            ExprKind::Unwrap(value) => format!("{}?", shape(value)),
+            ExprKind::Variant(name, variant, payload) => format!("{}.{}[{}]", name, variant, payload.iter().map(shape).collect::<Vec<_>>().join(", ")),
+            ExprKind::Sizeof(SizeofOperand::Type(_)) => "sizeof(type)".to_owned(),
+            ExprKind::Sizeof(SizeofOperand::Expr(value)) => format!("sizeof {}", shape(value)),
+            other => format!("{:?}", other),
+        }
+    }
+
+    fn expr(code: &str) -> String {
+        match &body(&format!("{};", code)).unwrap()[0].node {
+            StmtKind::Expr(expr) => shape(expr),
+            stmt => panic!("expected an expression, found {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        assert_eq!(expr("-a + b * c as i64 == d || e && !f"), "(|| (== (+ (Negate a) (* b (as c))) d) (&& e (Not f)))");
+        assert_eq!(expr("a - b - c | d & e"), "(| (- (- a b) c) (& d e))");
+        assert_eq!(expr("*p.next?.value + &mut x"), "(+ (Deref p.next?.value) (AddressOfMut x))");
+        assert_eq!(expr("shape.area(1, (2 + 3) * 4)"), "shape.area(1, (* (+ 2 3) 4))");
+        assert_eq!(expr("Shape.Circle(2) == Color.Red"), "(== Shape.Circle[2] Color.Red[])");
+        assert_eq!(expr("sizeof(Point) + sizeof(x) + sizeof *p"), "(+ (+ sizeof(type) sizeof x) sizeof (Deref p))");
+    }
+
+    #[test]
+    fn statements_closures_and_matches() {
+        let stmts = body("let mut total: i32 = 0; total = total + 1; if a { } else if b { return; } else { delete p; } while x < 3 { } let f = fn[&total](y: i32) -> i32 { return y; }; let n = match s { Circle(r, _) => r, None => 0, 1 => 2, };").unwrap();
+
+        assert!(matches!(&stmts[0].node, StmtKind::Let("total", true, Some(_), _)));
+        assert!(matches!(&stmts[1].node, StmtKind::Assign(place, _) if shape(place) == "total"));
+
+        let StmtKind::If(_, _, Some(otherwise)) = &stmts[2].node else { panic!("expected an if, found {:?}", stmts[2]) };
+
+        assert!(matches!(&otherwise[..], [ Spanned { node: StmtKind::If(_, then, Some(last)), .. } ] if matches!(&then[..], [ Spanned { node: StmtKind::Return(None), .. } ]) && matches!(&last[..], [ Spanned { node: StmtKind::Delete(_), .. } ])));
+        assert!(matches!(&stmts[3].node, StmtKind::While(_, body) if body.is_empty()));
+
+        let StmtKind::Let("f", false, None, Spanned { node: ExprKind::Closure(closure), .. }) = &stmts[4].node else { panic!("expected a closure, found {:?}", stmts[4]) };
+
+        assert_eq!((closure.by_ref[0].node, closure.params[0].name.node, closure.ret.is_some(), closure.body.len()), ("total", "y", true, 1));
+
+        let StmtKind::Let("n", false, None, Spanned { node: ExprKind::Match(_, arms), .. }) = &stmts[5].node else { panic!("expected a match, found {:?}", stmts[5]) };
+        let patterns: Vec<&PatternKind> = arms.iter().map(|arm| &arm.pattern.node).collect();
+
+        assert!(matches!(&patterns[..], [ PatternKind::Variant("Circle", fields), PatternKind::Binding("None"), PatternKind::Literal(Literal::Int(1, None)) ] if matches!(&fields[..], [ Spanned { node: PatternKind::Binding("r"), .. }, Spanned { node: PatternKind::Wildcard, .. } ])));
+    }
+
+    #[test]
+    fn literals_are_decoded() {
+        assert_eq!(expr("1_000u8"), "1000");
+        assert!(matches!(&body("'\\u{1F600}';").unwrap()[0].node, StmtKind::Expr(Spanned { node: ExprKind::Literal(Literal::Char('\u{1F600}')), .. })));
+        assert!(matches!(&body("\"a\\tb\\\"\";").unwrap()[0].node, StmtKind::Expr(Spanned { node: ExprKind::Literal(Literal::String("a\tb\"")), .. })));
+        assert!(matches!(&body("0.5f32;").unwrap()[0].node, StmtKind::Expr(Spanned { node: ExprKind::Literal(Literal::Float(_, Some(_))), .. })));
+        assert_eq!(body("99999999999999999999;").unwrap_err().get_message(), "integer literal `99999999999999999999` does not fit in 64 bits");
+        assert_eq!(body("\"\\q\";").unwrap_err().get_message(), "invalid escape sequence");
+    }
+}
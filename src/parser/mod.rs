@@ -0,0 +1,386 @@
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span, Spanned, Source, lexer::{ Lexer }, token::{ Token, TokenType } } };
+
+pub mod types;
+pub mod expr;
+
+type Parsed<T> = std::result::Result<T, Error>;
+
+// parses a whole source file into the items of its module
+pub fn parse_module(source: &'static Source) -> Parsed<Vec<Item>> {
+    Parser::new(source)?.items()
+}
+
+// a recursive descent parser over the tokens of one file, which are scanned up front
+pub struct Parser {
+    tokens: Vec<Spanned<Token>>,
+    position: usize,
+}
+
+impl Parser {
+    pub fn new(source: &'static Source) -> Parsed<Self> {
+        let tokens = Lexer::new(source).collect::<std::result::Result<Vec<Spanned<Token>>, Spanned<ErrorType>>>().map_err(|error| {
+            Error::from_span(error.span, error.node, "unexpected character")
+        })?;
+
+        Ok(Self {
+            tokens,
+            position: 0,
+        })
+    }
+
+    // imports come first, as that is where the module graph looks for them
+    pub fn items(mut self) -> Parsed<Vec<Item>> {
+        let mut items: Vec<Item> = vec![];
+
+        while self.peek().is_some() {
+            let item = self.item()?;
+
+            if let Item::Import(import) = &item {
+                if items.iter().any(|item| !matches!(item, Item::Import(_))) {
+                    return Err(Error::from_span(import.path.span, ErrorType::ParseError, "imports must come before every other item"));
+                }
+            }
+
+            items.push(item);
+        }
+
+        Ok(items)
+    }
+
+    fn item(&mut self) -> Parsed<Item> {
+        let is_pub = self.eat(TokenType::Pub);
+        let token = self.next("an item")?;
+
+        match token.node.get_type() {
+            TokenType::Import if !is_pub => self.import().map(Item::Import),
+            TokenType::Struct => self.struct_decl(is_pub).map(Item::Struct),
+            TokenType::Enum => self.enum_decl(is_pub).map(Item::Enum),
+            TokenType::Trait => self.trait_decl(is_pub).map(Item::Trait),
+            TokenType::Impl if !is_pub => self.impl_decl().map(Item::Impl),
+            TokenType::Fn => self.function(is_pub, false).map(Item::Fn),
+
+            TokenType::Extern => {
+                self.expect(TokenType::Fn, "`fn`")?;
+                self.function(is_pub, true).map(Item::ExternFn)
+            },
+
+            TokenType::Type => {
+                let name = self.identifier("the name of the type alias")?;
+
+                self.expect(TokenType::Equals, "`=`")?;
+
+                let ty = self.type_expr()?;
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                Ok(Item::TypeAlias(TypeAliasDecl { is_pub, name, ty }))
+            },
+
+            TokenType::Static => {
+                let name = self.identifier("the name of the static")?;
+
+                self.expect(TokenType::Colon, "`:`")?;
+
+                let ty = self.type_expr()?;
+
+                self.expect(TokenType::Equals, "`=`")?;
+
+                let value = self.expr()?;
+
+                self.expect(TokenType::Semicolon, "`;`")?;
+
+                Ok(Item::Static(StaticDecl { is_pub, name, ty, value }))
+            },
+
+            _ => Err(unexpected(&token, "an item")),
+        }
+    }
+
+    // `import A, B from "path";`
+    fn import(&mut self) -> Parsed<ImportDecl> {
+        let mut names = vec![ self.identifier("a name to import")? ];
+
+        while self.eat(TokenType::Comma) {
+            names.push(self.identifier("a name to import")?);
+        }
+
+        self.expect(TokenType::From, "`,` or `from`")?;
+
+        let token = self.next("a module path")?;
+        let TokenType::StringLit(path) = token.node.get_type() else {
+            return Err(unexpected(&token, "a module path"));
+        };
+
+        self.expect(TokenType::Semicolon, "`;`")?;
+
+        Ok(ImportDecl {
+            names,
+            path: Spanned::from_span(token.span, path),
+        })
+    }
+
+    fn struct_decl(&mut self, is_pub: bool) -> Parsed<StructDecl> {
+        let name = self.identifier("the name of the struct")?;
+        let generics = self.generic_params()?;
+
+        self.expect(TokenType::LeftBrace, "`{`")?;
+
+        let fields = self.separated(TokenType::RightBrace, |this| {
+            let is_pub = this.eat(TokenType::Pub);
+            let name = this.identifier("a field name")?.node;
+
+            this.expect(TokenType::Colon, "`:`")?;
+
+            Ok(StructField { is_pub, name, ty: this.type_expr()? })
+        })?;
+
+        Ok(StructDecl { is_pub, name, generics, fields })
+    }
+
+    fn enum_decl(&mut self, is_pub: bool) -> Parsed<EnumDecl> {
+        let name = self.identifier("the name of the enum")?;
+        let generics = self.generic_params()?;
+
+        self.expect(TokenType::LeftBrace, "`{`")?;
+
+        let variants = self.separated(TokenType::RightBrace, |this| {
+            let name = this.identifier("a variant name")?;
+            let payload = match this.eat(TokenType::LeftParen) {
+                true => this.separated(TokenType::RightParen, Self::type_expr)?,
+                false => vec![],
+            };
+
+            Ok(EnumVariantDecl { name, payload })
+        })?;
+
+        Ok(EnumDecl { is_pub, name, generics, variants })
+    }
+
+    // methods without a body are marked `abstract`, those with one are defaults
+    fn trait_decl(&mut self, is_pub: bool) -> Parsed<TraitDecl> {
+        let name = self.identifier("the name of the trait")?;
+        let generics = self.generic_params()?;
+        let mut methods = vec![];
+
+        self.expect(TokenType::LeftBrace, "`{`")?;
+
+        while !self.eat(TokenType::RightBrace) {
+            let is_abstract = self.eat(TokenType::Abstract);
+
+            self.expect(TokenType::Fn, "`fn` or `}`")?;
+
+            methods.push(TraitMethod { is_abstract, decl: self.function(false, is_abstract)? });
+        }
+
+        Ok(TraitDecl { is_pub, name, generics, methods })
+    }
+
+    // `impl Trait<Args> for Type { ... }`
+    fn impl_decl(&mut self) -> Parsed<ImplDecl> {
+        let trait_name = self.identifier("the name of a trait")?;
+        let trait_args = self.type_args()?;
+        let mut methods = vec![];
+
+        self.expect(TokenType::For, "`for`")?;
+
+        let for_type = self.type_expr()?;
+
+        self.expect(TokenType::LeftBrace, "`{`")?;
+
+        while !self.eat(TokenType::RightBrace) {
+            self.expect(TokenType::Fn, "`fn` or `}`")?;
+            methods.push(self.function(false, false)?);
+        }
+
+        Ok(ImplDecl { trait_name, trait_args, for_type, methods })
+    }
+
+    // what follows `fn`; only `extern` functions and abstract methods may end in `;` instead of a body
+    fn function(&mut self, is_pub: bool, may_omit_body: bool) -> Parsed<FnDecl> {
+        let name = self.identifier("the name of the function")?;
+        let generics = self.generic_params()?;
+
+        self.expect(TokenType::LeftParen, "`(`")?;
+
+        let params = self.params()?;
+        let ret = self.return_type()?;
+
+        let body = match may_omit_body && self.eat(TokenType::Semicolon) {
+            true => None,
+            false => Some(self.block()?),
+        };
+
+        Ok(FnDecl { is_pub, name, generics, params, ret, body })
+    }
+
+    // `name: Type, ...)`, after the opening parenthesis
+    fn params(&mut self) -> Parsed<Vec<Param>> {
+        self.separated(TokenType::RightParen, |this| {
+            let name = this.identifier("a parameter name")?;
+
+            this.expect(TokenType::Colon, "`:`")?;
+
+            Ok(Param { name, ty: this.type_expr()? })
+        })
+    }
+
+    fn return_type(&mut self) -> Parsed<Option<TypeExpr>> {
+        match self.eat(TokenType::ThinArrow) {
+            true => self.type_expr().map(Some),
+            false => Ok(None),
+        }
+    }
+
+    // `<T: Bound + Other<U>, U>`, or nothing
+    fn generic_params(&mut self) -> Parsed<Vec<GenericParam>> {
+        if !self.eat(TokenType::Smaller) {
+            return Ok(vec![]);
+        }
+
+        self.separated(TokenType::Greater, |this| {
+            let name = this.identifier("a type parameter")?;
+            let mut bounds = vec![];
+
+            if this.eat(TokenType::Colon) {
+                loop {
+                    bounds.push(TraitRef { name: this.identifier("a trait")?, args: this.type_args()? });
+
+                    if !this.eat(TokenType::Plus) {
+                        break;
+                    }
+                }
+            }
+
+            Ok(GenericParam { name, bounds })
+        })
+    }
+
+    // the items of a comma separated list up to and including `close`, which may follow a trailing comma
+    fn separated<T>(&mut self, close: TokenType, mut item: impl FnMut(&mut Self) -> Parsed<T>) -> Parsed<Vec<T>> {
+        let mut items = vec![];
+
+        while !self.eat(close) {
+            items.push(item(self)?);
+
+            if !self.eat(TokenType::Comma) {
+                self.expect(close, &format!("`,` or `{}`", close))?;
+                break;
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn peek(&self) -> Option<TokenType> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<TokenType> {
+        self.tokens.get(self.position + offset).map(|token| token.node.get_type())
+    }
+
+    fn eat(&mut self, expected: TokenType) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+
+            return true;
+        }
+
+        false
+    }
+
+    fn next(&mut self, expected: &str) -> Parsed<Spanned<Token>> {
+        let token = self.tokens.get(self.position).copied().ok_or_else(|| self.ended(expected))?;
+
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: TokenType, description: &str) -> Parsed<Span> {
+        let token = self.next(description)?;
+
+        match token.node.get_type() == expected {
+            true => Ok(token.span),
+            false => Err(unexpected(&token, description)),
+        }
+    }
+
+    fn identifier(&mut self, expected: &str) -> Parsed<Spanned<&'static str>> {
+        let token = self.next(expected)?;
+
+        match token.node.get_type() {
+            TokenType::Identifier(name) => Ok(Spanned::from_span(token.span, name)),
+            _ => Err(unexpected(&token, expected)),
+        }
+    }
+
+    fn ended(&self, expected: &str) -> Error {
+        Error::from_span(self.end(), ErrorType::ParseError, &format!("expected {} but the file ended", expected))
+    }
+
+    // from the start of `start` to the end of the last token consumed
+    fn span_from(&self, start: Span) -> Span {
+        Span::new(start.start, self.tokens[self.position - 1].span.end)
+    }
+
+    fn start(&self) -> Span {
+        self.tokens.get(self.position).map_or_else(|| self.end(), |token| token.span)
+    }
+
+    fn end(&self) -> Span {
+        self.tokens.last().map_or(Span::new(0, 0), |token| token.span)
+    }
+}
+
+fn unexpected(token: &Spanned<Token>, expected: &str) -> Error {
+    Error::from_span(token.span, ErrorType::ParseError, &format!("expected {}, found `{}`", expected, token.node.get_type()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ ast::{ fixtures::{ * } } };
+
+    pub(super) fn parse(code: &str) -> Parsed<Vec<Item>> {
+        parse_module(Box::leak(Box::new(Source::new("test", code))))
+    }
+
+    #[test]
+    fn items_are_parsed_with_their_spans() {
+        let items = parse("import Vec from \"vec\";\npub struct Point<T: Show + Eq> { pub x: T, y: i32, }\nextern fn puts(s: *char) -> i32;").unwrap();
+
+        let Item::Struct(point) = &items[1] else { panic!("expected a struct, found {:?}", items[1]) };
+        let Item::ExternFn(puts) = &items[2] else { panic!("expected an extern function, found {:?}", items[2]) };
+
+        assert!(matches!(&items[0], Item::Import(import) if import.names[0].node == "Vec" && import.path.node == "vec"));
+        assert_eq!((point.is_pub, point.name), (true, Spanned::new(34, 38, "Point")));
+        assert_eq!(point.generics[0].bounds.iter().map(|bound| bound.name.node).collect::<Vec<_>>(), vec![ "Show", "Eq" ]);
+        assert_eq!(point.fields.iter().map(|field| (field.is_pub, field.name)).collect::<Vec<_>>(), vec![ (true, "x"), (false, "y") ]);
+        assert_eq!(point.fields[1].ty.node, i32_type().node);
+        assert_eq!((puts.name.node, puts.body.is_none()), ("puts", true));
+    }
+
+    #[test]
+    fn traits_and_impls() {
+        let items = parse("trait Shape { abstract fn area(self: *Self) -> i32; fn double(self: *Self) -> i32 { return self.area() * 2; } }\nimpl Shape for Square { fn area(self: *Self) -> i32 { return 4; } }").unwrap();
+
+        let Item::Trait(shape) = &items[0] else { panic!("expected a trait, found {:?}", items[0]) };
+        let Item::Impl(imp) = &items[1] else { panic!("expected an impl, found {:?}", items[1]) };
+
+        assert_eq!(shape.methods.iter().map(|method| (method.decl.name.node, method.is_abstract, method.decl.body.is_some())).collect::<Vec<_>>(), vec![ ("area", true, false), ("double", false, true) ]);
+        assert_eq!(shape.methods[0].decl.params[0].ty.node, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(38, 41, TypeExprKind::SelfType))));
+        assert_eq!((imp.trait_name.node, &imp.for_type.node), ("Shape", &named("Square").node));
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        let error = parse("fn main() -> i32 { return 1 }").unwrap_err();
+
+        assert_eq!((error.get_error_type(), error.get_message(), error.get_location()), (ErrorType::ParseError, "expected `;`, found `}`".to_owned(), Span::new(28, 28)));
+        assert_eq!(parse("fn main() {").unwrap_err().get_message(), "expected a statement or `}` but the file ended");
+        assert_eq!(parse("fn f();").unwrap_err().get_message(), "expected `{`, found `;`");
+        assert_eq!(parse("fn f() {} import a from \"a\";").unwrap_err().get_message(), "imports must come before every other item");
+        assert_eq!(parse("fn f() { let c = 'ab'; }").unwrap_err().get_error_type(), ErrorType::LexError);
+    }
+}
@@ -0,0 +1,129 @@
+use super::{ * };
+use crate::{ types::{ CallingConvention, SimpleType } };
+
+impl Parser {
+    pub(super) fn type_expr(&mut self) -> Parsed<TypeExpr> {
+        let token = self.next("a type")?;
+        let start = token.span;
+
+        let kind = match token.node.get_type() {
+            TokenType::TypeIdentifier(simple) => TypeExprKind::Builtin(simple),
+            // `...` as the last parameter of a variadic function
+            TokenType::TripleDot => TypeExprKind::Builtin(SimpleType::Varargs),
+            TokenType::Identifier("Self") => TypeExprKind::SelfType,
+            TokenType::Identifier(name) => TypeExprKind::Named(name, self.type_args()?),
+            TokenType::Dyn => TypeExprKind::Dyn(self.identifier("a trait")?.node),
+
+            // `**T` is a single pointer type of two levels
+            TokenType::Star => {
+                let mut references = 1;
+
+                while self.eat(TokenType::Star) {
+                    references += 1;
+                }
+
+                let is_mutable = self.eat(TokenType::Mut);
+
+                TypeExprKind::Pointer(references, is_mutable, Box::new(self.type_expr()?))
+            },
+
+            TokenType::Question => TypeExprKind::Nullable(Box::new(self.type_expr()?)),
+
+            // `[length]T`, or `[?]T` for an array whose length is only known at runtime
+            TokenType::LeftBracket => {
+                let length = match self.peek() == Some(TokenType::Question) && self.peek_at(1) == Some(TokenType::RightBracket) {
+                    true => {
+                        self.eat(TokenType::Question);
+
+                        None
+                    },
+
+                    false => Some(Box::new(self.expr()?)),
+                };
+
+                self.expect(TokenType::RightBracket, "`]`")?;
+
+                TypeExprKind::Array(length, Box::new(self.type_expr()?))
+            },
+
+            TokenType::Fn => self.function_type(CallingConvention::Synthium)?,
+
+            TokenType::Extern => {
+                self.expect(TokenType::Fn, "`fn`")?;
+                self.function_type(CallingConvention::C)?
+            },
+
+            _ => return Err(unexpected(&token, "a type")),
+        };
+
+        Ok(Spanned::from_span(self.span_from(start), kind))
+    }
+
+    // `<Args...>` after the name of a generic type or trait, or nothing
+    pub(super) fn type_args(&mut self) -> Parsed<Vec<TypeExpr>> {
+        match self.eat(TokenType::Smaller) {
+            true => self.separated(TokenType::Greater, Self::type_expr),
+            false => Ok(vec![]),
+        }
+    }
+
+    // `(params...) -> ret`, after `fn`
+    fn function_type(&mut self, convention: CallingConvention) -> Parsed<TypeExprKind> {
+        self.expect(TokenType::LeftParen, "`(`")?;
+
+        let params = self.separated(TokenType::RightParen, Self::type_expr)?;
+        let ret = self.return_type()?;
+
+        Ok(TypeExprKind::Function(params, ret.map(Box::new), convention))
+    }
+
+    // whether the tokens from `offset` on spell a type rather than an expression, as after `sizeof(`;
+    // a name counts as a type when it is capitalised, like the names of structs, enums and aliases
+    pub(super) fn starts_type(&self, offset: usize) -> bool {
+        match self.peek_at(offset) {
+            Some(TokenType::TypeIdentifier(_) | TokenType::Question | TokenType::LeftBracket | TokenType::Dyn | TokenType::Fn | TokenType::Extern | TokenType::TripleDot) => true,
+            Some(TokenType::Star) => self.peek_at(offset + 1) == Some(TokenType::Mut) || self.starts_type(offset + 1),
+            Some(TokenType::Identifier(name)) => is_capitalised(name),
+            _ => false,
+        }
+    }
+}
+
+pub(super) fn is_capitalised(name: &str) -> bool {
+    name.starts_with(|first: char| first.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ ast::{ fixtures::{ * } }, types::{ SignedIntegerTypes } };
+
+    fn parse_type(code: &str) -> Parsed<TypeExprKind> {
+        Parser::new(Box::leak(Box::new(Source::new("test", code))))?.type_expr().map(|ty| ty.node)
+    }
+
+    #[test]
+    fn pointers_arrays_and_function_types() {
+        let pointer = |ty: TypeExprKind| match ty {
+            TypeExprKind::Pointer(references, is_mutable, base) => (references, is_mutable, base.node),
+            other => panic!("expected a pointer type, found {:?}", other),
+        };
+
+        assert_eq!(pointer(parse_type("**mut Pair<i32, *char>").unwrap()), (2, true, TypeExprKind::Named("Pair", vec![ Spanned::new(11, 13, i32_type().node), Spanned::new(16, 20, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(17, 20, TypeExprKind::Builtin(SimpleType::Char))))) ])));
+        assert!(matches!(parse_type("?*void").unwrap(), TypeExprKind::Nullable(inner) if matches!(inner.node, TypeExprKind::Pointer(1, false, _))));
+        assert!(matches!(parse_type("[?]u8").unwrap(), TypeExprKind::Array(None, _)));
+        assert!(matches!(parse_type("[4 * 2]u8").unwrap(), TypeExprKind::Array(Some(length), _) if matches!(length.node, ExprKind::Binary(_, BinaryOp::Mul, _))));
+
+        let TypeExprKind::Function(params, ret, convention) = parse_type("extern fn(*char, ...) -> i32").unwrap() else { panic!("expected a function type") };
+
+        assert_eq!((params.len(), params[1].node.clone(), convention), (2, TypeExprKind::Builtin(SimpleType::Varargs), CallingConvention::C));
+        assert_eq!(ret.map(|ret| ret.node), Some(TypeExprKind::Builtin(SimpleType::signed(SignedIntegerTypes::Int32))));
+    }
+
+    #[test]
+    fn malformed_types_are_rejected() {
+        assert_eq!(parse_type("*").unwrap_err().get_message(), "expected a type but the file ended");
+        assert_eq!(parse_type("Pair<i32 bool>").unwrap_err().get_message(), "expected `,` or `>`, found `bool`");
+        assert_eq!(parse_type("1").unwrap_err().get_message(), "expected a type, found `1`");
+    }
+}
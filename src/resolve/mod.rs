@@ -0,0 +1,660 @@
+use std::{ fmt::{ Display, Formatter, Result }, collections::{ HashMap } };
+
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span, Spanned }, modules::{ ModuleGraph, ImportedName }, types::{ UserDefinedType } };
+
+pub mod suggest;
+
+use suggest::{ closest };
+
+type Resolved<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Namespace {
+    Type,
+    Value,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeclKind {
+    Struct,
+    Enum,
+    Trait,
+    TypeAlias,
+    TypeParam,
+    Function,
+    Static,
+    Param,
+    Local,
+}
+
+impl DeclKind {
+    pub fn namespace(&self) -> Namespace {
+        match self {
+            Self::Struct | Self::Enum | Self::Trait | Self::TypeAlias | Self::TypeParam => Namespace::Type,
+            Self::Function | Self::Static | Self::Param | Self::Local => Namespace::Value,
+        }
+    }
+}
+
+impl Display for DeclKind {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Struct => write!(f, "struct"),
+            Self::Enum => write!(f, "enum"),
+            Self::Trait => write!(f, "trait"),
+            Self::TypeAlias => write!(f, "type alias"),
+            Self::TypeParam => write!(f, "type parameter"),
+            Self::Function => write!(f, "function"),
+            Self::Static => write!(f, "static"),
+            Self::Param => write!(f, "parameter"),
+            Self::Local => write!(f, "variable"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: &'static str,
+    pub kind: DeclKind,
+    // the module the declaration is written in, which differs from the current one for imports
+    pub module: &'static str,
+    pub span: Span,
+    // whether other modules may import it; always false for anything not declared at the top level
+    pub is_pub: bool,
+}
+
+impl Declaration {
+    pub fn user_type(&self) -> Option<UserDefinedType> {
+        match self.kind {
+            DeclKind::Struct | DeclKind::Enum | DeclKind::Trait | DeclKind::TypeAlias => Some(UserDefinedType::new(self.module, self.name)),
+            _ => None,
+        }
+    }
+}
+
+// one use of a name and the declaration it refers to
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Resolution {
+    pub span: Span,
+    pub declaration: Declaration,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    types: HashMap<&'static str, Declaration>,
+    values: HashMap<&'static str, Declaration>,
+}
+
+impl Scope {
+    pub fn get(&self, namespace: Namespace, name: &str) -> Option<Declaration> {
+        self.names(namespace).get(name).copied()
+    }
+
+    pub fn declarations(&self, namespace: Namespace) -> impl Iterator<Item = &Declaration> {
+        self.names(namespace).values()
+    }
+
+    fn names(&self, namespace: Namespace) -> &HashMap<&'static str, Declaration> {
+        match namespace {
+            Namespace::Type => &self.types,
+            Namespace::Value => &self.values,
+        }
+    }
+
+    // a later declaration in the same scope shadows an earlier one
+    fn insert(&mut self, declaration: Declaration) {
+        match declaration.kind.namespace() {
+            Namespace::Type => self.types.insert(declaration.name, declaration),
+            Namespace::Value => self.values.insert(declaration.name, declaration),
+        };
+    }
+}
+
+// resolves the names of one module: its top-level declarations and imports form the outermost scope,
+// functions, blocks, match arms and closures nest inside it
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    module: &'static str,
+    // what the module itself declares, which is what other modules can import from it
+    declared: Scope,
+    imported: Scope,
+    scopes: Vec<Scope>,
+    resolutions: Vec<Resolution>,
+}
+
+impl Resolver {
+    pub fn new(module: &'static str) -> Self {
+        Self {
+            module,
+            declared: Scope::default(),
+            imported: Scope::default(),
+            scopes: vec![],
+            resolutions: vec![],
+        }
+    }
+
+    pub fn declared(&self) -> &Scope {
+        &self.declared
+    }
+
+    // what the module's imports brought into scope, declared in the modules they come from
+    pub fn imported(&self) -> &Scope {
+        &self.imported
+    }
+
+    pub fn resolutions(&self) -> &[Resolution] {
+        &self.resolutions
+    }
+
+    pub fn resolution_at(&self, span: Span) -> Option<Declaration> {
+        self.resolutions.iter().find(|resolution| resolution.span == span).map(|resolution| resolution.declaration)
+    }
+
+    // collects the top-level declarations first, so that items may refer to each other in any order
+    pub fn declare_items(&mut self, items: &[Item]) -> Resolved<()> {
+        for item in items {
+            let (name, kind, is_pub) = match item {
+                Item::Import(_) | Item::Impl(_) => continue,
+                Item::Struct(decl) => (decl.name, DeclKind::Struct, decl.is_pub),
+                Item::Enum(decl) => (decl.name, DeclKind::Enum, decl.is_pub),
+                Item::Trait(decl) => (decl.name, DeclKind::Trait, decl.is_pub),
+                Item::TypeAlias(decl) => (decl.name, DeclKind::TypeAlias, decl.is_pub),
+                Item::Fn(decl) | Item::ExternFn(decl) => (decl.name, DeclKind::Function, decl.is_pub),
+                Item::Static(decl) => (decl.name, DeclKind::Static, decl.is_pub),
+            };
+
+            if let Some(previous) = self.declared.get(kind.namespace(), name.node) {
+                return Err(Error::from_span(name.span, ErrorType::NameError, &format!("the name `{}` is defined more than once; it is already a {}", name.node, previous.kind)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            self.declared.insert(Declaration {
+                is_pub,
+                ..self.declaration(name, kind)
+            });
+        }
+
+        Ok(())
+    }
+
+    // brings imported names into scope, looking each one up among the declarations of the module it comes from
+    pub fn import(&mut self, names: &HashMap<&'static str, ImportedName>, modules: &HashMap<&'static str, Scope>) -> Resolved<()> {
+        let mut names: Vec<&ImportedName> = names.values().collect();
+
+        names.sort_by_key(|imported| imported.span.start);
+
+        for imported in names {
+            let empty = Scope::default();
+            let module = modules.get(imported.module).unwrap_or(&empty);
+            let found: Vec<Declaration> = [ Namespace::Type, Namespace::Value ].iter().filter_map(|namespace| module.get(*namespace, imported.name)).collect();
+
+            if found.is_empty() {
+                let candidates = module.types.keys().chain(module.values.keys()).copied();
+
+                return Err(Error::from_span(imported.span, ErrorType::ImportError, &format!("module `{}` has no item named `{}`{}", imported.module, imported.name, Self::suggestion(imported.name, candidates))).with_code(codes::UNKNOWN_IMPORTED_ITEM));
+            }
+
+            // a struct and a function may share a name, so only the private ones are off limits
+            if !found.iter().any(|declaration| declaration.is_pub) {
+                return Err(Error::from_span(imported.span, ErrorType::ImportError, &format!("`{}` is private to module `{}`", imported.name, imported.module)).with_code(codes::PRIVATE_IMPORT));
+            }
+
+            for declaration in found.into_iter().filter(|declaration| declaration.is_pub) {
+                if let Some(local) = self.declared.get(declaration.kind.namespace(), imported.name) {
+                    return Err(Error::from_span(local.span, ErrorType::ImportError, &format!("`{}` is imported from `{}` and also declared in this module", imported.name, imported.module)).with_code(codes::CONFLICTING_IMPORT));
+                }
+
+                self.imported.insert(declaration);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve_items(&mut self, items: &[Item]) -> Resolved<()> {
+        for item in items {
+            match item {
+                Item::Import(_) => {},
+
+                Item::Struct(decl) => self.with_generics(&decl.generics, |this| {
+                    decl.fields.iter().try_for_each(|field| this.resolve_type(&field.ty))
+                })?,
+
+                Item::Enum(decl) => self.with_generics(&decl.generics, |this| {
+                    decl.variants.iter().flat_map(|variant| &variant.payload).try_for_each(|ty| this.resolve_type(ty))
+                })?,
+
+                Item::Trait(decl) => self.with_generics(&decl.generics, |this| {
+                    decl.methods.iter().try_for_each(|method| this.resolve_function(&method.decl))
+                })?,
+
+                Item::Impl(decl) => {
+                    self.resolve_trait(decl.trait_name)?;
+                    decl.trait_args.iter().try_for_each(|ty| self.resolve_type(ty))?;
+                    self.resolve_type(&decl.for_type)?;
+                    decl.methods.iter().try_for_each(|method| self.resolve_function(method))?;
+                },
+
+                Item::Fn(decl) | Item::ExternFn(decl) => self.resolve_function(decl)?,
+                Item::TypeAlias(decl) => self.resolve_type(&decl.ty)?,
+
+                Item::Static(decl) => {
+                    self.resolve_type(&decl.ty)?;
+                    self.resolve_expr(&decl.value)?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn declaration(&self, name: Spanned<&'static str>, kind: DeclKind) -> Declaration {
+        Declaration {
+            name: name.node,
+            kind,
+            module: self.module,
+            span: name.span,
+            is_pub: false,
+        }
+    }
+
+    fn declare(&mut self, name: Spanned<&'static str>, kind: DeclKind) {
+        let declaration = self.declaration(name, kind);
+
+        self.scopes.last_mut().expect("resolver has no scope").insert(declaration);
+    }
+
+    fn with_scope<T>(&mut self, f: impl FnOnce(&mut Self) -> Resolved<T>) -> Resolved<T> {
+        self.scopes.push(Scope::default());
+
+        let result = f(self);
+
+        self.scopes.pop();
+
+        result
+    }
+
+    fn with_generics(&mut self, generics: &[GenericParam], f: impl FnOnce(&mut Self) -> Resolved<()>) -> Resolved<()> {
+        self.with_scope(|this| {
+            for param in generics {
+                this.declare(param.name, DeclKind::TypeParam);
+            }
+
+            for param in generics {
+                param.bounds.iter().try_for_each(|bound| this.resolve_trait_ref(bound))?;
+            }
+
+            f(this)
+        })
+    }
+
+    // innermost scope first, then the module's own declarations, then its imports
+    fn lookup(&self, namespace: Namespace, name: &str) -> Option<Declaration> {
+        self.scopes.iter().rev()
+            .chain([ &self.declared, &self.imported ])
+            .find_map(|scope| scope.get(namespace, name))
+    }
+
+    fn resolve_name(&mut self, namespace: Namespace, name: &'static str, span: Span) -> Resolved<Declaration> {
+        let Some(declaration) = self.lookup(namespace, name) else {
+            let candidates = self.scopes.iter().chain([ &self.declared, &self.imported ]).flat_map(|scope| scope.names(namespace).keys().copied());
+            let what = match namespace {
+                Namespace::Type => "type",
+                Namespace::Value => "value",
+            };
+
+            return Err(Error::from_span(span, ErrorType::NameError, &format!("cannot find {} `{}` in this scope{}", what, name, Self::suggestion(name, candidates))).with_code(codes::UNDEFINED_NAME));
+        };
+
+        self.resolutions.push(Resolution {
+            span,
+            declaration,
+        });
+
+        Ok(declaration)
+    }
+
+    fn suggestion(name: &str, candidates: impl Iterator<Item = &'static str>) -> String {
+        let candidates: Vec<&str> = candidates.collect();
+
+        closest(name, candidates).map_or_else(String::new, |candidate| format!("; did you mean `{}`?", candidate))
+    }
+
+    fn resolve_trait(&mut self, name: Spanned<&'static str>) -> Resolved<()> {
+        let declaration = self.resolve_name(Namespace::Type, name.node, name.span)?;
+
+        if declaration.kind != DeclKind::Trait {
+            return Err(Error::from_span(name.span, ErrorType::NameError, &format!("`{}` is a {}, not a trait", name.node, declaration.kind)).with_code(codes::WRONG_KIND));
+        }
+
+        Ok(())
+    }
+
+    fn resolve_trait_ref(&mut self, trait_ref: &TraitRef) -> Resolved<()> {
+        self.resolve_trait(trait_ref.name)?;
+
+        trait_ref.args.iter().try_for_each(|ty| self.resolve_type(ty))
+    }
+
+    fn resolve_type(&mut self, ty: &TypeExpr) -> Resolved<()> {
+        match &ty.node {
+            TypeExprKind::Builtin(_) | TypeExprKind::SelfType => Ok(()),
+
+            TypeExprKind::Named(name, args) => {
+                self.resolve_name(Namespace::Type, name, ty.span)?;
+
+                args.iter().try_for_each(|arg| self.resolve_type(arg))
+            },
+
+            TypeExprKind::Dyn(name) => self.resolve_trait(Spanned::from_span(ty.span, *name)),
+            TypeExprKind::Pointer(_, _, pointee) | TypeExprKind::Nullable(pointee) => self.resolve_type(pointee),
+
+            TypeExprKind::Array(length, element) => {
+                if let Some(length) = length {
+                    self.resolve_expr(length)?;
+                }
+
+                self.resolve_type(element)
+            },
+
+            TypeExprKind::Function(params, ret, _) => {
+                params.iter().try_for_each(|param| self.resolve_type(param))?;
+
+                ret.as_ref().map_or(Ok(()), |ret| self.resolve_type(ret))
+            },
+        }
+    }
+
+    fn resolve_function(&mut self, decl: &FnDecl) -> Resolved<()> {
+        self.with_generics(&decl.generics, |this| {
+            this.resolve_params(&decl.params)?;
+
+            if let Some(ret) = &decl.ret {
+                this.resolve_type(ret)?;
+            }
+
+            match &decl.body {
+                Some(body) => this.resolve_block(body),
+                None => Ok(()),
+            }
+        })
+    }
+
+    // parameters are declared in the scope the caller opened for them
+    fn resolve_params(&mut self, params: &[Param]) -> Resolved<()> {
+        for param in params {
+            self.resolve_type(&param.ty)?;
+            self.declare(param.name, DeclKind::Param);
+        }
+
+        Ok(())
+    }
+
+    fn resolve_block(&mut self, block: &Block) -> Resolved<()> {
+        self.with_scope(|this| block.iter().try_for_each(|stmt| this.resolve_stmt(stmt)))
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) -> Resolved<()> {
+        match &stmt.node {
+            // the initialiser is resolved before the new binding exists, so `let x = x + 1;` uses the outer `x`
+            StmtKind::Let(name, _, ty, value) => {
+                if let Some(ty) = ty {
+                    self.resolve_type(ty)?;
+                }
+
+                self.resolve_expr(value)?;
+                self.declare(Spanned::from_span(stmt.span, *name), DeclKind::Local);
+
+                Ok(())
+            },
+
+            StmtKind::Assign(place, value) => {
+                self.resolve_expr(place)?;
+                self.resolve_expr(value)
+            },
+
+            StmtKind::Expr(expr) | StmtKind::Delete(expr) => self.resolve_expr(expr),
+            StmtKind::Return(value) => value.as_ref().map_or(Ok(()), |value| self.resolve_expr(value)),
+
+            StmtKind::If(condition, then, otherwise) => {
+                self.resolve_expr(condition)?;
+                self.resolve_block(then)?;
+
+                otherwise.as_ref().map_or(Ok(()), |otherwise| self.resolve_block(otherwise))
+            },
+
+            StmtKind::While(condition, body) => {
+                self.resolve_expr(condition)?;
+                self.resolve_block(body)
+            },
+        }
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) -> Resolved<()> {
+        match &expr.node {
+            ExprKind::Literal(_) => Ok(()),
+
+            ExprKind::Identifier(name) => self.resolve_name(Namespace::Value, name, expr.span).map(|_| ()),
+
+            ExprKind::Unary(_, operand) | ExprKind::Field(operand, _) | ExprKind::Unwrap(operand) => self.resolve_expr(operand),
+
+            ExprKind::Binary(lhs, _, rhs) => {
+                self.resolve_expr(lhs)?;
+                self.resolve_expr(rhs)
+            },
+
+            ExprKind::Cast(value, ty) => {
+                self.resolve_expr(value)?;
+                self.resolve_type(ty)
+            },
+
+            ExprKind::Sizeof(SizeofOperand::Type(ty)) | ExprKind::New(ty) => self.resolve_type(ty),
+            ExprKind::Sizeof(SizeofOperand::Expr(operand)) => self.resolve_expr(operand),
+
+            ExprKind::Variant(enum_name, _, args) => {
+                let declaration = self.resolve_name(Namespace::Type, enum_name, expr.span)?;
+
+                if !matches!(declaration.kind, DeclKind::Enum | DeclKind::TypeAlias) {
+                    return Err(Error::from_span(expr.span, ErrorType::NameError, &format!("`{}` is a {}, not an enum", enum_name, declaration.kind)).with_code(codes::WRONG_KIND));
+                }
+
+                args.iter().try_for_each(|arg| self.resolve_expr(arg))
+            },
+
+            ExprKind::Match(scrutinee, arms) => {
+                self.resolve_expr(scrutinee)?;
+
+                arms.iter().try_for_each(|arm| self.with_scope(|this| {
+                    this.declare_pattern(&arm.pattern);
+                    this.resolve_expr(&arm.body)
+                }))
+            },
+
+            ExprKind::Call(callee, args) => {
+                self.resolve_expr(callee)?;
+
+                args.iter().try_for_each(|arg| self.resolve_expr(arg))
+            },
+
+            // methods are looked up on the receiver's type during type checking
+            ExprKind::MethodCall(receiver, _, args) => {
+                self.resolve_expr(receiver)?;
+
+                args.iter().try_for_each(|arg| self.resolve_expr(arg))
+            },
+
+            ExprKind::Closure(closure) => self.with_scope(|this| {
+                for name in &closure.by_ref {
+                    this.resolve_name(Namespace::Value, name.node, name.span)?;
+                }
+
+                this.resolve_params(&closure.params)?;
+
+                if let Some(ret) = &closure.ret {
+                    this.resolve_type(ret)?;
+                }
+
+                this.resolve_block(&closure.body)
+            }),
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.node {
+            PatternKind::Binding(name) => self.declare(Spanned::from_span(pattern.span, *name), DeclKind::Local),
+            PatternKind::Variant(_, fields) => fields.iter().for_each(|field| self.declare_pattern(field)),
+            PatternKind::Wildcard | PatternKind::Literal(_) => {},
+        }
+    }
+}
+
+// the resolved names of every module in a graph
+#[derive(Debug, Clone)]
+pub struct ResolvedProgram {
+    modules: HashMap<&'static str, Resolver>,
+}
+
+impl ResolvedProgram {
+    // modules are resolved after the modules they import, so their declarations are known by then
+    pub fn resolve(graph: &ModuleGraph, items: &HashMap<&'static str, Vec<Item>>) -> Resolved<Self> {
+        let mut modules: HashMap<&'static str, Resolver> = HashMap::new();
+        let mut scopes: HashMap<&'static str, Scope> = HashMap::new();
+
+        for module in graph.modules() {
+            let items = items.get(module.name).map_or(&[][..], |items| &items[..]);
+            let mut resolver = Resolver::new(module.name);
+
+            resolver.declare_items(items)?;
+            resolver.import(&graph.imported_names(module.name)?, &scopes)?;
+            resolver.resolve_items(items)?;
+
+            scopes.insert(module.name, resolver.declared().clone());
+            modules.insert(module.name, resolver);
+        }
+
+        Ok(Self {
+            modules,
+        })
+    }
+
+    pub fn module(&self, name: &str) -> Option<&Resolver> {
+        self.modules.get(name)
+    }
+
+    // the declaration behind a user-defined type; instances like `Pair<i32, bool>` resolve to their template
+    pub fn declaration_of(&self, ty: UserDefinedType) -> Option<Declaration> {
+        let name = ty.name.split('<').next().unwrap_or(ty.name);
+
+        self.modules.get(ty.file)?.declared().get(Namespace::Type, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ ast::{ fixtures::{ * } } };
+
+    fn param_at(start: usize, name: &'static str) -> Param {
+        Param { name: Spanned::new(start, start, name), ty: i32_type() }
+    }
+
+    fn let_stmt(start: usize, name: &'static str, value: Expr) -> Stmt {
+        Spanned::new(start, start, StmtKind::Let(name, false, None, value))
+    }
+
+    #[test]
+    fn lets_shadow_earlier_bindings() {
+        let mut resolver = Resolver::new("main");
+        // fn f(x: i32) { let x = x; let y = x; if x { let x = y; x; } x; }
+        let items = vec![ function("f", vec![ param_at(1, "x") ], None, vec![
+            let_stmt(10, "x", at(11, ident("x"))),
+            let_stmt(20, "y", at(21, ident("x"))),
+            Spanned::new(30, 30, StmtKind::If(at(31, ident("x")), vec![
+                let_stmt(40, "x", at(41, ident("y"))),
+                Spanned::new(50, 50, StmtKind::Expr(at(51, ident("x")))),
+            ], None)),
+            Spanned::new(60, 60, StmtKind::Expr(at(61, ident("x")))),
+        ]) ];
+
+        resolver.declare_items(&items).unwrap();
+        resolver.resolve_items(&items).unwrap();
+
+        let declared_at = |start| resolver.resolution_at(Span::new(start, start)).map(|declaration| (declaration.kind, declaration.span.start));
+
+        assert_eq!(declared_at(11), Some((DeclKind::Param, 1)));
+        assert_eq!(declared_at(21), Some((DeclKind::Local, 10)));
+        assert_eq!(declared_at(41), Some((DeclKind::Local, 20)));
+        assert_eq!(declared_at(51), Some((DeclKind::Local, 40)));
+        assert_eq!(declared_at(61), Some((DeclKind::Local, 10)));
+    }
+
+    #[test]
+    fn undefined_names_suggest_close_matches() {
+        let mut resolver = Resolver::new("main");
+        let items = vec![ function("f", vec![ param_at(1, "count") ], None, vec![ Spanned::new(2, 2, StmtKind::Expr(at(3, ident("conut")))) ]) ];
+
+        resolver.declare_items(&items).unwrap();
+
+        let error = resolver.resolve_items(&items).unwrap_err();
+
+        assert_eq!(error.get_message(), "cannot find value `conut` in this scope; did you mean `count`?");
+        assert_eq!((error.get_error_type(), error.get_code()), (ErrorType::NameError, codes::UNDEFINED_NAME));
+    }
+
+    #[test]
+    fn imported_types_resolve_to_their_module() {
+        let point = Item::Struct(StructDecl {
+            is_pub: true,
+            name: Spanned::new(5, 9, "Point"),
+            generics: vec![],
+            fields: vec![],
+        });
+        let mut geometry = Resolver::new("geometry");
+
+        geometry.declare_items(&[ point ]).unwrap();
+
+        let scopes = HashMap::from([ ("geometry", geometry.declared().clone()) ]);
+        let import = |name| HashMap::from([ (name, ImportedName { name, module: "geometry", span: Span::new(0, 0) }) ]);
+        let uses_point = vec![ Item::TypeAlias(TypeAliasDecl { is_pub: false, name: Spanned::new(0, 0, "P"), ty: Spanned::new(30, 34, TypeExprKind::Named("Point", vec![])) }) ];
+        let mut main = Resolver::new("main");
+
+        main.declare_items(&uses_point).unwrap();
+        main.import(&import("Point"), &scopes).unwrap();
+        main.resolve_items(&uses_point).unwrap();
+
+        let declaration = main.resolution_at(Span::new(30, 34)).unwrap();
+
+        assert_eq!(declaration.user_type(), Some(UserDefinedType::new("geometry", "Point")));
+        assert_eq!(declaration.span, Span::new(5, 9));
+
+        let error = Resolver::new("main").import(&import("Piont"), &scopes).unwrap_err();
+
+        assert_eq!(error.get_message(), "module `geometry` has no item named `Piont`; did you mean `Point`?");
+    }
+
+    #[test]
+    fn private_items_cannot_be_imported() {
+        let helper = |is_pub| Item::Fn(FnDecl {
+            is_pub,
+            name: Spanned::new(0, 0, "helper"),
+            generics: vec![],
+            params: vec![],
+            ret: None,
+            body: Some(vec![]),
+        });
+        let import = HashMap::from([ ("helper", ImportedName { name: "helper", module: "util", span: Span::new(0, 0) }) ]);
+
+        for is_pub in [ false, true ] {
+            let mut util = Resolver::new("util");
+
+            util.declare_items(&[ helper(is_pub) ]).unwrap();
+
+            let scopes = HashMap::from([ ("util", util.declared().clone()) ]);
+            let imported = Resolver::new("main").import(&import, &scopes);
+
+            match is_pub {
+                true => assert!(imported.is_ok()),
+                false => assert_eq!(imported.unwrap_err().get_message(), "`helper` is private to module `util`"),
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// the number of single character insertions, deletions, substitutions and swaps of
+// neighbouring characters that turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<Vec<usize>> = (0 ..= a.len()).map(|i| vec![ i; b.len() + 1 ]).collect();
+
+    distances[0] = (0 ..= b.len()).collect();
+
+    for i in 1 ..= a.len() {
+        for j in 1 ..= b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(distances[i - 1][j] + 1).min(distances[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+// the candidate closest to `name`, if it is close enough to be a plausible typo;
+// ties go to the alphabetically first candidate so that diagnostics are stable
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    #[test]
+    fn distances_and_suggestions() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("count", "count"), 0);
+        assert_eq!(edit_distance("conut", "count"), 1);
+
+        assert_eq!(closest("conut", [ "count", "counter", "amount" ]), Some("count"));
+        assert_eq!(closest("x", [ "y", "z" ]), Some("y"));
+        assert_eq!(closest("length", [ "width", "height" ]), None);
+    }
+}
@@ -0,0 +1,130 @@
+use super::{ * };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+impl Typechecker {
+    // aliases are transparent: `type Meters = i32;` makes `Meters` another spelling of `i32`
+    pub fn declare_aliases(&mut self, decls: &[TypeAliasDecl]) -> Checked<()> {
+        self.register_aliases(decls)?;
+        self.check_aliases(decls)
+    }
+
+    // a module registers its aliases before resolving the fields of its types, which may be spelled with them
+    pub fn register_aliases(&mut self, decls: &[TypeAliasDecl]) -> Checked<()> {
+        for decl in decls {
+            if self.is_type_name(decl.name.node) {
+                return Err(Error::from_span(decl.name.span, ErrorType::NameError, &format!("type `{}` is defined more than once", decl.name.node)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            self.aliases.insert(decl.name.node, decl.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn check_aliases(&mut self, decls: &[TypeAliasDecl]) -> Checked<()> {
+        // every alias is expanded up front so that cycles are reported even if the alias is never used
+        for decl in decls {
+            self.expand_alias(decl.name.node, decl.name.span)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn expand_alias(&mut self, name: &'static str, span: Span) -> Checked<Type> {
+        if let Some(ty) = self.alias_types.get(name) {
+            return Ok(*ty);
+        }
+
+        if let Some(start) = self.expanding_aliases.iter().position(|other| *other == name) {
+            let mut cycle: Vec<&str> = self.expanding_aliases[start ..].to_vec();
+
+            cycle.push(name);
+
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("type alias `{}` refers to itself: `{}`", name, cycle.join("` -> `"))));
+        }
+
+        let decl = self.aliases[name].clone();
+
+        self.expanding_aliases.push(name);
+
+        let expanded = self.resolve_type(&decl.ty);
+
+        self.expanding_aliases.pop();
+
+        let ty = expanded?;
+
+        self.alias_types.insert(name, ty);
+
+        Ok(ty)
+    }
+
+    // renders a written type for diagnostics, spelling out what an alias stands for
+    pub fn describe_type(&self, written: &TypeExpr, ty: Type) -> String {
+        match written.node {
+            TypeExprKind::Named(name, _) if self.aliases.contains_key(name) => format!("`{}` (aka `{}`)", name, ty),
+            _ => format!("`{}`", ty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ * }, lexer::{ Spanned }, types::{ * } };
+
+    fn alias(name: &'static str, ty: TypeExprKind) -> TypeAliasDecl {
+        TypeAliasDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, name),
+            ty: Spanned::new(0, 0, ty),
+        }
+    }
+
+    fn named(name: &'static str) -> TypeExprKind {
+        TypeExprKind::Named(name, vec![])
+    }
+
+    #[test]
+    fn aliases_are_transparent() {
+        let mut checker = Typechecker::new("main");
+        let handle = alias("Handle", TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::Builtin(SimpleType::Void)))));
+        let table = alias("Table", TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, named("Handle")))));
+
+        checker.declare_aliases(&[ table, handle ]).unwrap();
+
+        let ty = checker.resolve_type(&Spanned::new(0, 0, named("Table"))).unwrap();
+
+        assert_eq!(ty.to_string(), "**void");
+    }
+
+    #[test]
+    fn alias_cycles_are_reported() {
+        let mut checker = Typechecker::new("main");
+        let a = alias("A", TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, named("B")))));
+        let b = alias("B", named("A"));
+        let err = checker.declare_aliases(&[ a, b ]).unwrap_err();
+
+        assert_eq!(err.get_message(), "type alias `A` refers to itself: `A` -> `B` -> `A`");
+    }
+
+    #[test]
+    fn mismatches_show_the_expanded_alias() {
+        let mut checker = Typechecker::new("main");
+        let meters = alias("Meters", TypeExprKind::Builtin(SimpleType::signed(SignedIntegerTypes::Int32)));
+        let decl = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "f"),
+            generics: vec![],
+            params: vec![],
+            ret: None,
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Let("m", false, Some(Spanned::new(0, 0, named("Meters"))), Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))))) ]),
+        };
+
+        checker.declare_aliases(&[ meters ]).unwrap();
+
+        let err = checker.declare_functions(&[ decl ]).unwrap_err();
+
+        assert_eq!(err.get_message(), "expected `Meters` (aka `i32`), found `bool`");
+    }
+}
@@ -0,0 +1,666 @@
+use std::{ collections::{ HashMap } };
+
+use super::{ *, layout::{ StructType }, mutability::{ Binding } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span, Spanned } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+pub fn is_integer(ty: Type) -> bool {
+    matches!(ty, Type::Simple(SimpleType::Int(_)) | Type::Simple(SimpleType::Uint(_)))
+}
+
+pub fn is_numeric(ty: Type) -> bool {
+    is_integer(ty) || matches!(ty, Type::Simple(SimpleType::Fp(_)))
+}
+
+impl Typechecker {
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    // declares an immutable binding, like a parameter
+    pub fn declare_variable(&mut self, name: &'static str, ty: Type) {
+        self.declare_binding(name, ty, false);
+    }
+
+    pub fn declare_binding(&mut self, name: &'static str, ty: Type, is_mutable: bool) {
+        self.scopes.last_mut().expect("typechecker has no scope").insert(name, Binding::new(ty, is_mutable));
+    }
+
+    pub fn lookup_variable(&self, name: &str) -> Option<Type> {
+        self.lookup_binding(name).map(|(_, binding)| binding.ty)
+    }
+
+    // also returns the index of the scope the variable was found in
+    pub fn lookup_binding(&self, name: &str) -> Option<(usize, Binding)> {
+        self.scopes.iter().enumerate().rev().find_map(|(depth, scope)| scope.get(name).map(|binding| (depth, *binding)))
+    }
+
+    pub fn declare_structs(&mut self, decls: &[StructDecl]) -> Checked<()> {
+        self.declare_types(decls, &[])
+    }
+
+    // every struct and enum name is registered before any field or payload is resolved, so that they
+    // may refer to each other, and nothing is laid out until all of them are complete
+    pub fn declare_types(&mut self, structs: &[StructDecl], enums: &[EnumDecl]) -> Checked<()> {
+        for decl in structs {
+            self.register_struct(decl)?;
+        }
+
+        for decl in enums {
+            self.register_enum(decl)?;
+        }
+
+        for decl in structs.iter().filter(|decl| !decl.generics.is_empty()) {
+            self.check_template(decl.name, &decl.generics)?;
+        }
+
+        for decl in enums.iter().filter(|decl| !decl.generics.is_empty()) {
+            self.check_template(decl.name, &decl.generics)?;
+        }
+
+        for decl in structs.iter().filter(|decl| decl.generics.is_empty()) {
+            self.resolve_fields(decl)?;
+        }
+
+        for decl in enums.iter().filter(|decl| decl.generics.is_empty()) {
+            self.resolve_variants(decl)?;
+        }
+
+        for decl in structs.iter().filter(|decl| decl.generics.is_empty()) {
+            self.struct_layout(UserDefinedType::new(self.file, decl.name.node), decl.name.span)?;
+        }
+
+        for decl in enums.iter().filter(|decl| decl.generics.is_empty()) {
+            self.enum_layout(UserDefinedType::new(self.file, decl.name.node), decl.name.span)?;
+        }
+
+        Ok(())
+    }
+
+    // reserves the name of a type declared in this file
+    pub(super) fn check_type_name(&self, name: Spanned<&'static str>) -> Checked<()> {
+        if self.is_type_name(name.node) {
+            return Err(Error::from_span(name.span, ErrorType::NameError, &format!("type `{}` is defined more than once", UserDefinedType::new(self.file, name.node))).with_code(codes::DUPLICATE_DEFINITION));
+        }
+
+        Ok(())
+    }
+
+    fn register_struct(&mut self, decl: &StructDecl) -> Checked<()> {
+        self.check_type_name(decl.name)?;
+
+        if decl.generics.is_empty() {
+            let name = UserDefinedType::new(self.file, decl.name.node);
+
+            self.structs.insert(name, StructType::new(name));
+        } else {
+            self.struct_templates.insert(decl.name.node, decl.clone());
+        }
+
+        Ok(())
+    }
+
+    fn resolve_fields(&mut self, decl: &StructDecl) -> Checked<()> {
+        let mut fields: Vec<(&'static str, Type)> = Vec::with_capacity(decl.fields.len());
+
+        for field in &decl.fields {
+            if fields.iter().any(|(name, _)| *name == field.name) {
+                return Err(Error::from_span(field.ty.span, ErrorType::TypeError, &format!("field `{}` is declared more than once in struct `{}`", field.name, decl.name.node)));
+            }
+
+            fields.push((field.name, self.resolve_type(&field.ty)?));
+        }
+
+        let name = UserDefinedType::new(self.file, decl.name.node);
+        let decl_type = self.structs.get_mut(&name).expect("struct was registered before its fields are resolved");
+
+        decl_type.fields = fields;
+        decl_type.private_fields = decl.fields.iter().filter(|field| !field.is_pub).map(|field| field.name).collect();
+
+        Ok(())
+    }
+
+    pub fn resolve_type(&mut self, ty: &TypeExpr) -> Checked<Type> {
+        match &ty.node {
+            TypeExprKind::Builtin(simple) => Ok(Type::Simple(*simple)),
+
+            TypeExprKind::Named(name, args) => {
+                if let Some(bound) = self.lookup_type_param(name) {
+                    if !args.is_empty() {
+                        return Err(Error::from_span(ty.span, ErrorType::TypeError, &format!("type parameter `{}` does not take type arguments", name)));
+                    }
+
+                    return Ok(bound);
+                }
+
+                if self.aliases.contains_key(name) {
+                    if !args.is_empty() {
+                        return Err(Error::from_span(ty.span, ErrorType::TypeError, &format!("type alias `{}` does not take type arguments", name)));
+                    }
+
+                    return self.expand_alias(name, ty.span);
+                }
+
+                if let Some(instance) = self.resolve_generic_type(Spanned::from_span(ty.span, *name), args)? {
+                    return Ok(instance);
+                }
+
+                let user_type = self.user_type_named(name);
+
+                if !args.is_empty() && self.is_user_type(user_type) {
+                    return Err(Error::from_span(ty.span, ErrorType::TypeError, &format!("type `{}` does not take type arguments", name)));
+                }
+
+                if !self.is_user_type(user_type) {
+                    return Err(Error::from_span(ty.span, ErrorType::NameError, &format!("unknown type `{}`", name)).with_code(codes::UNDEFINED_NAME));
+                }
+
+                Ok(Type::Simple(SimpleType::UserType(user_type)))
+            },
+
+            TypeExprKind::SelfType => self.self_type.ok_or_else(|| {
+                Error::from_span(ty.span, ErrorType::TypeError, "`Self` is only available inside traits and impls")
+            }),
+
+            TypeExprKind::Dyn(name) => {
+                self.check_object_safety(name, ty.span)?;
+
+                Ok(Type::Simple(SimpleType::TraitObject(name)))
+            },
+
+            TypeExprKind::Pointer(references, is_mutable, base) => {
+                match self.resolve_type(base)? {
+                    Type::Simple(simple) => Ok(Type::Complex(ComplexType::Pointer(Pointer::new(8, 0, *references, simple).with_mutability(*is_mutable)))),
+                    // only reachable through an alias, e.g. `*Handle` with `type Handle = *void;`
+                    Type::Complex(ComplexType::Pointer(inner)) => Ok(Type::Complex(ComplexType::Pointer(Pointer::new(8, 0, inner.references + references, inner.base_type).with_mutability(*is_mutable)))),
+                    Type::Complex(complex) => Err(Error::from_span(ty.span, ErrorType::TypeError, &format!("pointers to `{}` are not supported", complex))),
+                }
+            },
+
+            TypeExprKind::Nullable(pointer) => match self.resolve_type(pointer)? {
+                Type::Complex(ComplexType::Pointer(ptr)) => Ok(Type::Complex(ComplexType::Pointer(ptr.with_nullability(true)))),
+                other => Err(Error::from_span(ty.span, ErrorType::TypeError, &format!("only pointers can be nullable, found `?{}`", other))),
+            },
+
+            TypeExprKind::Function(params, ret, convention) => {
+                let params: Vec<&TypeExpr> = params.iter().collect();
+
+                Ok(self.resolve_signature(&params, ret.as_deref(), *convention)?.fn_type(&self.function_types))
+            },
+
+            TypeExprKind::Array(length, base) => {
+                let base_type = match self.resolve_type(base)? {
+                    Type::Simple(SimpleType::Void) | Type::Simple(SimpleType::Varargs) | Type::Simple(SimpleType::TraitObject(_)) => {
+                        return Err(Error::from_span(base.span, ErrorType::TypeError, "array elements must have a size"));
+                    },
+
+                    Type::Simple(simple) => simple,
+
+                    Type::Complex(complex) => {
+                        return Err(Error::from_span(base.span, ErrorType::TypeError, &format!("arrays of `{}` are not supported", complex)));
+                    },
+                };
+
+                match length {
+                    Some(length) => {
+                        let size = self.eval_array_length(length)?;
+
+                        Ok(Type::Complex(ComplexType::Array(Array::new(size, false, base_type))))
+                    },
+
+                    None => Ok(Type::Complex(ComplexType::Array(Array::new(0, true, base_type)))),
+                }
+            },
+        }
+    }
+
+    pub fn check_expr(&mut self, expr: &Expr, expected: Option<Type>) -> Checked<Type> {
+        match &expr.node {
+            ExprKind::Literal(Literal::Null) => self.check_null(expected, expr.span),
+
+            ExprKind::Literal(literal) => {
+                let ty = self.check_literal(*literal, expected);
+
+                Self::check_literal_range(*literal, false, ty, expr.span)?;
+
+                Ok(ty)
+            },
+
+            ExprKind::Unwrap(pointer) => self.check_unwrap(pointer, expected, expr.span),
+
+            ExprKind::Identifier(name) => self.check_identifier(name, expr.span),
+
+            ExprKind::Closure(closure) => self.check_closure(closure, expr.span),
+
+            ExprKind::Unary(op, operand) => self.check_unary(*op, operand, expected, expr.span),
+
+            ExprKind::Binary(lhs, op, rhs) => self.check_binary(lhs, *op, rhs, expected, expr.span),
+
+            ExprKind::Cast(value, target) => {
+                let from = self.check_expr(value, None)?;
+                let to = self.resolve_type(target)?;
+
+                if Self::can_cast(from, to) {
+                    Ok(to)
+                } else {
+                    Err(Error::from_span(expr.span, ErrorType::TypeError, &format!("cannot cast `{}` as {}", from, self.describe_type(target, to))))
+                }
+            },
+
+            ExprKind::New(ty) => self.check_new(ty, expr.span),
+
+            ExprKind::Sizeof(operand) => {
+                self.eval_sizeof(operand, expr.span)?;
+
+                Ok(Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint64)))
+            },
+
+            ExprKind::Field(value, field) => {
+                let ty = self.check_expr(value, None)?;
+
+                self.field_type(ty, field, expr.span)
+            },
+
+            ExprKind::Variant(enum_name, variant, args) => self.check_variant(enum_name, variant, args, expected, expr.span),
+
+            ExprKind::Match(scrutinee, arms) => self.check_match(scrutinee, arms, expected, expr.span),
+
+            ExprKind::Call(callee, args) => self.check_call(callee, args, expr.span),
+
+            ExprKind::MethodCall(receiver, method, args) => self.check_method_call(receiver, *method, args, expr.span),
+        }
+    }
+
+    // implicit conversions allowed wherever a value of type `to` is expected
+    pub fn can_coerce(&self, from: Type, to: Type) -> bool {
+        if from == to {
+            return true;
+        }
+
+        match (from.pointee(), to) {
+            // a plain function is a closure with an empty environment
+            (_, Type::Complex(ComplexType::Function(closure))) if closure.convention == CallingConvention::Closure => match from {
+                Type::Complex(ComplexType::Function(function)) => function.convention == CallingConvention::Synthium && FunctionType { convention: CallingConvention::Closure, ..function } == closure,
+                _ => false,
+            },
+
+            // unsizing `*T` to `*dyn Trait` when `T` implements `Trait`
+            (Some(pointee), Type::Complex(ComplexType::Pointer(target @ Pointer { references: 1, base_type: SimpleType::TraitObject(trait_name), .. }))) => {
+                (!target.is_mutable || Self::is_mutable_pointer(from)) && self.implements(pointee, trait_name)
+            },
+
+            // a `*mut T` can always be used as a `*T` and a `*T` as a `?*T`, never the other way around
+            (_, Type::Complex(ComplexType::Pointer(target))) => match from {
+                Type::Complex(ComplexType::Pointer(source)) => {
+                    let mutability = source.is_mutable || !target.is_mutable;
+                    let nullability = !source.is_nullable || target.is_nullable;
+
+                    mutability && nullability && source.with_mutability(target.is_mutable).with_nullability(target.is_nullable) == target
+                },
+
+                _ => false,
+            },
+
+            _ => false,
+        }
+    }
+
+    // `sizeof` is always known at compile time, whether it is applied to a type or an expression
+    pub fn eval_sizeof(&mut self, operand: &SizeofOperand, span: Span) -> Checked<u64> {
+        let ty = match operand {
+            SizeofOperand::Type(ty) => self.resolve_type(ty)?,
+            SizeofOperand::Expr(expr) => self.check_expr(expr, None)?,
+        };
+
+        Ok(self.layout_of(ty, span)?.size)
+    }
+
+    // `new T` needs a layout for `T` to size the allocation by, which rules out `void` and unsized types
+    fn check_new(&mut self, ty: &TypeExpr, span: Span) -> Checked<Type> {
+        let allocated = self.resolve_type(ty)?;
+
+        self.layout_of(allocated, span)?;
+
+        allocated.pointer_to_mut().ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("cannot allocate a value of type `{}`", allocated))
+        })
+    }
+
+    // anything `new` could have returned can be deleted; a null pointer is allowed and does nothing
+    pub fn check_delete(&mut self, pointer: &Expr) -> Checked<()> {
+        let ty = self.check_expr(pointer, None)?;
+
+        match ty {
+            Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::TraitObject(_), .. })) => {
+                Err(Error::from_span(pointer.span, ErrorType::TypeError, &format!("cannot delete through a `{}`, delete the value it was made from", ty)))
+            },
+
+            Type::Complex(ComplexType::Pointer(_)) => Ok(()),
+
+            _ => Err(Error::from_span(pointer.span, ErrorType::TypeError, &format!("cannot delete a value of type `{}`", ty))),
+        }
+    }
+
+    pub fn field_type(&self, ty: Type, field: &str, span: Span) -> Checked<Type> {
+        // fields can be accessed through a single level of indirection
+        let user_type = match ty {
+            Type::Simple(SimpleType::UserType(name)) => name,
+            Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::UserType(name), .. })) => name,
+
+            _ => return Err(Error::from_span(span, ErrorType::TypeError, &format!("type `{}` has no fields", ty))),
+        };
+
+        Self::check_non_null(ty, span)?;
+
+        let decl = self.structs.get(&user_type).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("type `{}` has no fields", ty))
+        })?;
+
+        let (_, field_type) = decl.get_field(field).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("struct `{}` has no field named `{}`", user_type, field))
+        })?;
+
+        if user_type.file != self.file && decl.private_fields.contains(&field) {
+            return Err(Error::from_span(span, ErrorType::NameError, &format!("field `{}` of struct `{}` is private to module `{}`", field, user_type.name, user_type.file)).with_code(codes::PRIVATE_ITEM));
+        }
+
+        Ok(field_type)
+    }
+
+    // a named function used as a value decays to a pointer to it
+    fn check_identifier(&mut self, name: &'static str, span: Span) -> Checked<Type> {
+        if let Some((depth, binding)) = self.lookup_binding(name) {
+            self.capture_variable(name, binding.ty, depth);
+
+            return Ok(binding.ty);
+        }
+
+        if let Some(ty) = self.static_type(name) {
+            return Ok(ty);
+        }
+
+        if self.generic_functions.contains_key(name) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("generic function `{}` cannot be used as a value", name)));
+        }
+
+        match self.functions.get(name) {
+            Some(sig) => Ok(sig.fn_type(&self.function_types)),
+            None => Err(Error::from_span(span, ErrorType::NameError, &format!("cannot find value `{}` in this scope", name)).with_code(codes::UNDEFINED_NAME)),
+        }
+    }
+
+    pub(super) fn check_literal(&self, literal: Literal, expected: Option<Type>) -> Type {
+        match literal {
+            Literal::Int(_, Some(IntegerSuffix::Signed(suffix))) => Type::Simple(SimpleType::signed(suffix)),
+            Literal::Int(_, Some(IntegerSuffix::Unsigned(suffix))) => Type::Simple(SimpleType::unsigned(suffix)),
+
+            // an unsuffixed integer takes whatever integer type its context expects
+            Literal::Int(_, None) => match expected {
+                Some(ty) if is_integer(ty) => ty,
+                _ => Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)),
+            },
+
+            Literal::Float(_, Some(suffix)) => Type::Simple(SimpleType::float(suffix)),
+
+            Literal::Float(_, None) => match expected {
+                Some(ty @ Type::Simple(SimpleType::Fp(_))) => ty,
+                _ => Type::Simple(SimpleType::float(FloatingPointTypes::FP64)),
+            },
+
+            Literal::Bool(_) => Type::Simple(SimpleType::Bool),
+            Literal::Char(_) => Type::Simple(SimpleType::Char),
+            Literal::String(_) => Type::Simple(SimpleType::String),
+
+            Literal::Null => match expected {
+                Some(Type::Complex(ComplexType::Pointer(ptr))) => Type::Complex(ComplexType::Pointer(ptr.with_nullability(true))),
+                _ => Type::Complex(ComplexType::Pointer(Pointer::new(8, 0, 1, SimpleType::Void).with_nullability(true))),
+            },
+        }
+    }
+
+    fn check_unary(&mut self, op: UnaryOp, operand: &Expr, expected: Option<Type>, span: Span) -> Checked<Type> {
+        match op {
+            // `-128i8` is in range even though `128i8` is not
+            UnaryOp::Negate if matches!(operand.node, ExprKind::Literal(Literal::Int(..))) => {
+                let ExprKind::Literal(literal) = operand.node else {
+                    unreachable!("matched above")
+                };
+
+                let ty = self.check_literal(literal, expected);
+
+                match ty {
+                    Type::Simple(SimpleType::Int(_)) => Self::check_literal_range(literal, true, ty, span).map(|_| ty),
+                    _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot negate a value of type `{}`", ty))),
+                }
+            },
+
+            UnaryOp::Negate => {
+                let ty = self.check_expr(operand, expected)?;
+
+                match ty {
+                    Type::Simple(SimpleType::Int(_)) | Type::Simple(SimpleType::Fp(_)) => Ok(ty),
+                    _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot negate a value of type `{}`", ty))),
+                }
+            },
+
+            UnaryOp::Not => {
+                let ty = self.check_expr(operand, expected)?;
+
+                match ty {
+                    Type::Simple(SimpleType::Bool) => Ok(ty),
+                    _ if is_integer(ty) => Ok(ty),
+                    _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot apply `!` to a value of type `{}`", ty))),
+                }
+            },
+
+            UnaryOp::Deref => {
+                let ty = self.check_expr(operand, None)?;
+
+                Self::check_non_null(ty, span)?;
+
+                match ty.pointee() {
+                    Some(Type::Simple(SimpleType::Void)) => Err(Error::from_span(span, ErrorType::TypeError, "cannot dereference a `*void`")),
+                    Some(pointee) => Ok(pointee),
+                    None => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot dereference a value of type `{}`", ty))),
+                }
+            },
+
+            UnaryOp::AddressOf => {
+                let ty = self.check_expr(operand, None)?;
+
+                ty.pointer_to().ok_or_else(|| {
+                    Error::from_span(span, ErrorType::TypeError, &format!("cannot take the address of a value of type `{}`", ty))
+                })
+            },
+
+            UnaryOp::AddressOfMut => {
+                let ty = self.check_expr(operand, None)?;
+
+                self.check_mutable_place(operand)?;
+
+                ty.pointer_to_mut().ok_or_else(|| {
+                    Error::from_span(span, ErrorType::TypeError, &format!("cannot take the address of a value of type `{}`", ty))
+                })
+            },
+        }
+    }
+
+    fn check_binary(&mut self, lhs: &Expr, op: BinaryOp, rhs: &Expr, expected: Option<Type>, span: Span) -> Checked<Type> {
+        let (lhs_type, rhs_type) = self.check_operands(lhs, op, rhs, expected)?;
+
+        if lhs_type != rhs_type {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("mismatched operand types `{}` and `{}`", lhs_type, rhs_type)));
+        }
+
+        if op.is_logical() {
+            return match lhs_type {
+                Type::Simple(SimpleType::Bool) => Ok(lhs_type),
+                _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("logical operators need `bool` operands, found `{}`", lhs_type))),
+            };
+        }
+
+        if op.is_comparison() {
+            return Ok(Type::Simple(SimpleType::Bool));
+        }
+
+        let valid = match op {
+            BinaryOp::BitAnd | BinaryOp::BitOr => is_integer(lhs_type) || lhs_type == Type::Simple(SimpleType::Bool),
+            _ => is_numeric(lhs_type),
+        };
+
+        if valid {
+            Ok(lhs_type)
+        } else {
+            Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot apply arithmetic to values of type `{}`", lhs_type)))
+        }
+    }
+
+    // types both operands, letting an unsuffixed literal on either side pick up the other side's type
+    pub fn check_operands(&mut self, lhs: &Expr, op: BinaryOp, rhs: &Expr, expected: Option<Type>) -> Checked<(Type, Type)> {
+        let operand_expected = if op.is_arithmetic() { expected } else { None };
+
+        if Self::is_untyped_literal(lhs) && !Self::is_untyped_literal(rhs) {
+            let rhs_type = self.check_expr(rhs, operand_expected)?;
+            let lhs_type = self.check_expr(lhs, Some(rhs_type))?;
+
+            Ok((lhs_type, rhs_type))
+        } else {
+            let lhs_type = self.check_expr(lhs, operand_expected)?;
+            let rhs_type = self.check_expr(rhs, Some(lhs_type))?;
+
+            Ok((lhs_type, rhs_type))
+        }
+    }
+
+    pub(super) fn is_untyped_literal(expr: &Expr) -> bool {
+        match &expr.node {
+            ExprKind::Literal(Literal::Int(_, None)) | ExprKind::Literal(Literal::Float(_, None)) | ExprKind::Literal(Literal::Null) => true,
+            ExprKind::Unary(UnaryOp::Negate, operand) => Self::is_untyped_literal(operand),
+            _ => false,
+        }
+    }
+
+    // the conversions `Value::cast` performs at run time, for the types the checker can tell apart
+    pub(crate) fn can_cast(from: Type, to: Type) -> bool {
+        let is_primitive = |ty: Type| is_numeric(ty) || matches!(ty, Type::Simple(SimpleType::Char) | Type::Simple(SimpleType::Bool));
+
+        match (from, to) {
+            _ if from == to => true,
+            // a cast may change what a pointer points to, but not make it writable or assume it is not null
+            (Type::Complex(ComplexType::Pointer(from)), Type::Complex(ComplexType::Pointer(to))) => (from.is_mutable || !to.is_mutable) && (to.is_nullable || !from.is_nullable),
+            (Type::Complex(ComplexType::Pointer(_)), _) | (_, Type::Complex(ComplexType::Pointer(_))) => is_integer(from) || is_integer(to),
+            // a float is not a code point, even when it is a whole number
+            (Type::Simple(SimpleType::Fp(_)), Type::Simple(SimpleType::Char)) => false,
+            _ => is_primitive(from) && is_primitive(to),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ collections::{ HashMap } };
+
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, modules::{ ModuleGraph, fixtures::{ TempDir } }, resolve::{ ResolvedProgram }, types::{ * } };
+
+    fn sizeof_type(ty: TypeExpr) -> Expr {
+        Spanned::new(0, 0, ExprKind::Sizeof(SizeofOperand::Type(ty)))
+    }
+
+    fn declare_foo(checker: &mut Typechecker) {
+        let foo = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Foo"),
+            generics: vec![],
+            fields: vec![
+                StructField { is_pub: true, name: "a", ty: builtin(SimpleType::signed(SignedIntegerTypes::Int64)) },
+                StructField { is_pub: false, name: "b", ty: builtin(SimpleType::Bool) },
+            ],
+        };
+
+        checker.declare_structs(&[ foo ]).unwrap();
+    }
+
+    #[test]
+    fn sizeof_in_array_length() {
+        let mut checker = Typechecker::new("main");
+
+        declare_foo(&mut checker);
+
+        // [sizeof(Foo) * 2]u8
+        let length = Spanned::new(0, 0, ExprKind::Binary(Box::new(sizeof_type(named("Foo"))), BinaryOp::Mul, Box::new(int(2))));
+        let arr = Spanned::new(0, 0, TypeExprKind::Array(Some(Box::new(length)), Box::new(builtin(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)))));
+
+        let ty = checker.resolve_type(&arr).unwrap();
+
+        assert_eq!(ty, Type::Complex(ComplexType::Array(Array::new(32, false, SimpleType::unsigned(UnsignedIntegerTypes::Uint8)))));
+    }
+
+    #[test]
+    fn sizeof_expression() {
+        let mut checker = Typechecker::new("main");
+
+        declare_foo(&mut checker);
+        checker.declare_variable("foo", Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Foo"))));
+
+        let field = Spanned::new(0, 0, ExprKind::Field(Box::new(Spanned::new(0, 0, ExprKind::Identifier("foo"))), "b"));
+
+        assert_eq!(checker.eval_sizeof(&SizeofOperand::Expr(Box::new(field)), Span::new(0, 0)).unwrap(), 1);
+        assert_eq!(checker.eval_sizeof(&SizeofOperand::Type(named("Foo")), Span::new(0, 0)).unwrap(), 16);
+    }
+
+    #[test]
+    fn sizeof_unsized_type_is_an_error() {
+        let mut checker = Typechecker::new("main");
+        let runtime_arr = Spanned::new(0, 0, TypeExprKind::Array(None, Box::new(builtin(SimpleType::Char))));
+
+        assert!(checker.eval_sizeof(&SizeofOperand::Type(runtime_arr), Span::new(0, 0)).is_err());
+        assert!(checker.eval_sizeof(&SizeofOperand::Type(builtin(SimpleType::Void)), Span::new(0, 0)).is_err());
+        assert!(checker.eval_sizeof(&SizeofOperand::Type(builtin(SimpleType::Varargs)), Span::new(0, 0)).is_err());
+    }
+
+    #[test]
+    fn private_fields_are_hidden_from_other_modules() {
+        let root = TempDir::new("privacy").with_modules(&[
+            ("main", "import Point from \"geometry\";"),
+            ("geometry", ""),
+        ]);
+        let graph = ModuleGraph::load(root.path(), "main").unwrap();
+        let point = StructDecl {
+            is_pub: true,
+            name: Spanned::new(0, 0, "Point"),
+            generics: vec![],
+            fields: vec![
+                StructField { is_pub: true, name: "x", ty: i32_type() },
+                StructField { is_pub: false, name: "y", ty: i32_type() },
+            ],
+        };
+        // fn get(p: Point) -> i32 { return p.<field>; }
+        let get = |field| FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "get"),
+            generics: vec![],
+            params: vec![ param("p", named("Point")) ],
+            ret: Some(i32_type()),
+            body: Some(vec![ stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Field(Box::new(ident("p")), field))))) ]),
+        };
+
+        let check = |field| {
+            let items = HashMap::from([ ("geometry", vec![ Item::Struct(point.clone()) ]), ("main", vec![ Item::Fn(get(field)) ]) ]);
+            let program = ResolvedProgram::resolve(&graph, &items).unwrap();
+            let mut checker = Typechecker::new("geometry");
+
+            checker.enter_module("geometry", program.module("geometry").unwrap().imported());
+            checker.declare_structs(std::slice::from_ref(&point))?;
+            checker.enter_module("main", program.module("main").unwrap().imported());
+            checker.declare_functions(&[ get(field) ])
+        };
+
+        assert!(check("x").is_ok());
+        assert_eq!(check("y").unwrap_err().get_message(), "field `y` of struct `Point` is private to module `geometry`");
+    }
+}
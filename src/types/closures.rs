@@ -0,0 +1,225 @@
+use super::{ *, layout::{ Layout }, functions::{ FnSignature } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CaptureMode {
+    // the value is copied into the environment when the closure is created
+    Value,
+    // the environment holds the address of the variable, which must outlive the closure
+    Ref,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Capture {
+    pub name: &'static str,
+    pub ty: Type,
+    pub mode: CaptureMode,
+}
+
+impl Capture {
+    // the type of the environment slot holding this capture
+    pub fn slot_type(&self) -> Option<Type> {
+        match self.mode {
+            CaptureMode::Value => Some(self.ty),
+            CaptureMode::Ref => self.ty.pointer_to(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureFrame {
+    // index of the closure's outermost scope; variables from shallower scopes are captured
+    pub boundary: usize,
+    pub by_ref: Vec<&'static str>,
+    pub captures: Vec<Capture>,
+}
+
+// everything code generation needs to build a closure object: its signature and the layout of
+// the heap allocated environment the captured variables are stored in, in order of first use
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureType {
+    pub span: Span,
+    pub sig: FnSignature,
+    pub captures: Vec<Capture>,
+    pub environment: Layout,
+    pub offsets: Vec<u64>,
+}
+
+impl Typechecker {
+    pub fn closure_at(&self, span: Span) -> Option<&ClosureType> {
+        self.closures.iter().find(|closure| closure.span == span)
+    }
+
+    // records that every closure being checked whose boundary lies above `depth` uses the variable
+    pub(super) fn capture_variable(&mut self, name: &'static str, ty: Type, depth: usize) {
+        for frame in self.closure_frames.iter_mut().filter(|frame| depth < frame.boundary) {
+            if !frame.captures.iter().any(|capture| capture.name == name) {
+                let mode = if frame.by_ref.contains(&name) { CaptureMode::Ref } else { CaptureMode::Value };
+
+                frame.captures.push(Capture {
+                    name,
+                    ty,
+                    mode,
+                });
+            }
+        }
+    }
+
+    pub(super) fn check_closure(&mut self, closure: &ClosureExpr, span: Span) -> Checked<Type> {
+        for (index, param) in closure.params.iter().enumerate() {
+            if closure.params[.. index].iter().any(|other| other.name.node == param.name.node) {
+                return Err(Error::from_span(param.name.span, ErrorType::TypeError, &format!("parameter `{}` is declared more than once", param.name.node)));
+            }
+        }
+
+        let params: Vec<&TypeExpr> = closure.params.iter().map(|param| &param.ty).collect();
+        let mut sig = self.resolve_signature(&params, closure.ret.as_ref(), CallingConvention::Closure)?;
+
+        if sig.is_variadic {
+            return Err(Error::from_span(span, ErrorType::TypeError, "closures cannot be variadic"));
+        }
+
+        for name in &closure.by_ref {
+            if self.lookup_variable(name.node).is_none() {
+                return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("cannot capture `{}` by reference: there is no such variable in the enclosing scope", name.node)));
+            }
+        }
+
+        self.closure_frames.push(ClosureFrame {
+            boundary: self.scopes.len(),
+            by_ref: closure.by_ref.iter().map(|name| name.node).collect(),
+            captures: vec![],
+        });
+
+        let previous = self.return_type.replace(sig.ret);
+
+        self.push_scope();
+
+        for (param, ty) in closure.params.iter().zip(&sig.params) {
+            self.declare_variable(param.name.node, *ty);
+        }
+
+        let checked = self.check_block(&closure.body);
+
+        self.pop_scope();
+        self.return_type = previous;
+
+        let frame = self.closure_frames.pop().expect("closure frame was pushed above");
+
+        checked?;
+
+        if sig.ret != Type::Simple(SimpleType::Void) && !Self::block_returns(&closure.body) {
+            return Err(Error::from_span(span, ErrorType::TypeError, "closure does not return a value on every path"));
+        }
+
+        if let Some(unused) = closure.by_ref.iter().find(|name| !frame.captures.iter().any(|capture| capture.name == name.node)) {
+            return Err(Error::from_span(unused.span, ErrorType::TypeError, &format!("`{}` is captured by reference but never used", unused.node)));
+        }
+
+        let (environment, offsets) = self.environment_layout(&frame.captures, span)?;
+
+        sig.convention = CallingConvention::Closure;
+
+        let ty = sig.fn_type(&self.function_types);
+
+        self.closures.push(ClosureType {
+            span,
+            sig,
+            captures: frame.captures,
+            environment,
+            offsets,
+        });
+
+        Ok(ty)
+    }
+
+    // laid out like a struct with one field per capture
+    fn environment_layout(&self, captures: &[Capture], span: Span) -> Checked<(Layout, Vec<u64>)> {
+        let mut offsets = Vec::with_capacity(captures.len());
+        let mut offset = 0;
+        let mut align = 1;
+
+        for capture in captures {
+            let ty = capture.slot_type().ok_or_else(|| {
+                Error::from_span(span, ErrorType::TypeError, &format!("cannot capture `{}` of type `{}` by reference", capture.name, capture.ty))
+            })?;
+
+            let field = self.layout_of(ty, span)?;
+
+            offset = field.align_offset(offset);
+            offsets.push(offset);
+            offset += field.size;
+            align = align.max(field.align);
+        }
+
+        Ok((Layout::new(offset.div_ceil(align) * align, align), offsets))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, types::{ *, layout::{ Layout }, closures::{ CaptureMode } } };
+
+    fn add(lhs: Expr, rhs: Expr) -> Expr {
+        binary(lhs, BinaryOp::Add, rhs)
+    }
+
+    // fn[by_ref...](x: i32) -> i32 { return x + a + b; }
+    fn closure(by_ref: Vec<&'static str>) -> Expr {
+        let closure = ClosureExpr {
+            by_ref: by_ref.into_iter().map(|name| Spanned::new(0, 0, name)).collect(),
+            params: vec![ Param { name: Spanned::new(0, 0, "x"), ty: i32_type() } ],
+            ret: Some(i32_type()),
+            body: vec![ Spanned::new(0, 0, StmtKind::Return(Some(add(add(ident("x"), ident("a")), ident("b"))))) ],
+        };
+
+        Spanned::new(1, 2, ExprKind::Closure(Box::new(closure)))
+    }
+
+    fn checker() -> Typechecker {
+        let mut checker = Typechecker::new("main");
+
+        checker.declare_variable("a", Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+        checker.declare_variable("b", Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+
+        checker
+    }
+
+    #[test]
+    fn captures_are_laid_out_in_the_environment() {
+        let mut checker = checker();
+        let ty = checker.check_expr(&closure(vec![ "b" ]), None).unwrap();
+
+        assert_eq!(ty.to_string(), "closure fn(i32) -> i32");
+        assert_eq!(checker.layout_of(ty, Span::new(0, 0)).unwrap(), Layout::new(16, 8));
+
+        let info = checker.closure_at(Span::new(1, 2)).unwrap();
+        let modes: Vec<(&str, CaptureMode)> = info.captures.iter().map(|capture| (capture.name, capture.mode)).collect();
+
+        assert_eq!(modes, vec![ ("a", CaptureMode::Value), ("b", CaptureMode::Ref) ]);
+        assert_eq!(info.offsets, vec![ 0, 8 ]);
+        assert_eq!(info.environment, Layout::new(16, 8));
+    }
+
+    #[test]
+    fn closures_are_called_through_variables() {
+        let mut checker = checker();
+        let ty = checker.check_expr(&closure(vec![]), None).unwrap();
+
+        checker.declare_variable("f", ty);
+
+        let call = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("f")), vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Int(1, None))) ]));
+
+        assert_eq!(checker.check_expr(&call, None).unwrap(), Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+    }
+
+    #[test]
+    fn unknown_reference_captures_are_rejected() {
+        let mut checker = checker();
+        let err = checker.check_expr(&closure(vec![ "c" ]), None).unwrap_err();
+
+        assert_eq!(err.get_message(), "cannot capture `c` by reference: there is no such variable in the enclosing scope");
+    }
+}
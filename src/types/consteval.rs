@@ -0,0 +1,381 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ *, check::{ is_integer } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Evaluated = std::result::Result<ConstValue, Error>;
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(SignedInteger),
+    Uint(UnsignedInteger),
+    Fp(FloatingPoint),
+    Bool(bool),
+    Char(char),
+}
+
+impl ConstValue {
+    pub fn get_type(&self) -> Type {
+        match self {
+            Self::Int(v) => Type::Simple(SimpleType::signed(v.base_type)),
+            Self::Uint(v) => Type::Simple(SimpleType::unsigned(v.base_type)),
+            Self::Fp(v) => Type::Simple(SimpleType::float(v.base_type)),
+            Self::Bool(_) => Type::Simple(SimpleType::Bool),
+            Self::Char(_) => Type::Simple(SimpleType::Char),
+        }
+    }
+
+    // the value as a mathematical integer, if it is one
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::Int(v) => Some(v.value as i128),
+            Self::Uint(v) => Some(v.value as i128),
+            Self::Char(c) => Some(*c as i128),
+            Self::Bool(b) => Some(*b as i128),
+            Self::Fp(_) => None,
+        }
+    }
+
+    // an integer of the same type as `self` holding `value`, if it fits
+    fn with_int(&self, value: i128) -> Option<Self> {
+        match self {
+            Self::Int(v) if (v.base_type.min() ..= v.base_type.max()).contains(&value) => Some(Self::Int(SignedInteger::new(v.base_type, value as i64))),
+            Self::Uint(v) if (0 ..= v.base_type.max()).contains(&value) => Some(Self::Uint(UnsignedInteger::new(v.base_type, value as u64))),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ConstValue {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(v) => write!(f, "{}{}", v.value, v.base_type),
+            Self::Uint(v) => write!(f, "{}{}", v.value, v.base_type),
+            Self::Fp(v) => write!(f, "{}{}", v.value, v.base_type),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Char(c) => write!(f, "'{}'", c),
+        }
+    }
+}
+
+impl Typechecker {
+    // evaluates a constant expression; the expression is typechecked once, up front, so that
+    // every literal is folded at the type it would have at runtime
+    pub fn eval_const(&mut self, expr: &Expr, expected: Option<Type>) -> Evaluated {
+        self.check_expr(expr, expected)?;
+
+        self.fold_const(expr, expected)
+    }
+
+    // evaluates an expression that has already been checked; the type of each part is inferred
+    // the way the checker infers it, from what its context expects and the values folded so far
+    pub(super) fn fold_const(&mut self, expr: &Expr, expected: Option<Type>) -> Evaluated {
+        match &expr.node {
+            ExprKind::Literal(literal) => Self::eval_literal(*literal, self.check_literal(*literal, expected), expr.span),
+
+            ExprKind::Identifier(name) => self.eval_const_identifier(name, expr.span),
+
+            ExprKind::Call(callee, args) => self.eval_const_call(callee, args, expr.span),
+
+            ExprKind::Unary(op, operand) => {
+                // the literal of `-128i8` is only in range once negated, so it is negated before it is fitted to its type
+                if let (UnaryOp::Negate, ExprKind::Literal(literal @ Literal::Int(value, _))) = (op, &operand.node) {
+                    let ty = self.check_literal(*literal, expected);
+                    let zero = Self::eval_literal(Literal::Int(0, None), ty, operand.span)?;
+
+                    return Self::fit(zero, -(*value as i128), &format!("-{}{}", value, ty), expr.span);
+                }
+
+                let value = self.fold_const(operand, expected)?;
+
+                Self::eval_unary(*op, value, expr.span)
+            },
+
+            ExprKind::Binary(lhs, op, rhs) => {
+                let operand_expected = if op.is_arithmetic() { expected } else { None };
+
+                let (lhs, rhs) = if Self::is_untyped_literal(lhs) && !Self::is_untyped_literal(rhs) {
+                    let rhs = self.fold_const(rhs, operand_expected)?;
+
+                    (self.fold_const(lhs, Some(rhs.get_type()))?, rhs)
+                } else {
+                    let lhs = self.fold_const(lhs, operand_expected)?;
+
+                    (lhs, self.fold_const(rhs, Some(lhs.get_type()))?)
+                };
+
+                Self::eval_binary(lhs, *op, rhs, expr.span)
+            },
+
+            ExprKind::Cast(value, target) => {
+                let to = self.resolve_type(target)?;
+                let value = self.fold_const(value, None)?;
+
+                Self::eval_cast(value, to, expr.span)
+            },
+
+            ExprKind::Sizeof(operand) => {
+                let size = self.eval_sizeof(operand, expr.span)?;
+
+                Ok(ConstValue::Uint(UnsignedInteger::new(UnsignedIntegerTypes::Uint64, size)))
+            },
+
+            ExprKind::Field(..) | ExprKind::Variant(..) | ExprKind::Match(..) | ExprKind::MethodCall(..) | ExprKind::Closure(..) | ExprKind::Unwrap(..) | ExprKind::New(..) => {
+                Err(Error::from_span(expr.span, ErrorType::TypeError, "expression is not a constant expression"))
+            },
+        }
+    }
+
+    pub fn eval_array_length(&mut self, expr: &Expr) -> std::result::Result<usize, Error> {
+        let value = self.eval_const(expr, Some(Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint64))))?;
+
+        if !is_integer(value.get_type()) {
+            return Err(Error::from_span(expr.span, ErrorType::TypeError, &format!("array length must be an integer, found `{}`", value.get_type())));
+        }
+
+        match value.as_i128() {
+            Some(length) if length >= 0 => Ok(length as usize),
+            _ => Err(Error::from_span(expr.span, ErrorType::TypeError, &format!("array length must not be negative, found `{}`", value))),
+        }
+    }
+
+    fn eval_literal(literal: Literal, ty: Type, span: Span) -> Evaluated {
+        match (literal, ty) {
+            (Literal::Int(value, _), Type::Simple(SimpleType::Int(v))) => Ok(ConstValue::Int(SignedInteger::new(v.base_type, value as i64))),
+            (Literal::Int(value, _), Type::Simple(SimpleType::Uint(v))) => Ok(ConstValue::Uint(UnsignedInteger::new(v.base_type, value))),
+            (Literal::Float(value, _), Type::Simple(SimpleType::Fp(v))) => Ok(ConstValue::Fp(FloatingPoint::new(v.base_type, value))),
+            (Literal::Bool(value), _) => Ok(ConstValue::Bool(value)),
+            (Literal::Char(value), _) => Ok(ConstValue::Char(value)),
+
+            _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("a literal of type `{}` is not a constant expression", ty))),
+        }
+    }
+
+    fn eval_unary(op: UnaryOp, value: ConstValue, span: Span) -> Evaluated {
+        match (op, value) {
+            (UnaryOp::Negate, ConstValue::Int(v)) => Self::fit(value, -(v.value as i128), &format!("-{}", value), span),
+            (UnaryOp::Negate, ConstValue::Fp(v)) => Ok(ConstValue::Fp(FloatingPoint::new(v.base_type, -v.value))),
+            (UnaryOp::Not, ConstValue::Bool(b)) => Ok(ConstValue::Bool(!b)),
+            (UnaryOp::Not, ConstValue::Int(v)) => Ok(ConstValue::Int(SignedInteger::new(v.base_type, !v.value))),
+            (UnaryOp::Not, ConstValue::Uint(v)) => Ok(ConstValue::Uint(UnsignedInteger::new(v.base_type, v.value ^ v.base_type.max() as u64))),
+
+            _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot evaluate `{:?}` on `{}` at compile time", op, value))),
+        }
+    }
+
+    fn eval_binary(lhs: ConstValue, op: BinaryOp, rhs: ConstValue, span: Span) -> Evaluated {
+        if op.is_comparison() {
+            let ordering = match (lhs, rhs) {
+                (ConstValue::Fp(a), ConstValue::Fp(b)) => a.value.partial_cmp(&b.value),
+                _ => lhs.as_i128().zip(rhs.as_i128()).map(|(a, b)| a.cmp(&b)),
+            };
+
+            let result = match (op, ordering) {
+                (BinaryOp::Equals, ordering) => ordering == Some(std::cmp::Ordering::Equal),
+                (BinaryOp::NotEquals, ordering) => ordering != Some(std::cmp::Ordering::Equal),
+                (_, None) => false,
+                (BinaryOp::Smaller, Some(ordering)) => ordering.is_lt(),
+                (BinaryOp::Greater, Some(ordering)) => ordering.is_gt(),
+                (BinaryOp::SmallerEquals, Some(ordering)) => ordering.is_le(),
+                (_, Some(ordering)) => ordering.is_ge(),
+            };
+
+            return Ok(ConstValue::Bool(result));
+        }
+
+        match (lhs, rhs) {
+            (ConstValue::Bool(a), ConstValue::Bool(b)) => match op {
+                BinaryOp::And | BinaryOp::BitAnd => Ok(ConstValue::Bool(a && b)),
+                BinaryOp::Or | BinaryOp::BitOr => Ok(ConstValue::Bool(a || b)),
+                _ => Err(Error::from_span(span, ErrorType::TypeError, "cannot apply arithmetic to `bool` values")),
+            },
+
+            // computed exactly, then checked against the width of the operands' type
+            (ConstValue::Int(_), ConstValue::Int(_)) | (ConstValue::Uint(_), ConstValue::Uint(_)) => {
+                let (a, b) = lhs.as_i128().zip(rhs.as_i128()).expect("integers convert to i128");
+                let value = match op {
+                    BinaryOp::Add => a.checked_add(b),
+                    BinaryOp::Sub => a.checked_sub(b),
+                    BinaryOp::Mul => a.checked_mul(b),
+                    BinaryOp::Div | BinaryOp::Mod if b == 0 => return Err(Self::division_by_zero(span)),
+                    BinaryOp::Div => a.checked_div(b),
+                    BinaryOp::Mod => a.checked_rem(b),
+                    BinaryOp::BitAnd => Some(a & b),
+                    _ => Some(a | b),
+                };
+
+                let expression = format!("{} {} {}", lhs, op, rhs);
+
+                match value {
+                    Some(value) => Self::fit(lhs, value, &expression, span),
+                    None => Err(Error::from_span(span, ErrorType::TypeError, &format!("arithmetic overflow in constant expression: `{}` does not fit in `{}`", expression, lhs.get_type()))),
+                }
+            },
+
+            (ConstValue::Fp(a), ConstValue::Fp(b)) => {
+                let value = match op {
+                    BinaryOp::Add => a.value + b.value,
+                    BinaryOp::Sub => a.value - b.value,
+                    BinaryOp::Mul => a.value * b.value,
+                    BinaryOp::Div => a.value / b.value,
+                    _ => a.value % b.value,
+                };
+
+                Ok(ConstValue::Fp(FloatingPoint::new(a.base_type, value)))
+            },
+
+            _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot evaluate `{} {} {}` at compile time", lhs, op, rhs))),
+        }
+    }
+
+    fn eval_cast(value: ConstValue, to: Type, span: Span) -> Evaluated {
+        let float = match value {
+            ConstValue::Fp(v) => Some(v.value),
+            _ => None,
+        };
+
+        match to {
+            // integers wrap around to the target width, floats saturate
+            Type::Simple(SimpleType::Int(v)) => {
+                let shift = 128 - v.base_type.bits();
+                let value = match float {
+                    Some(f) => (f as i128).clamp(v.base_type.min(), v.base_type.max()),
+                    None => (value.as_i128().unwrap_or(0) << shift) >> shift,
+                };
+
+                Ok(ConstValue::Int(SignedInteger::new(v.base_type, value as i64)))
+            },
+
+            Type::Simple(SimpleType::Uint(v)) => {
+                let value = match float {
+                    Some(f) => (f as i128).clamp(0, v.base_type.max()),
+                    None => value.as_i128().unwrap_or(0) & v.base_type.max(),
+                };
+
+                Ok(ConstValue::Uint(UnsignedInteger::new(v.base_type, value as u64)))
+            },
+
+            Type::Simple(SimpleType::Fp(v)) => {
+                let value = float.unwrap_or_else(|| value.as_i128().unwrap_or(0) as f64);
+
+                Ok(ConstValue::Fp(FloatingPoint::new(v.base_type, value)))
+            },
+
+            Type::Simple(SimpleType::Bool) => Ok(ConstValue::Bool(float.map_or_else(|| value.as_i128() != Some(0), |f| f != 0.0))),
+
+            _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot cast `{}` to `{}` at compile time", value, to))),
+        }
+    }
+
+    // `value` as an integer of the same type as `template`, or an overflow error showing what `expression` evaluated to
+    fn fit(template: ConstValue, value: i128, expression: &str, span: Span) -> Evaluated {
+        template.with_int(value).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("arithmetic overflow in constant expression: `{}` evaluates to {}, which does not fit in `{}`", expression, value, template.get_type()))
+        })
+    }
+
+    pub(super) fn check_literal_range(literal: Literal, negated: bool, ty: Type, span: Span) -> Checked<()> {
+        let out_of_range = |value: &dyn Display, min: &dyn Display, max: &dyn Display| {
+            Err(Error::from_span(span, ErrorType::TypeError, &format!("literal `{}` is out of range for `{}`, whose values range from {} to {}", value, ty, min, max)))
+        };
+
+        match (literal, ty) {
+            (Literal::Int(value, _), Type::Simple(SimpleType::Int(v))) => {
+                let value = if negated { -(value as i128) } else { value as i128 };
+
+                if value < v.base_type.min() || value > v.base_type.max() {
+                    return out_of_range(&value, &v.base_type.min(), &v.base_type.max());
+                }
+            },
+
+            (Literal::Int(value, _), Type::Simple(SimpleType::Uint(v))) if value as i128 > v.base_type.max() => {
+                return out_of_range(&value, &0, &v.base_type.max());
+            },
+
+            (Literal::Float(value, _), Type::Simple(SimpleType::Fp(v))) if value.is_finite() && v.base_type.round(value).is_infinite() => {
+                return out_of_range(&value, &-v.base_type.max(), &v.base_type.max());
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+
+    fn division_by_zero(span: Span) -> Error {
+        Error::from_span(span, ErrorType::TypeError, "division by zero in constant expression")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ * }, lexer::{ Spanned }, types::{ *, consteval::{ ConstValue } } };
+
+    fn int(value: u64, suffix: Option<IntegerSuffix>) -> Expr {
+        Spanned::new(0, 0, ExprKind::Literal(Literal::Int(value, suffix)))
+    }
+
+    fn u8_type() -> Type {
+        Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint8))
+    }
+
+    #[test]
+    fn out_of_range_literals_are_rejected() {
+        let mut checker = Typechecker::new("main");
+        let error = checker.check_expr(&int(300, None), Some(u8_type())).unwrap_err();
+
+        assert_eq!(error.get_message(), "literal `300` is out of range for `u8`, whose values range from 0 to 255");
+        assert!(checker.check_expr(&int(255, None), Some(u8_type())).is_ok());
+    }
+
+    #[test]
+    fn negated_literals_use_the_signed_range() {
+        let mut checker = Typechecker::new("main");
+        let i8_type = Type::Simple(SimpleType::signed(SignedIntegerTypes::Int8));
+        let negate = |value| Spanned::new(0, 0, ExprKind::Unary(UnaryOp::Negate, Box::new(int(value, None))));
+
+        let min = checker.eval_const(&negate(128), Some(i8_type)).unwrap();
+
+        assert_eq!(min.as_i128(), Some(-128));
+        assert!(checker.check_expr(&negate(129), Some(i8_type)).is_err());
+        assert!(checker.check_expr(&int(128, None), Some(i8_type)).is_err());
+    }
+
+    #[test]
+    fn the_most_negative_i64_is_a_constant() {
+        let mut checker = Typechecker::new("main");
+        let suffix = Some(IntegerSuffix::Signed(SignedIntegerTypes::Int64));
+        let min = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::Negate, Box::new(int(9223372036854775808, suffix))));
+
+        assert!(checker.check_expr(&min, None).is_ok());
+        assert_eq!(checker.eval_const(&min, None).unwrap().as_i128(), Some(i64::MIN as i128));
+    }
+
+    #[test]
+    fn constant_overflow_reports_the_computed_value() {
+        let mut checker = Typechecker::new("main");
+        let suffix = Some(IntegerSuffix::Unsigned(UnsignedIntegerTypes::Uint8));
+        let sum = Spanned::new(0, 0, ExprKind::Binary(Box::new(int(255, suffix)), BinaryOp::Add, Box::new(int(1, None))));
+        let error = checker.eval_const(&sum, None).unwrap_err();
+
+        assert_eq!(error.get_message(), "arithmetic overflow in constant expression: `255u8 + 1u8` evaluates to 256, which does not fit in `u8`");
+    }
+
+    #[test]
+    fn f16_constants_round_at_every_step() {
+        let mut checker = Typechecker::new("main");
+        let float = |text| Spanned::new(0, 0, ExprKind::Literal(Literal::parse_float(text).unwrap()));
+        let sum = Spanned::new(0, 0, ExprKind::Binary(Box::new(float("0.1f16")), BinaryOp::Add, Box::new(float("0.2f16"))));
+
+        match checker.eval_const(&sum, None).unwrap() {
+            ConstValue::Fp(v) => assert_eq!(half::F16::from_f64(v.value).to_bits(), 0x34cc),
+            value => panic!("expected an f16, found `{}`", value),
+        }
+
+        let f16_type = Type::Simple(SimpleType::float(FloatingPointTypes::FP16));
+
+        assert!(checker.check_expr(&float("65519.0"), Some(f16_type)).is_ok());
+        assert!(checker.check_expr(&float("65520.0"), Some(f16_type)).is_err());
+    }
+}
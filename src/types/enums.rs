@@ -0,0 +1,313 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ *, layout::{ Layout } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: &'static str,
+    pub payload: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumType {
+    pub name: UserDefinedType,
+    pub variants: Vec<EnumVariant>,
+}
+
+impl EnumType {
+    pub fn new(name: UserDefinedType) -> Self {
+        Self {
+            name,
+            variants: vec![],
+        }
+    }
+
+    pub fn get_variant(&self, name: &str) -> Option<(usize, &EnumVariant)> {
+        self.variants.iter().enumerate().find(|(_, variant)| variant.name == name)
+    }
+
+    // the smallest unsigned integer able to hold every variant index
+    pub fn tag_type(&self) -> UnsignedIntegerTypes {
+        match self.variants.len() {
+            0 ..= 0x100 => UnsignedIntegerTypes::Uint8,
+            0x101 ..= 0x1_0000 => UnsignedIntegerTypes::Uint16,
+            _ => UnsignedIntegerTypes::Uint32,
+        }
+    }
+}
+
+// an enum is laid out as its tag followed by a payload area shared by all variants;
+// every variant's payload is laid out like a struct starting at `payload_offset`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumLayout {
+    pub layout: Layout,
+    pub tag: UnsignedIntegerTypes,
+    pub payload_offset: u64,
+    pub payload_size: u64,
+    pub field_offsets: Vec<Vec<u64>>,
+}
+
+impl Display for EnumLayout {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} (tag {}, payload at {} of size {})", self.layout, self.tag, self.payload_offset, self.payload_size)
+    }
+}
+
+impl Typechecker {
+    pub fn declare_enums(&mut self, decls: &[EnumDecl]) -> std::result::Result<(), Error> {
+        self.declare_types(&[], decls)
+    }
+
+    pub(super) fn register_enum(&mut self, decl: &EnumDecl) -> std::result::Result<(), Error> {
+        self.check_type_name(decl.name)?;
+
+        if decl.generics.is_empty() {
+            let name = UserDefinedType::new(self.file, decl.name.node);
+
+            self.enums.insert(name, EnumType::new(name));
+        } else {
+            self.enum_templates.insert(decl.name.node, decl.clone());
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn resolve_variants(&mut self, decl: &EnumDecl) -> std::result::Result<(), Error> {
+        let mut variants: Vec<EnumVariant> = Vec::with_capacity(decl.variants.len());
+
+        for variant in &decl.variants {
+            if variants.iter().any(|v| v.name == variant.name.node) {
+                return Err(Error::from_span(variant.name.span, ErrorType::TypeError, &format!("variant `{}` is declared more than once in enum `{}`", variant.name.node, decl.name.node)));
+            }
+
+            let mut payload = Vec::with_capacity(variant.payload.len());
+
+            for ty in &variant.payload {
+                payload.push(self.resolve_type(ty)?);
+            }
+
+            variants.push(EnumVariant {
+                name: variant.name.node,
+                payload,
+            });
+        }
+
+        let name = UserDefinedType::new(self.file, decl.name.node);
+
+        self.enums.get_mut(&name).expect("enum was registered before its variants are resolved").variants = variants;
+
+        Ok(())
+    }
+
+    pub fn enum_type(&self, name: UserDefinedType) -> Option<&EnumType> {
+        self.enums.get(&name)
+    }
+
+    pub fn enum_layout(&self, name: UserDefinedType, span: Span) -> std::result::Result<EnumLayout, Error> {
+        self.enum_layout_inner(name, span, &mut vec![])
+    }
+
+    pub(super) fn enum_layout_inner(&self, name: UserDefinedType, span: Span, visiting: &mut Vec<UserDefinedType>) -> std::result::Result<EnumLayout, Error> {
+        let decl = self.enums.get(&name).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("unknown type `{}`", name))
+        })?;
+
+        if visiting.contains(&name) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("enum `{}` contains itself and has infinite size", name)));
+        }
+
+        visiting.push(name);
+
+        let tag = decl.tag_type();
+        let tag_layout = self.layout_of(Type::Simple(SimpleType::unsigned(tag)), span)?;
+        let mut payload_size = 0;
+        let mut payload_align = 1;
+        let mut variant_offsets = Vec::with_capacity(decl.variants.len());
+
+        for variant in &decl.variants {
+            let mut offsets = Vec::with_capacity(variant.payload.len());
+            let mut offset = 0;
+
+            for ty in &variant.payload {
+                let field = self.layout_of_inner(*ty, span, visiting)?;
+
+                offset = field.align_offset(offset);
+                offsets.push(offset);
+                offset += field.size;
+                payload_align = payload_align.max(field.align);
+            }
+
+            payload_size = payload_size.max(offset);
+            variant_offsets.push(offsets);
+        }
+
+        visiting.pop();
+
+        let payload_offset = Layout::new(payload_size, payload_align).align_offset(tag_layout.size);
+        let align = tag_layout.align.max(payload_align);
+        let size = Layout::new(0, align).align_offset(payload_offset + payload_size);
+
+        // field offsets are reported relative to the start of the enum
+        for offsets in &mut variant_offsets {
+            for offset in offsets.iter_mut() {
+                *offset += payload_offset;
+            }
+        }
+
+        Ok(EnumLayout {
+            layout: Layout::new(size, align),
+            tag,
+            payload_offset,
+            payload_size,
+            field_offsets: variant_offsets,
+        })
+    }
+
+    pub(super) fn check_variant(&mut self, enum_name: &'static str, variant: &'static str, args: &[Expr], expected: Option<Type>, span: Span) -> std::result::Result<Type, Error> {
+        let name = if self.enum_templates.contains_key(enum_name) {
+            self.infer_enum_instance(enum_name, variant, args, expected, span)?
+        } else {
+            self.user_type_named(enum_name)
+        };
+        let payload = match self.enums.get(&name) {
+            Some(decl) => match decl.get_variant(variant) {
+                Some((_, variant)) => variant.payload.clone(),
+                None => return Err(Error::from_span(span, ErrorType::TypeError, &format!("enum `{}` has no variant named `{}`", enum_name, variant))),
+            },
+
+            None => return Err(Error::from_span(span, ErrorType::NameError, &format!("unknown enum `{}`", enum_name)).with_code(codes::UNDEFINED_NAME)),
+        };
+
+        if payload.len() != args.len() {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("variant `{}.{}` takes {} value(s) but {} were given", enum_name, variant, payload.len(), args.len())));
+        }
+
+        for (arg, expected) in args.iter().zip(payload) {
+            let ty = self.check_expr(arg, Some(expected))?;
+
+            self.expect_type(expected, ty, arg.span)?;
+        }
+
+        Ok(Type::Simple(SimpleType::UserType(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, types::{ *, layout::{ Layout } } };
+
+    fn variant(name: &'static str, payload: Vec<TypeExpr>) -> EnumVariantDecl {
+        EnumVariantDecl {
+            name: Spanned::new(0, 0, name),
+            payload,
+        }
+    }
+
+    #[test]
+    fn enum_layout_has_tag_and_payload() {
+        let mut checker = Typechecker::new("main");
+        let shape = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Shape"),
+            generics: vec![],
+            variants: vec![
+                variant("Point", vec![]),
+                variant("Circle", vec![ builtin(SimpleType::float(FloatingPointTypes::FP32)) ]),
+                variant("Rect", vec![ builtin(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)), builtin(SimpleType::float(FloatingPointTypes::FP64)) ]),
+            ],
+        };
+
+        checker.declare_enums(&[ shape ]).unwrap();
+
+        let layout = checker.enum_layout(UserDefinedType::new("main", "Shape"), Span::new(0, 0)).unwrap();
+
+        assert_eq!(layout.tag, UnsignedIntegerTypes::Uint8);
+        assert_eq!(layout.payload_offset, 8);
+        assert_eq!(layout.payload_size, 16);
+        assert_eq!(layout.layout, Layout::new(24, 8));
+        assert_eq!(layout.field_offsets, vec![ vec![], vec![ 8 ], vec![ 8, 16 ] ]);
+    }
+
+    #[test]
+    fn fieldless_enum_is_just_a_tag() {
+        let mut checker = Typechecker::new("main");
+        let color = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Color"),
+            generics: vec![],
+            variants: vec![ variant("Red", vec![]), variant("Green", vec![]) ],
+        };
+
+        checker.declare_enums(&[ color ]).unwrap();
+
+        let ty = Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Color")));
+
+        assert_eq!(checker.layout_of(ty, Span::new(0, 0)).unwrap(), Layout::new(1, 1));
+    }
+
+    #[test]
+    fn variant_payload_is_typechecked() {
+        let mut checker = Typechecker::new("main");
+        let opt = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Opt"),
+            generics: vec![],
+            variants: vec![ variant("None", vec![]), variant("Some", vec![ builtin(SimpleType::signed(SignedIntegerTypes::Int64)) ]) ],
+        };
+
+        checker.declare_enums(&[ opt ]).unwrap();
+
+        let good = Spanned::new(0, 0, ExprKind::Literal(Literal::Int(4, None)));
+        let bad = Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true)));
+
+        assert!(checker.check_variant("Opt", "Some", &[ good ], None, Span::new(0, 0)).is_ok());
+        assert!(checker.check_variant("Opt", "Some", &[ bad ], None, Span::new(0, 0)).is_err());
+        assert!(checker.check_variant("Opt", "Other", &[], None, Span::new(0, 0)).is_err());
+    }
+
+    #[test]
+    fn variant_payloads_coerce_like_arguments() {
+        let mut checker = Typechecker::new("main");
+        let pointer = |is_mutable| Spanned::new(0, 0, TypeExprKind::Pointer(1, is_mutable, Box::new(i32_type())));
+        let slot = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Slot"),
+            generics: vec![],
+            variants: vec![ variant("Empty", vec![]), variant("Full", vec![ pointer(false) ]) ],
+        };
+
+        checker.declare_enums(&[ slot ]).unwrap();
+
+        let mutable = checker.resolve_type(&pointer(true)).unwrap();
+
+        checker.declare_variable("p", mutable);
+
+        // `*mut i32` coerces to the `*i32` payload
+        assert!(checker.check_variant("Slot", "Full", &[ ident("p") ], None, Span::new(0, 0)).is_ok());
+    }
+
+    #[test]
+    fn structs_and_enums_declared_together_may_refer_to_each_other() {
+        let mut checker = Typechecker::new("main");
+        // struct Node { kind: Kind }  enum Kind { Leaf, Branch(*Node) }
+        let node = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Node"),
+            generics: vec![],
+            fields: vec![ StructField { is_pub: true, name: "kind", ty: named("Kind") } ],
+        };
+        let kind = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Kind"),
+            generics: vec![],
+            variants: vec![ variant("Leaf", vec![]), variant("Branch", vec![ Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(named("Node")))) ]) ],
+        };
+
+        checker.declare_types(&[ node ], &[ kind ]).unwrap();
+
+        let node = Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Node")));
+
+        assert_eq!(checker.layout_of(node, Span::new(0, 0)).unwrap(), Layout::new(16, 8));
+    }
+}
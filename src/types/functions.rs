@@ -0,0 +1,492 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ *, generics::{ GenericFunction } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnSignature {
+    pub params: Vec<Type>,
+    pub ret: Type,
+    pub is_variadic: bool,
+    pub convention: CallingConvention,
+}
+
+impl FnSignature {
+    pub fn new(params: Vec<Type>, ret: Type) -> Self {
+        Self {
+            params,
+            ret,
+            is_variadic: false,
+            convention: CallingConvention::Synthium,
+        }
+    }
+
+    pub fn substitute(&self, from: SimpleType, to: Type, functions: &FunctionTypes) -> Option<Self> {
+        let params = self.params.iter().map(|ty| ty.substitute(from, to, functions)).collect::<Option<Vec<Type>>>()?;
+
+        Some(Self {
+            params,
+            ret: self.ret.substitute(from, to, functions)?,
+            is_variadic: self.is_variadic,
+            convention: self.convention,
+        })
+    }
+
+    // the type of a pointer to a function with this signature
+    pub fn fn_type(&self, functions: &FunctionTypes) -> Type {
+        Type::Complex(ComplexType::Function(functions.intern(self.params.clone(), self.ret, self.is_variadic, self.convention)))
+    }
+}
+
+impl From<FunctionType> for FnSignature {
+    fn from(function: FunctionType) -> Self {
+        Self {
+            params: function.params.to_vec(),
+            ret: *function.ret,
+            is_variadic: function.is_variadic,
+            convention: function.convention,
+        }
+    }
+}
+
+impl Display for FnSignature {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let mut params: Vec<String> = self.params.iter().map(|ty| ty.to_string()).collect();
+
+        if self.is_variadic {
+            params.push("...".to_owned());
+        }
+
+        write!(f, "{}fn({}) -> {}", self.convention.prefix(), params.join(", "), self.ret)
+    }
+}
+
+impl Typechecker {
+    pub fn signature_of(&mut self, decl: &FnDecl) -> Checked<FnSignature> {
+        for (index, param) in decl.params.iter().enumerate() {
+            if decl.params[.. index].iter().any(|other| other.name.node == param.name.node) {
+                return Err(Error::from_span(param.name.span, ErrorType::TypeError, &format!("parameter `{}` is declared more than once", param.name.node)));
+            }
+        }
+
+        let params: Vec<&TypeExpr> = decl.params.iter().map(|param| &param.ty).collect();
+
+        self.resolve_signature(&params, decl.ret.as_ref(), CallingConvention::Synthium)
+    }
+
+    // shared by declarations and `fn(...)` type expressions
+    pub fn resolve_signature(&mut self, params: &[&TypeExpr], ret: Option<&TypeExpr>, convention: CallingConvention) -> Checked<FnSignature> {
+        let mut sig = FnSignature::new(Vec::with_capacity(params.len()), Type::Simple(SimpleType::Void));
+
+        sig.convention = convention;
+
+        for (index, param) in params.iter().enumerate() {
+            let ty = self.resolve_type(param)?;
+
+            match ty {
+                Type::Simple(SimpleType::Varargs) if index + 1 == params.len() => sig.is_variadic = true,
+                Type::Simple(SimpleType::Varargs) => return Err(Error::from_span(param.span, ErrorType::TypeError, "`...` must be the last parameter")),
+                Type::Simple(SimpleType::Void) => return Err(Error::from_span(param.span, ErrorType::TypeError, "parameters cannot have type `void`")),
+                _ => sig.params.push(ty),
+            }
+        }
+
+        if let Some(ret) = ret {
+            sig.ret = self.resolve_type(ret)?;
+        }
+
+        Ok(sig)
+    }
+
+    pub fn function_signature(&self, name: &str) -> Option<&FnSignature> {
+        self.functions.get(name)
+    }
+
+    pub fn is_generic_function(&self, name: &str) -> bool {
+        self.generic_functions.contains_key(name)
+    }
+
+    pub fn declare_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        self.register_functions(decls)?;
+        self.check_functions(decls)
+    }
+
+    // a module registers the signatures of all its functions and impls before any body is checked,
+    // so that bodies may call functions and methods declared after them
+    pub fn register_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        for decl in decls {
+            if self.functions.contains_key(decl.name.node) {
+                return Err(Error::from_span(decl.name.span, ErrorType::NameError, &format!("function `{}` is defined more than once", decl.name.node)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            // generic signatures are kept in terms of `SimpleType::Generic` and instantiated per call
+            let sig = self.with_generics(&decl.generics, |this| this.signature_of(decl))?;
+
+            if sig.is_variadic {
+                return Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("function `{}` is variadic, which is only supported for `extern` functions without a body", decl.name.node)));
+            }
+
+            if !decl.generics.is_empty() {
+                let params = self.with_generics(&decl.generics, |this| this.type_params_of(&decl.generics))?;
+
+                self.generic_functions.insert(decl.name.node, GenericFunction {
+                    file: self.file,
+                    decl: decl.clone(),
+                    params,
+                });
+            } else if decl.body.is_some() {
+                self.function_decls.insert(decl.name.node, decl.clone());
+            }
+
+            self.functions.insert(decl.name.node, sig);
+        }
+
+        Ok(())
+    }
+
+    pub fn check_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        for decl in decls {
+            let sig = self.functions[decl.name.node].clone();
+
+            self.with_generics(&decl.generics, |this| this.check_function(decl, &sig))?;
+        }
+
+        Ok(())
+    }
+
+    // `extern` functions use the C calling convention: without a body they are provided by a C library,
+    // with one they are defined here and may be handed to C code as callbacks
+    pub fn declare_extern_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        self.register_extern_functions(decls)?;
+        self.check_extern_functions(decls)
+    }
+
+    pub fn register_extern_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        for decl in decls {
+            if self.functions.contains_key(decl.name.node) {
+                return Err(Error::from_span(decl.name.span, ErrorType::NameError, &format!("function `{}` is defined more than once", decl.name.node)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            if !decl.generics.is_empty() {
+                return Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("`extern` function `{}` cannot be generic", decl.name.node)));
+            }
+
+            let mut sig = self.signature_of(decl)?;
+
+            sig.convention = CallingConvention::C;
+
+            if sig.is_variadic && decl.body.is_some() {
+                return Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("variadic `extern` function `{}` cannot have a body", decl.name.node)));
+            }
+
+            self.functions.insert(decl.name.node, sig);
+        }
+
+        Ok(())
+    }
+
+    pub fn check_extern_functions(&mut self, decls: &[FnDecl]) -> Checked<()> {
+        for decl in decls.iter().filter(|decl| decl.body.is_some()) {
+            let sig = self.functions[decl.name.node].clone();
+
+            self.check_function(decl, &sig)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn check_function(&mut self, decl: &FnDecl, sig: &FnSignature) -> Checked<()> {
+        let Some(body) = &decl.body else {
+            return Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("function `{}` has no body", decl.name.node)));
+        };
+
+        let previous = self.return_type.replace(sig.ret);
+
+        self.push_scope();
+
+        for (param, ty) in decl.params.iter().zip(&sig.params) {
+            self.declare_variable(param.name.node, *ty);
+        }
+
+        let checked = self.check_block(body);
+
+        self.pop_scope();
+        self.return_type = previous;
+        checked?;
+
+        if sig.ret != Type::Simple(SimpleType::Void) && !Self::block_returns(body) {
+            return Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("function `{}` does not return a value on every path", decl.name.node)));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_block(&mut self, block: &Block) -> Checked<()> {
+        self.push_scope();
+
+        let checked = block.iter().try_for_each(|stmt| self.check_stmt(stmt));
+
+        self.pop_scope();
+
+        checked
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt) -> Checked<()> {
+        match &stmt.node {
+            StmtKind::Let(name, is_mutable, ty, value) => {
+                let declared = match ty {
+                    Some(ty) => Some(self.resolve_type(ty)?),
+                    None => None,
+                };
+
+                let actual = self.check_expr(value, declared)?;
+
+                if let (Some(ty), Some(declared)) = (ty, declared) {
+                    if !self.can_coerce(actual, declared) {
+                        return Err(Error::from_span(value.span, ErrorType::TypeError, &format!("expected {}, found `{}`", self.describe_type(ty, declared), actual)));
+                    }
+                }
+
+                if actual == Type::Simple(SimpleType::Void) {
+                    return Err(Error::from_span(value.span, ErrorType::TypeError, &format!("cannot bind `{}` to a value of type `void`", name)));
+                }
+
+                self.declare_binding(name, declared.unwrap_or(actual), *is_mutable);
+
+                Ok(())
+            },
+
+            StmtKind::Assign(place, value) => self.check_assign(place, value),
+
+            StmtKind::Expr(expr) => self.check_expr(expr, None).map(|_| ()),
+
+            StmtKind::Delete(pointer) => self.check_delete(pointer),
+
+            StmtKind::Return(value) => {
+                let expected = self.return_type.ok_or_else(|| {
+                    Error::from_span(stmt.span, ErrorType::TypeError, "`return` outside of a function")
+                })?;
+
+                match value {
+                    Some(value) => {
+                        let actual = self.check_expr(value, Some(expected))?;
+
+                        self.expect_type(expected, actual, value.span)
+                    },
+
+                    None => self.expect_type(expected, Type::Simple(SimpleType::Void), stmt.span),
+                }
+            },
+
+            StmtKind::If(condition, then, otherwise) => {
+                self.check_condition(condition)?;
+                self.check_narrowed_block(then, condition, true)?;
+
+                if let Some(otherwise) = otherwise {
+                    self.check_narrowed_block(otherwise, condition, false)?;
+                }
+
+                self.narrow_after_if(condition, then, otherwise.as_ref());
+
+                Ok(())
+            },
+
+            StmtKind::While(condition, body) => {
+                self.check_condition(condition)?;
+                self.check_narrowed_block(body, condition, true)
+            },
+        }
+    }
+
+    fn check_condition(&mut self, condition: &Expr) -> Checked<()> {
+        let ty = self.check_expr(condition, Some(Type::Simple(SimpleType::Bool)))?;
+
+        self.expect_type(Type::Simple(SimpleType::Bool), ty, condition.span)
+    }
+
+    pub fn expect_type(&self, expected: Type, actual: Type, span: Span) -> Checked<()> {
+        if self.can_coerce(actual, expected) {
+            Ok(())
+        } else {
+            Err(Error::from_span(span, ErrorType::TypeError, &format!("expected `{}`, found `{}`", expected, actual)))
+        }
+    }
+
+    // whether every path through the block ends in a `return`
+    pub fn block_returns(block: &Block) -> bool {
+        block.iter().any(|stmt| match &stmt.node {
+            StmtKind::Return(_) => true,
+            StmtKind::If(_, then, Some(otherwise)) => Self::block_returns(then) && Self::block_returns(otherwise),
+            _ => false,
+        })
+    }
+
+    // calls a named function directly, or anything else of function type through its pointer
+    pub(super) fn check_call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> Checked<Type> {
+        let sig = match &callee.node {
+            ExprKind::Identifier(name) if self.lookup_variable(name).is_none() && self.generic_functions.contains_key(name) => {
+                return self.check_generic_call(name, args, span);
+            },
+
+            ExprKind::Identifier(name) if self.lookup_variable(name).is_none() => self.functions.get(name).cloned().ok_or_else(|| {
+                Error::from_span(callee.span, ErrorType::NameError, &format!("cannot find function `{}` in this scope", name)).with_code(codes::UNDEFINED_NAME)
+            })?,
+
+            _ => match self.check_expr(callee, None)? {
+                Type::Complex(ComplexType::Function(function)) => FnSignature::from(function),
+                ty => return Err(Error::from_span(callee.span, ErrorType::TypeError, &format!("cannot call a value of type `{}`", ty))),
+            },
+        };
+
+        self.check_args(&sig.params, sig.is_variadic, args, span)?;
+
+        Ok(sig.ret)
+    }
+
+    pub(super) fn check_args(&mut self, params: &[Type], is_variadic: bool, args: &[Expr], span: Span) -> Checked<()> {
+        if args.len() < params.len() || (!is_variadic && args.len() > params.len()) {
+            let expected = if is_variadic { format!("at least {}", params.len()) } else { params.len().to_string() };
+
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("expected {} argument(s), found {}", expected, args.len())));
+        }
+
+        for (arg, param) in args.iter().zip(params) {
+            let ty = self.check_expr(arg, Some(*param))?;
+
+            self.expect_type(*param, ty, arg.span)?;
+        }
+
+        // the extra arguments of a variadic call are passed as they are
+        for arg in &args[params.len() ..] {
+            if self.check_expr(arg, None)? == Type::Simple(SimpleType::Void) {
+                return Err(Error::from_span(arg.span, ErrorType::TypeError, "cannot pass a value of type `void` as a variadic argument"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Spanned }, types::{ * } };
+
+    #[test]
+    fn functions_must_return_on_every_path() {
+        let mut checker = Typechecker::new("main");
+        let body = vec![
+            Spanned::new(0, 0, StmtKind::If(
+                Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))),
+                vec![ Spanned::new(0, 0, StmtKind::Return(Some(ident("a")))) ],
+                None,
+            )),
+        ];
+
+        let decl = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "f"),
+            generics: vec![],
+            params: vec![ Param { name: Spanned::new(0, 0, "a"), ty: i32_type() } ],
+            ret: Some(i32_type()),
+            body: Some(body),
+        };
+
+        assert!(checker.declare_functions(&[ decl ]).is_err());
+    }
+
+    #[test]
+    fn call_arguments_are_checked() {
+        let mut checker = Typechecker::new("main");
+        let id = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "id"),
+            generics: vec![],
+            params: vec![ Param { name: Spanned::new(0, 0, "a"), ty: i32_type() } ],
+            ret: Some(i32_type()),
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Return(Some(ident("a")))) ]),
+        };
+
+        checker.declare_functions(&[ id ]).unwrap();
+
+        let good = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("id")), vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Int(1, None))) ]));
+        let bad = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("id")), vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(false))) ]));
+
+        assert_eq!(checker.check_expr(&good, None).unwrap(), Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+        assert!(checker.check_expr(&bad, None).is_err());
+    }
+
+    #[test]
+    fn function_types_are_interned() {
+        let mut checker = Typechecker::new("main");
+        let id = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "id"),
+            generics: vec![],
+            params: vec![ param("a", i32_type()) ],
+            ret: Some(i32_type()),
+            body: Some(vec![ stmt(StmtKind::Return(Some(ident("a")))) ]),
+        };
+
+        checker.declare_functions(&[ id ]).unwrap();
+
+        let (Ok(Type::Complex(ComplexType::Function(first))), Ok(Type::Complex(ComplexType::Function(second)))) = (checker.check_expr(&ident("id"), None), checker.check_expr(&ident("id"), None)) else {
+            panic!("`id` is a function");
+        };
+
+        // every use of `id` shares the same parameter and return types
+        assert!(std::ptr::eq(first.params, second.params));
+        assert!(std::ptr::eq(first.ret, second.ret));
+    }
+
+    fn char_ptr() -> TypeExpr {
+        Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(builtin(SimpleType::Char))))
+    }
+
+    #[test]
+    fn function_types_display_like_signatures() {
+        let mut checker = Typechecker::new("main");
+        let printf = Spanned::new(0, 0, TypeExprKind::Function(vec![ i32_type(), char_ptr(), builtin(SimpleType::Varargs) ], Some(Box::new(i32_type())), CallingConvention::Synthium));
+        let callback = Spanned::new(0, 0, TypeExprKind::Function(vec![], None, CallingConvention::C));
+
+        assert_eq!(checker.resolve_type(&printf).unwrap().to_string(), "fn(i32, *char, ...) -> i32");
+        assert_eq!(checker.resolve_type(&callback).unwrap().to_string(), "extern \"C\" fn() -> void");
+    }
+
+    #[test]
+    fn named_functions_coerce_to_function_pointers() {
+        let mut checker = Typechecker::new("main");
+        let printf = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "printf"),
+            generics: vec![],
+            params: vec![
+                Param { name: Spanned::new(0, 0, "fmt"), ty: char_ptr() },
+                Param { name: Spanned::new(0, 0, "args"), ty: builtin(SimpleType::Varargs) },
+            ],
+            ret: Some(i32_type()),
+            body: None,
+        };
+
+        checker.declare_extern_functions(&[ printf ]).unwrap();
+        let s = checker.resolve_type(&char_ptr()).unwrap();
+
+        checker.declare_variable("s", s);
+
+        let printf_type = checker.check_expr(&ident("printf"), None).unwrap();
+
+        assert_eq!(printf_type.to_string(), "extern \"C\" fn(*char, ...) -> i32");
+
+        checker.declare_variable("f", printf_type);
+
+        let one = Spanned::new(0, 0, ExprKind::Literal(Literal::Int(1, None)));
+        let indirect = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("f")), vec![ ident("s"), one.clone(), one ]));
+
+        assert_eq!(checker.check_expr(&indirect, None).unwrap(), Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+
+        let missing = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("f")), vec![]));
+
+        assert_eq!(checker.check_expr(&missing, None).unwrap_err().get_message(), "expected at least 1 argument(s), found 0");
+    }
+}
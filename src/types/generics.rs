@@ -0,0 +1,568 @@
+use super::{ *, functions::{ FnSignature }, layout::{ StructType }, enums::{ EnumType, EnumVariant }, traits::{ TraitType }, mono::{ Instance } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span, Spanned } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParam {
+    pub name: &'static str,
+    // names of the (instantiated) traits the parameter must implement
+    pub bounds: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericFunction {
+    // the module declaring it, whose names its body is checked against in every instance
+    pub file: &'static str,
+    pub decl: FnDecl,
+    pub params: Vec<TypeParam>,
+}
+
+// the name an instantiated item is registered under, e.g. `Pair<i32, bool>`
+pub fn instance_name(name: &str, args: &[Type]) -> &'static str {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+
+    Box::leak(format!("{}<{}>", name, args.join(", ")).into_boxed_str())
+}
+
+impl Typechecker {
+    // runs `f` with the given type parameters in scope as opaque `SimpleType::Generic`s
+    pub fn with_generics<R>(&mut self, generics: &[GenericParam], f: impl FnOnce(&mut Self) -> Checked<R>) -> Checked<R> {
+        for (index, param) in generics.iter().enumerate() {
+            if generics[.. index].iter().any(|other| other.name.node == param.name.node) {
+                return Err(Error::from_span(param.name.span, ErrorType::TypeError, &format!("type parameter `{}` is declared more than once", param.name.node)));
+            }
+        }
+
+        let bindings: Vec<(&'static str, Type)> = generics.iter().map(|param| (param.name.node, Type::Simple(SimpleType::Generic(param.name.node)))).collect();
+
+        self.with_type_bindings(&bindings, |this| {
+            let params = this.type_params_of(generics)?;
+            let depth = this.bounds.len();
+
+            this.bounds.extend(params.into_iter().map(|param| (param.name, param.bounds)));
+
+            let result = f(this);
+
+            this.bounds.truncate(depth);
+
+            result
+        })
+    }
+
+    // runs `f` with type parameters bound to the given types
+    pub fn with_type_bindings<R>(&mut self, bindings: &[(&'static str, Type)], f: impl FnOnce(&mut Self) -> Checked<R>) -> Checked<R> {
+        let depth = self.bind_type_params(bindings);
+        let result = f(self);
+
+        self.unbind_type_params(depth);
+
+        result
+    }
+
+    // like `with_type_bindings`, for callers that hold the checker across the bound region,
+    // e.g. the compiler lowering an instantiated body; the returned depth is handed back to `unbind_type_params`
+    pub fn bind_type_params(&mut self, bindings: &[(&'static str, Type)]) -> usize {
+        let depth = self.type_params.len();
+
+        self.type_params.extend_from_slice(bindings);
+
+        depth
+    }
+
+    pub fn unbind_type_params(&mut self, depth: usize) {
+        self.type_params.truncate(depth);
+    }
+
+    pub fn lookup_type_param(&self, name: &str) -> Option<Type> {
+        self.type_params.iter().rev().find(|(param, _)| *param == name).map(|(_, ty)| *ty)
+    }
+
+    pub(super) fn type_params_of(&mut self, generics: &[GenericParam]) -> Checked<Vec<TypeParam>> {
+        let mut params = Vec::with_capacity(generics.len());
+
+        for param in generics {
+            let mut bounds = Vec::with_capacity(param.bounds.len());
+
+            for bound in &param.bounds {
+                bounds.push(self.resolve_trait_ref(bound.name, &bound.args)?);
+            }
+
+            params.push(TypeParam {
+                name: param.name.node,
+                bounds,
+            });
+        }
+
+        Ok(params)
+    }
+
+    pub fn resolve_trait_ref(&mut self, name: Spanned<&'static str>, args: &[TypeExpr]) -> Checked<&'static str> {
+        if let Some(template) = self.trait_templates.get(name.node).cloned() {
+            let args = self.resolve_type_args(name, &template.generics, args)?;
+
+            return self.instantiate_trait(&template, args, name.span);
+        }
+
+        if !args.is_empty() {
+            return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("trait `{}` does not take type arguments", name.node)));
+        }
+
+        match self.traits.get_key_value(name.node) {
+            Some((name, _)) => Ok(name),
+            None => Err(Error::from_span(name.span, ErrorType::NameError, &format!("unknown trait `{}`", name.node)).with_code(codes::UNDEFINED_NAME)),
+        }
+    }
+
+    fn resolve_type_args(&mut self, name: Spanned<&'static str>, generics: &[GenericParam], args: &[TypeExpr]) -> Checked<Vec<Type>> {
+        if generics.len() != args.len() {
+            return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("`{}` takes {} type argument(s) but {} were given", name.node, generics.len(), args.len())));
+        }
+
+        args.iter().map(|arg| self.resolve_type(arg)).collect()
+    }
+
+    // resolves `Name<Args...>` to an instance of a generic struct or enum, or `None` if `name` is not generic
+    pub(super) fn resolve_generic_type(&mut self, name: Spanned<&'static str>, args: &[TypeExpr]) -> Checked<Option<Type>> {
+        let generics = match (self.struct_templates.get(name.node), self.enum_templates.get(name.node)) {
+            (Some(template), _) => template.generics.clone(),
+            (_, Some(template)) => template.generics.clone(),
+            _ => return Ok(None),
+        };
+
+        let args = self.resolve_type_args(name, &generics, args)?;
+
+        self.instantiate_user_type(name.node, args, name.span).map(Some)
+    }
+
+    pub fn instantiate_user_type(&mut self, template: &'static str, args: Vec<Type>, span: Span) -> Checked<Type> {
+        let name = UserDefinedType::new(self.file, instance_name(template, &args));
+
+        if self.is_user_type(name) {
+            return Ok(Type::Simple(SimpleType::UserType(name)));
+        }
+
+        let generics = match (self.struct_templates.get(template), self.enum_templates.get(template)) {
+            (Some(decl), _) => decl.generics.clone(),
+            (_, Some(decl)) => decl.generics.clone(),
+            _ => return Err(Error::from_span(span, ErrorType::TypeError, &format!("unknown generic type `{}`", template))),
+        };
+
+        self.check_bounds(template, &generics, &args, span)?;
+
+        let bindings: Vec<(&'static str, Type)> = generics.iter().map(|param| param.name.node).zip(args.iter().copied()).collect();
+
+        self.instantiated.insert(name, (template, args));
+
+        // the instance is registered before its fields are resolved so that it may point to itself
+        if let Some(decl) = self.struct_templates.get(template).cloned() {
+            self.structs.insert(name, StructType::new(name));
+
+            let fields = self.with_type_bindings(&bindings, |this| {
+                decl.fields.iter().map(|field| Ok((field.name, this.resolve_type(&field.ty)?))).collect::<Checked<Vec<(&'static str, Type)>>>()
+            })?;
+
+            let instance = self.structs.get_mut(&name).expect("instance was registered above");
+
+            instance.fields = fields;
+            instance.private_fields = decl.fields.iter().filter(|field| !field.is_pub).map(|field| field.name).collect();
+        } else {
+            let decl = self.enum_templates[template].clone();
+
+            self.enums.insert(name, EnumType::new(name));
+
+            let variants = self.with_type_bindings(&bindings, |this| {
+                decl.variants.iter().map(|variant| {
+                    let payload = variant.payload.iter().map(|ty| this.resolve_type(ty)).collect::<Checked<Vec<Type>>>()?;
+
+                    Ok(EnumVariant {
+                        name: variant.name.node,
+                        payload,
+                    })
+                }).collect::<Checked<Vec<EnumVariant>>>()
+            })?;
+
+            self.enums.get_mut(&name).expect("instance was registered above").variants = variants;
+        }
+
+        Ok(Type::Simple(SimpleType::UserType(name)))
+    }
+
+    // a generic struct or enum is validated once with its parameters left opaque
+    pub(super) fn check_template(&mut self, name: Spanned<&'static str>, generics: &[GenericParam]) -> Checked<()> {
+        let params: Vec<Type> = generics.iter().map(|param| Type::Simple(SimpleType::Generic(param.name.node))).collect();
+
+        self.with_generics(generics, |this| this.instantiate_user_type(name.node, params, name.span)).map(|_| ())
+    }
+
+    // picks the instance of a generic enum a variant constructor builds, from the expected type or else from its payload
+    pub(super) fn infer_enum_instance(&mut self, template: &'static str, variant: &'static str, args: &[Expr], expected: Option<Type>, span: Span) -> Checked<UserDefinedType> {
+        if let Some(Type::Simple(SimpleType::UserType(name))) = expected {
+            if self.instantiated.get(&name).is_some_and(|(other, _)| *other == template) {
+                return Ok(name);
+            }
+        }
+
+        let decl = self.enum_templates[template].clone();
+        let Some(variant_decl) = decl.variants.iter().find(|other| other.name.node == variant) else {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("enum `{}` has no variant named `{}`", template, variant)));
+        };
+
+        if variant_decl.payload.len() != args.len() {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("variant `{}.{}` takes {} value(s) but {} were given", template, variant, variant_decl.payload.len(), args.len())));
+        }
+
+        let payload = self.with_generics(&decl.generics, |this| {
+            variant_decl.payload.iter().map(|ty| this.resolve_type(ty)).collect::<Checked<Vec<Type>>>()
+        })?;
+
+        let mut bindings: Vec<(&'static str, Option<Type>)> = decl.generics.iter().map(|param| (param.name.node, None)).collect();
+
+        for (arg, param) in args.iter().zip(payload) {
+            let ty = self.check_expr(arg, None)?;
+
+            if !self.unify(param, ty, &mut bindings) {
+                return Err(Error::from_span(arg.span, ErrorType::TypeError, &format!("expected `{}`, found `{}`", param, ty)));
+            }
+        }
+
+        let mut type_args = Vec::with_capacity(bindings.len());
+
+        for (param, binding) in bindings {
+            match binding {
+                Some(binding) => type_args.push(binding),
+                None => return Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot infer type parameter `{}` of `{}`, annotate the expected type", param, template))),
+            }
+        }
+
+        match self.instantiate_user_type(template, type_args, span)? {
+            Type::Simple(SimpleType::UserType(name)) => Ok(name),
+            _ => unreachable!("instances of generic enums are user types"),
+        }
+    }
+
+    fn instantiate_trait(&mut self, template: &TraitDecl, args: Vec<Type>, span: Span) -> Checked<&'static str> {
+        let name = instance_name(template.name.node, &args);
+
+        if self.traits.contains_key(name) {
+            return Ok(name);
+        }
+
+        self.check_bounds(template.name.node, &template.generics, &args, span)?;
+
+        let bindings: Vec<(&'static str, Type)> = template.generics.iter().map(|param| param.name.node).zip(args).collect();
+        let previous = self.self_type.replace(Type::Simple(SimpleType::SelfType));
+        let methods = self.with_type_bindings(&bindings, |this| this.trait_methods(template));
+
+        self.self_type = previous;
+
+        self.traits.insert(name, TraitType {
+            name,
+            methods: methods?,
+            decl: template.clone(),
+            bindings,
+        });
+
+        Ok(name)
+    }
+
+    // whether `ty` implements `trait_name`, where a type parameter implements exactly its bounds
+    pub fn satisfies(&self, ty: Type, trait_name: &str) -> bool {
+        match ty {
+            Type::Simple(SimpleType::Generic(param)) => self.bounds_of(param).contains(&trait_name),
+            _ => self.implements(ty, trait_name),
+        }
+    }
+
+    pub fn bounds_of(&self, param: &str) -> Vec<&'static str> {
+        self.bounds.iter().rev().find(|(name, _)| *name == param).map_or_else(Vec::new, |(_, bounds)| bounds.clone())
+    }
+
+    fn check_bounds(&mut self, item: &str, generics: &[GenericParam], args: &[Type], span: Span) -> Checked<()> {
+        let bindings: Vec<(&'static str, Type)> = generics.iter().map(|param| param.name.node).zip(args.iter().copied()).collect();
+        let params = self.with_type_bindings(&bindings, |this| this.type_params_of(generics))?;
+
+        for (param, arg) in params.iter().zip(args) {
+            if let Some(bound) = param.bounds.iter().find(|bound| !self.satisfies(*arg, bound)) {
+                return Err(Error::from_span(span, ErrorType::TypeError, &format!("type `{}` does not implement trait `{}` required by `{}` of `{}`", arg, bound, param.name, item)));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn mentions_generic(&self, ty: Type) -> bool {
+        if let Type::Complex(ComplexType::Function(function)) = ty {
+            return function.params.iter().chain([ function.ret ]).any(|ty| self.mentions_generic(*ty));
+        }
+
+        match Self::base_of(ty) {
+            SimpleType::Generic(_) => true,
+            SimpleType::UserType(name) => self.instantiated.get(&name).is_some_and(|(_, args)| args.iter().any(|arg| self.mentions_generic(*arg))),
+            _ => false,
+        }
+    }
+
+    fn base_of(ty: Type) -> SimpleType {
+        match ty {
+            Type::Simple(simple) => simple,
+            Type::Complex(ComplexType::Pointer(ptr)) => ptr.base_type,
+            Type::Complex(ComplexType::Array(arr)) => arr.base_type,
+            // function types have no single base, callers look into them first
+            Type::Complex(ComplexType::Function(_)) => SimpleType::Void,
+        }
+    }
+
+    // replaces bound type parameters in `ty`, instantiating generic structs and enums as needed
+    pub fn substitute_generics(&mut self, ty: Type, bindings: &[(&'static str, Type)], span: Span) -> Checked<Type> {
+        if let Type::Complex(ComplexType::Function(function)) = ty {
+            let params = function.params.iter().map(|param| self.substitute_generics(*param, bindings, span)).collect::<Checked<Vec<Type>>>()?;
+            let ret = self.substitute_generics(*function.ret, bindings, span)?;
+
+            return Ok(Type::Complex(ComplexType::Function(self.function_types.intern(params, ret, function.is_variadic, function.convention))));
+        }
+
+        let base = Self::base_of(ty);
+        let replacement = match base {
+            SimpleType::Generic(param) => match bindings.iter().find(|(name, _)| *name == param) {
+                Some((_, binding)) => *binding,
+                None => return Ok(ty),
+            },
+
+            SimpleType::UserType(name) => match self.instantiated.get(&name).cloned() {
+                Some((template, args)) => {
+                    let args = args.into_iter().map(|arg| self.substitute_generics(arg, bindings, span)).collect::<Checked<Vec<Type>>>()?;
+
+                    self.instantiate_user_type(template, args, span)?
+                },
+
+                None => return Ok(ty),
+            },
+
+            _ => return Ok(ty),
+        };
+
+        ty.substitute(base, replacement, &self.function_types).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("cannot substitute `{}` into `{}`", replacement, ty))
+        })
+    }
+
+    // infers type parameters by matching a parameter type against the type of its argument
+    fn unify(&self, param: Type, arg: Type, bindings: &mut [(&'static str, Option<Type>)]) -> bool {
+        let mut bind = |name: &str, ty: Type| match bindings.iter_mut().find(|(param, _)| *param == name) {
+            Some((_, Some(bound))) => *bound == ty,
+            Some((_, binding)) => {
+                *binding = Some(ty);
+
+                true
+            },
+
+            None => param == arg,
+        };
+
+        match (param, arg) {
+            (Type::Simple(SimpleType::Generic(name)), _) => bind(name, arg),
+
+            (Type::Complex(ComplexType::Pointer(ptr)), Type::Complex(ComplexType::Pointer(actual))) if actual.references >= ptr.references && (actual.is_mutable || !ptr.is_mutable) => {
+                let inner = match actual.references - ptr.references {
+                    0 => Type::Simple(actual.base_type),
+                    references => Type::Complex(ComplexType::Pointer(Pointer::new(actual.alignment, actual.offset, references, actual.base_type))),
+                };
+
+                match ptr.base_type {
+                    SimpleType::Generic(name) => bind(name, inner),
+                    base => self.unify(Type::Simple(base), inner, bindings),
+                }
+            },
+
+            (Type::Complex(ComplexType::Array(arr)), Type::Complex(ComplexType::Array(actual))) if arr.size == actual.size && arr.is_runtime_sized == actual.is_runtime_sized => {
+                self.unify(Type::Simple(arr.base_type), Type::Simple(actual.base_type), bindings)
+            },
+
+            (Type::Complex(ComplexType::Function(expected)), Type::Complex(ComplexType::Function(actual))) => {
+                let compatible = expected.params.len() == actual.params.len() && expected.is_variadic == actual.is_variadic && expected.convention == actual.convention;
+
+                compatible && expected.params.iter().zip(actual.params).chain([ (expected.ret, actual.ret) ]).all(|(param, arg)| self.unify(*param, *arg, bindings))
+            },
+
+            (Type::Simple(SimpleType::UserType(expected)), Type::Simple(SimpleType::UserType(actual))) => {
+                match (self.instantiated.get(&expected), self.instantiated.get(&actual)) {
+                    (Some((template, params)), Some((other, args))) if template == other => {
+                        params.iter().zip(args).all(|(param, arg)| self.unify(*param, *arg, bindings))
+                    },
+
+                    _ => expected == actual,
+                }
+            },
+
+            _ => self.can_coerce(arg, param),
+        }
+    }
+
+    pub(super) fn check_generic_call(&mut self, name: &'static str, args: &[Expr], span: Span) -> Checked<Type> {
+        let (instance, sig) = self.infer_generic_call(name, args, span)?;
+
+        self.instances.push(instance);
+
+        Ok(sig.ret)
+    }
+
+    // the instance a call to a generic function needs, inferred from its arguments, and the signature of that instance
+    pub fn infer_generic_call(&mut self, name: &'static str, args: &[Expr], span: Span) -> Checked<(Instance, FnSignature)> {
+        let generic = self.generic_functions[name].clone();
+        let sig: FnSignature = self.functions[name].clone();
+
+        if sig.params.len() != args.len() {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("expected {} argument(s), found {}", sig.params.len(), args.len())));
+        }
+
+        let mut bindings: Vec<(&'static str, Option<Type>)> = generic.params.iter().map(|param| (param.name, None)).collect();
+
+        // unsuffixed literals take their type from the arguments that bind the parameters they use,
+        // so that `max(1, x)` works like `max(x, 1)`
+        let mut ordered: Vec<(&Expr, Type)> = args.iter().zip(sig.params.iter().copied()).collect();
+
+        ordered.sort_by_key(|(arg, _)| Self::is_untyped_literal(arg));
+
+        for (arg, param) in ordered {
+            // parameters bound by earlier arguments are known by now
+            let bound: Vec<(&'static str, Type)> = bindings.iter().filter_map(|(name, binding)| binding.map(|ty| (*name, ty))).collect();
+            let param = self.substitute_generics(param, &bound, arg.span)?;
+
+            if !self.mentions_generic(param) {
+                let ty = self.check_expr(arg, Some(param))?;
+
+                self.expect_type(param, ty, arg.span)?;
+
+                continue;
+            }
+
+            let ty = self.check_expr(arg, None)?;
+
+            if !self.unify(param, ty, &mut bindings) {
+                return Err(Error::from_span(arg.span, ErrorType::TypeError, &format!("expected `{}`, found `{}`", param, ty)));
+            }
+        }
+
+        let mut type_args = Vec::with_capacity(bindings.len());
+
+        for (param, binding) in &bindings {
+            match binding {
+                Some(binding) => type_args.push(*binding),
+                None => return Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot infer type parameter `{}` of `{}`", param, name))),
+            }
+        }
+
+        for (param, arg) in generic.params.iter().zip(&type_args) {
+            if let Some(bound) = param.bounds.iter().find(|bound| !self.satisfies(*arg, bound)) {
+                return Err(Error::from_span(span, ErrorType::TypeError, &format!("type `{}` does not implement trait `{}` required by `{}` of `{}`", arg, bound, param.name, name)));
+            }
+        }
+
+        let resolved: Vec<(&'static str, Type)> = generic.params.iter().map(|param| param.name).zip(type_args.iter().copied()).collect();
+        let params = sig.params.iter().map(|param| self.substitute_generics(*param, &resolved, span)).collect::<Checked<Vec<Type>>>()?;
+        let ret = self.substitute_generics(sig.ret, &resolved, span)?;
+
+        Ok((Instance::new(name, type_args), FnSignature { params, ret, ..sig }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, types::{ *, layout::{ Layout } } };
+
+    fn generic(name: &'static str, bounds: Vec<&'static str>) -> GenericParam {
+        GenericParam {
+            name: Spanned::new(0, 0, name),
+            bounds: bounds.into_iter().map(|bound| TraitRef { name: Spanned::new(0, 0, bound), args: vec![] }).collect(),
+        }
+    }
+
+    // struct Pair<A, B> { first: A, second: B }
+    fn pair() -> StructDecl {
+        StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Pair"),
+            generics: vec![ generic("A", vec![]), generic("B", vec![]) ],
+            fields: vec![
+                StructField { is_pub: false, name: "first", ty: named("A") },
+                StructField { is_pub: false, name: "second", ty: named("B") },
+            ],
+        }
+    }
+
+    // fn first<T>(a: T, b: T) -> T { return a; }
+    fn first(bounds: Vec<&'static str>) -> FnDecl {
+        FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "first"),
+            generics: vec![ generic("T", bounds) ],
+            params: vec![
+                Param { name: Spanned::new(0, 0, "a"), ty: named("T") },
+                Param { name: Spanned::new(0, 0, "b"), ty: named("T") },
+            ],
+            ret: Some(named("T")),
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Return(Some(ident("a")))) ]),
+        }
+    }
+
+    #[test]
+    fn generic_structs_are_instantiated_per_argument_list() {
+        let mut checker = Typechecker::new("main");
+
+        checker.declare_structs(&[ pair() ]).unwrap();
+
+        let ty = checker.resolve_type(&instance("Pair", vec![ builtin(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)), builtin(SimpleType::float(FloatingPointTypes::FP64)) ])).unwrap();
+
+        assert_eq!(ty.to_string(), "main.Pair<u8, f64>");
+        assert_eq!(checker.layout_of(ty, Span::new(0, 0)).unwrap(), Layout::new(16, 8));
+        assert!(checker.resolve_type(&instance("Pair", vec![ builtin(SimpleType::Bool) ])).is_err());
+    }
+
+    #[test]
+    fn type_arguments_are_inferred_at_call_sites() {
+        let mut checker = Typechecker::new("main");
+
+        checker.declare_functions(&[ first(vec![]) ]).unwrap();
+        checker.declare_variable("x", Type::Simple(SimpleType::Char));
+        checker.declare_variable("y", Type::Simple(SimpleType::Bool));
+
+        let good = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("first")), vec![ ident("x"), ident("x") ]));
+        let bad = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("first")), vec![ ident("x"), ident("y") ]));
+
+        assert_eq!(checker.check_expr(&good, None).unwrap(), Type::Simple(SimpleType::Char));
+        assert!(checker.check_expr(&bad, None).is_err());
+    }
+
+    #[test]
+    fn literals_take_the_type_bound_by_the_other_arguments() {
+        let mut checker = Typechecker::new("main");
+        let i64_type = Type::Simple(SimpleType::signed(SignedIntegerTypes::Int64));
+
+        checker.declare_functions(&[ first(vec![]) ]).unwrap();
+        checker.declare_variable("x", i64_type);
+
+        assert_eq!(checker.check_expr(&call("first", vec![ ident("x"), int(1) ]), None).unwrap(), i64_type);
+        assert_eq!(checker.check_expr(&call("first", vec![ int(1), ident("x") ]), None).unwrap(), i64_type);
+    }
+
+    #[test]
+    fn unsatisfied_bounds_are_rejected() {
+        let mut checker = Typechecker::new("main");
+        let show = TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Show"),
+            generics: vec![],
+            methods: vec![],
+        };
+
+        checker.declare_traits(&[ show ]).unwrap();
+        checker.declare_functions(&[ first(vec![ "Show" ]) ]).unwrap();
+        checker.declare_variable("x", Type::Simple(SimpleType::Char));
+
+        let call = Spanned::new(0, 0, ExprKind::Call(Box::new(ident("first")), vec![ ident("x"), ident("x") ]));
+        let err = checker.check_expr(&call, None).unwrap_err();
+
+        assert_eq!(err.get_message(), "type `char` does not implement trait `Show` required by `T` of `first`");
+    }
+}
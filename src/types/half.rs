@@ -0,0 +1,291 @@
+use std::{ fmt::{ Display, Formatter, Result }, cmp::{ Ordering }, num::{ ParseFloatError }, str::{ FromStr }, ops::{ Add, Sub, Mul, Div, Rem, Neg } };
+
+// an IEEE 754 binary16 value: 1 sign bit, 5 exponent bits and 10 mantissa bits
+//
+// arithmetic is carried out in f64 and rounded back once; binary64 has more than twice
+// the precision of binary16 plus two bits, so the single rounding is exact for + - * / and %
+#[derive(Debug, Copy, Clone)]
+pub struct F16(u16);
+
+impl F16 {
+    pub const ZERO: Self = Self(0x0000);
+    pub const ONE: Self = Self(0x3c00);
+    pub const MAX: Self = Self(0x7bff);
+    pub const MIN: Self = Self(0xfbff);
+    pub const MIN_POSITIVE: Self = Self(0x0400);
+    pub const MIN_POSITIVE_SUBNORMAL: Self = Self(0x0001);
+    pub const EPSILON: Self = Self(0x1400);
+    pub const INFINITY: Self = Self(0x7c00);
+    pub const NEG_INFINITY: Self = Self(0xfc00);
+    pub const NAN: Self = Self(0x7e00);
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.0 & 0x7c00 == 0x7c00 && self.0 & 0x03ff != 0
+    }
+
+    pub fn is_infinite(self) -> bool {
+        self.0 & 0x7fff == 0x7c00
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.0 & 0x7c00 != 0x7c00
+    }
+
+    pub fn is_sign_negative(self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+
+    // rounds to the nearest binary16 value, ties to even
+    pub fn from_f64(value: f64) -> Self {
+        match Self::truncate(value) {
+            Some((bits, Ordering::Greater)) => Self(bits + 1),
+            Some((bits, Ordering::Equal)) => Self(bits + (bits & 1)),
+            Some((bits, Ordering::Less)) => Self(bits),
+            None => Self::special(value),
+        }
+    }
+
+    // widening to f64 is exact, so this rounds only once
+    pub fn from_f32(value: f32) -> Self {
+        Self::from_f64(value as f64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        let sign = if self.is_sign_negative() { -1.0 } else { 1.0 };
+        let exponent = ((self.0 >> 10) & 0x1f) as i32;
+        let mantissa = (self.0 & 0x03ff) as f64;
+
+        match exponent {
+            0 => sign * mantissa * 2f64.powi(-24),
+            0x1f if mantissa == 0.0 => sign * f64::INFINITY,
+            0x1f => f64::from_bits(((self.0 as u64 & 0x8000) << 48) | 0x7ff8_0000_0000_0000 | ((self.0 as u64 & 0x03ff) << 42)),
+            _ => sign * (1024.0 + mantissa) * 2f64.powi(exponent - 25),
+        }
+    }
+
+    // every binary16 value is exactly representable as an f32
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    // the bits of `value` with its magnitude truncated to binary16, and how the dropped part compares to half an ulp;
+    // `None` for infinities and NaNs
+    fn truncate(value: f64) -> Option<(u16, Ordering)> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        let bits = value.to_bits();
+        let sign = ((bits >> 48) & 0x8000) as u16;
+        let exponent = ((bits >> 52) & 0x7ff) as i32 - 1023;
+        let significand = (bits & 0x000f_ffff_ffff_ffff) | if exponent > -1023 { 1 << 52 } else { 0 };
+
+        if exponent > 15 {
+            return Some((sign | 0x7bff, Ordering::Greater));
+        }
+
+        // normal results keep 11 significant bits; subnormal ones lose one more per step below 2^-14.
+        // the implicit bit of a normal result carries into the exponent field, so rounding up past
+        // the largest mantissa moves to the next exponent and past 65504 to infinity
+        let (shift, base) = if exponent >= -14 {
+            (42, ((exponent + 14) as u64) << 10)
+        } else {
+            ((28 - exponent) as u32, 0)
+        };
+
+        if shift >= 64 {
+            return Some((sign, Ordering::Less));
+        }
+
+        let truncated = (base + (significand >> shift)) as u16;
+        let dropped = significand & ((1 << shift) - 1);
+
+        Some((sign | truncated, dropped.cmp(&(1 << (shift - 1)))))
+    }
+
+    fn special(value: f64) -> Self {
+        let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+
+        if value.is_nan() {
+            // keep the top of the payload so that NaNs stay NaNs
+            Self(sign | 0x7e00 | ((value.to_bits() >> 42) & 0x03ff) as u16)
+        } else {
+            Self(sign | 0x7c00)
+        }
+    }
+}
+
+impl From<F16> for f64 {
+    fn from(value: F16) -> Self {
+        value.to_f64()
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(value: F16) -> Self {
+        value.to_f32()
+    }
+}
+
+impl PartialEq for F16 {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_f64() == other.to_f64()
+    }
+}
+
+impl PartialOrd for F16 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f64().partial_cmp(&other.to_f64())
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for F16 {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                Self::from_f64(self.to_f64() $op rhs.to_f64())
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, +);
+impl_binary_op!(Sub, sub, -);
+impl_binary_op!(Mul, mul, *);
+impl_binary_op!(Div, div, /);
+impl_binary_op!(Rem, rem, %);
+
+impl Neg for F16 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0 ^ 0x8000)
+    }
+}
+
+impl Display for F16 {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl FromStr for F16 {
+    type Err = ParseFloatError;
+
+    // parsing to f64 rounds once already; when that lands exactly halfway between two binary16
+    // values the decimal text decides which way to go instead of the tie-breaking rule
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let value: f64 = text.parse()?;
+
+        match Self::truncate(value) {
+            Some((bits, Ordering::Equal)) => match compare_decimal(text.trim_start_matches(['+', '-']), &format!("{:.60e}", value.abs())) {
+                Ordering::Greater => Ok(Self(bits + 1)),
+                Ordering::Less => Ok(Self(bits)),
+                Ordering::Equal => Ok(Self(bits + (bits & 1))),
+            },
+
+            _ => Ok(Self::from_f64(value)),
+        }
+    }
+}
+
+// compares two unsigned decimal numbers such as `1.5e3` without rounding either of them
+fn compare_decimal(a: &str, b: &str) -> Ordering {
+    let (a_digits, a_exponent) = normalize_decimal(a);
+    let (b_digits, b_exponent) = normalize_decimal(b);
+
+    match (a_digits.is_empty(), b_digits.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        // with leading and trailing zeros gone, the digits compare like strings
+        _ => a_exponent.cmp(&b_exponent).then_with(|| a_digits.cmp(&b_digits)),
+    }
+}
+
+// the significant digits `d` and exponent `e` such that the number is `0.d * 10^e`
+fn normalize_decimal(text: &str) -> (String, i64) {
+    let (mantissa, exponent) = text.split_once(['e', 'E']).unwrap_or((text, "0"));
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer, fraction);
+    let leading = digits.len() - digits.trim_start_matches('0').len();
+    let exponent = exponent.parse::<i64>().unwrap_or(0) + integer.len() as i64 - leading as i64;
+
+    (digits.trim_matches('0').to_string(), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    #[test]
+    fn conversions_match_known_vectors() {
+        let vectors: [(f64, u16); 16] = [
+            (1.0, 0x3c00),
+            (-2.0, 0xc000),
+            (0.5, 0x3800),
+            (1.0 / 3.0, 0x3555),
+            (0.1, 0x2e66),
+            (65504.0, 0x7bff),
+            (65519.99, 0x7bff),
+            (65520.0, 0x7c00),
+            (1e10, 0x7c00),
+            (6.103515625e-5, 0x0400),
+            (2f64.powi(-24), 0x0001),
+            (2f64.powi(-25), 0x0000),
+            (1.5 * 2f64.powi(-25), 0x0001),
+            (1.0 + 2f64.powi(-11), 0x3c00),
+            (1.0 + 3.0 * 2f64.powi(-11), 0x3c02),
+            (-0.0, 0x8000),
+        ];
+
+        for (value, bits) in vectors {
+            assert_eq!(F16::from_f64(value).to_bits(), bits, "{}", value);
+        }
+
+        assert_eq!(F16::from_bits(0x3555).to_f64(), 0.333251953125);
+        assert_eq!(F16::from_bits(0x0001).to_f64(), 2f64.powi(-24));
+        assert_eq!(F16::from_bits(0xfbff).to_f32(), -65504.0);
+        assert_eq!(F16::from_f32(1.5).to_bits(), 0x3e00);
+        assert!(F16::from_f64(f64::NAN).is_nan());
+        assert!(F16::from_bits(0x7c01).to_f64().is_nan());
+    }
+
+    #[test]
+    fn arithmetic_rounds_every_result() {
+        let a = F16::from_f64(0.1);
+        let b = F16::from_f64(0.2);
+
+        // 0.0999755859375 + 0.199951171875 lies exactly between two binary16 values
+        assert_eq!((a + b).to_bits(), 0x34cc);
+        assert_eq!((F16::ONE + F16::from_bits(0x1000)).to_bits(), 0x3c00);
+        assert_eq!((F16::MAX + F16::MAX).to_bits(), 0x7c00);
+        assert_eq!((F16::ONE / F16::from_f64(3.0)).to_bits(), 0x3555);
+        assert_eq!((F16::MIN_POSITIVE_SUBNORMAL * F16::from_f64(0.5)).to_bits(), 0x0000);
+        assert_eq!((-F16::ONE).to_bits(), 0xbc00);
+        assert!((F16::ZERO / F16::ZERO).is_nan());
+    }
+
+    #[test]
+    fn parsing_rounds_from_the_decimal_text() {
+        let parse = |text: &str| text.parse::<F16>().unwrap().to_bits();
+
+        assert_eq!(parse("1.00048828125"), 0x3c00);
+        // indistinguishable from the tie above as an f64, but just past it
+        assert_eq!(parse("1.000488281250000000000001"), 0x3c01);
+        assert_eq!(parse("65519.999999999999999"), 0x7bff);
+        assert_eq!(parse("65520"), 0x7c00);
+        assert_eq!(parse("2.98023223876953125e-8"), 0x0000);
+        assert_eq!(parse("-0.1"), 0xae66);
+    }
+}
@@ -0,0 +1,217 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ * };
+use crate::{ error::{ * }, lexer::{ Span } };
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Layout {
+    pub size: u64,
+    pub align: u64,
+}
+
+impl Layout {
+    pub fn new(size: u64, align: u64) -> Self {
+        Self {
+            size,
+            align,
+        }
+    }
+
+    // rounds `offset` up to the next multiple of this layout's alignment
+    pub fn align_offset(&self, offset: u64) -> u64 {
+        offset.div_ceil(self.align) * self.align
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "size {}, align {}", self.size, self.align)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructType {
+    pub name: UserDefinedType,
+    pub fields: Vec<(&'static str, Type)>,
+    // fields without `pub`, which only the declaring module may access
+    pub private_fields: Vec<&'static str>,
+}
+
+impl StructType {
+    pub fn new(name: UserDefinedType) -> Self {
+        Self {
+            name,
+            fields: vec![],
+            private_fields: vec![],
+        }
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<(usize, Type)> {
+        self.fields.iter().enumerate().find(|(_, (field, _))| *field == name).map(|(index, (_, ty))| (index, *ty))
+    }
+}
+
+impl Typechecker {
+    pub fn struct_type(&self, name: UserDefinedType) -> Option<&StructType> {
+        self.structs.get(&name)
+    }
+
+    pub fn layout_of(&self, ty: Type, span: Span) -> std::result::Result<Layout, Error> {
+        self.layout_of_inner(ty, span, &mut vec![])
+    }
+
+    // computes the layout of a struct together with the offset of every field, in declaration order
+    pub fn struct_layout(&self, name: UserDefinedType, span: Span) -> std::result::Result<(Layout, Vec<u64>), Error> {
+        self.struct_layout_inner(name, span, &mut vec![])
+    }
+
+    pub(super) fn layout_of_inner(&self, ty: Type, span: Span, visiting: &mut Vec<UserDefinedType>) -> std::result::Result<Layout, Error> {
+        match ty {
+            Type::Simple(simple) => self.simple_layout(simple, span, visiting),
+
+            // a pointer to a trait object also carries the address of its vtable
+            Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::TraitObject(_), .. })) => Ok(Layout::new(16, 8)),
+
+            // as does a closure with the address of its environment
+            Type::Complex(ComplexType::Function(FunctionType { convention: CallingConvention::Closure, .. })) => Ok(Layout::new(16, 8)),
+
+            Type::Complex(ComplexType::Pointer(_)) | Type::Complex(ComplexType::Function(_)) => Ok(Layout::new(8, 8)),
+
+            Type::Complex(ComplexType::Array(arr)) => {
+                if arr.is_runtime_sized {
+                    return Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot compute the size of runtime sized array `{}`", arr)));
+                }
+
+                let element = self.simple_layout(arr.base_type, span, visiting)?;
+                let size = element.size.checked_mul(arr.size as u64).ok_or_else(|| {
+                    Error::from_span(span, ErrorType::TypeError, &format!("array `{}` is too large", arr))
+                })?;
+
+                Ok(Layout::new(size, element.align))
+            },
+        }
+    }
+
+    fn simple_layout(&self, ty: SimpleType, span: Span, visiting: &mut Vec<UserDefinedType>) -> std::result::Result<Layout, Error> {
+        match ty {
+            SimpleType::Int(v) => match v.base_type {
+                SignedIntegerTypes::Int8 => Ok(Layout::new(1, 1)),
+                SignedIntegerTypes::Int16 => Ok(Layout::new(2, 2)),
+                SignedIntegerTypes::Int32 => Ok(Layout::new(4, 4)),
+                SignedIntegerTypes::Int64 => Ok(Layout::new(8, 8)),
+            },
+
+            SimpleType::Uint(v) => match v.base_type {
+                UnsignedIntegerTypes::Uint8 => Ok(Layout::new(1, 1)),
+                UnsignedIntegerTypes::Uint16 => Ok(Layout::new(2, 2)),
+                UnsignedIntegerTypes::Uint32 => Ok(Layout::new(4, 4)),
+                UnsignedIntegerTypes::Uint64 => Ok(Layout::new(8, 8)),
+            },
+
+            SimpleType::Fp(v) => match v.base_type {
+                FloatingPointTypes::FP16 => Ok(Layout::new(2, 2)),
+                FloatingPointTypes::FP32 => Ok(Layout::new(4, 4)),
+                FloatingPointTypes::FP64 => Ok(Layout::new(8, 8)),
+            },
+
+            // a char holds any unicode scalar value
+            SimpleType::Char => Ok(Layout::new(4, 4)),
+
+            SimpleType::Bool => Ok(Layout::new(1, 1)),
+
+            // a string is a pointer to its bytes followed by their length
+            SimpleType::String => Ok(Layout::new(16, 8)),
+
+            SimpleType::Void => Err(Error::from_span(span, ErrorType::TypeError, "type `void` has no size")),
+
+            SimpleType::Varargs => Err(Error::from_span(span, ErrorType::TypeError, "type `...` has no size")),
+
+            SimpleType::SelfType => Err(Error::from_span(span, ErrorType::TypeError, "the size of `Self` is not known inside a trait")),
+
+            SimpleType::Generic(name) => Err(Error::from_span(span, ErrorType::TypeError, &format!("the size of type parameter `{}` is not known until it is instantiated", name))),
+
+            SimpleType::TraitObject(name) => Err(Error::from_span(span, ErrorType::TypeError, &format!("trait object `dyn {}` has no size, it must be used behind a pointer", name))),
+
+            SimpleType::UserType(name) if self.enums.contains_key(&name) => Ok(self.enum_layout_inner(name, span, visiting)?.layout),
+
+            SimpleType::UserType(name) => Ok(self.struct_layout_inner(name, span, visiting)?.0),
+        }
+    }
+
+    fn struct_layout_inner(&self, name: UserDefinedType, span: Span, visiting: &mut Vec<UserDefinedType>) -> std::result::Result<(Layout, Vec<u64>), Error> {
+        let decl = self.structs.get(&name).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("unknown type `{}`", name))
+        })?;
+
+        if visiting.contains(&name) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("struct `{}` contains itself and has infinite size", name)));
+        }
+
+        visiting.push(name);
+
+        let mut offsets = Vec::with_capacity(decl.fields.len());
+        let mut offset = 0;
+        let mut align = 1;
+
+        for (_, ty) in &decl.fields {
+            let field = self.layout_of_inner(*ty, span, visiting)?;
+
+            offset = field.align_offset(offset);
+            offsets.push(offset);
+            offset += field.size;
+            align = align.max(field.align);
+        }
+
+        visiting.pop();
+
+        Ok((Layout::new(offset.div_ceil(align) * align, align), offsets))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ types::{ *, layout::{ * } }, lexer::{ Span } };
+
+    fn span() -> Span {
+        Span::new(0, 0)
+    }
+
+    #[test]
+    fn struct_fields_are_padded() {
+        let mut checker = Typechecker::new("main");
+        let name = UserDefinedType::new("main", "Foo");
+        let mut foo = StructType::new(name);
+
+        foo.fields.push(("a", Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint8))));
+        foo.fields.push(("b", Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32))));
+        foo.fields.push(("c", Type::Simple(SimpleType::Bool)));
+        checker.structs.insert(name, foo);
+
+        let (layout, offsets) = checker.struct_layout(name, span()).unwrap();
+
+        assert_eq!(layout, Layout::new(12, 4));
+        assert_eq!(offsets, vec![ 0, 4, 8 ]);
+    }
+
+    #[test]
+    fn recursive_struct_has_no_size() {
+        let mut checker = Typechecker::new("main");
+        let name = UserDefinedType::new("main", "Node");
+        let mut node = StructType::new(name);
+
+        node.fields.push(("next", Type::Simple(SimpleType::UserType(name))));
+        checker.structs.insert(name, node);
+
+        assert!(checker.layout_of(Type::Simple(SimpleType::UserType(name)), span()).is_err());
+    }
+
+    #[test]
+    fn unsized_types_have_no_layout() {
+        let checker = Typechecker::new("main");
+        let runtime_arr = Type::Complex(ComplexType::Array(Array::new(0, true, SimpleType::Char)));
+
+        assert!(checker.layout_of(runtime_arr, span()).is_err());
+        assert!(checker.layout_of(Type::Simple(SimpleType::Void), span()).is_err());
+        assert!(checker.layout_of(Type::Simple(SimpleType::Varargs), span()).is_err());
+    }
+}
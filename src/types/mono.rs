@@ -0,0 +1,158 @@
+use std::{ collections::{ VecDeque } };
+
+use super::{ *, functions::{ FnSignature } };
+use crate::{ ast::{ FnDecl }, error::{ * } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+// how many generic calls deep an instantiation may be before it is assumed to never end,
+// e.g. `fn f<T>(x: T) { f(&x); }` which would need `f<*T>`, `f<**T>` and so on
+pub const INSTANTIATION_DEPTH_LIMIT: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    pub function: &'static str,
+    pub type_args: Vec<Type>,
+}
+
+impl Instance {
+    pub fn new(function: &'static str, type_args: Vec<Type>) -> Self {
+        Self {
+            function,
+            type_args,
+        }
+    }
+
+    // the name under which the instantiated body is emitted, e.g. `max<i32>`
+    pub fn symbol(&self) -> String {
+        let args: Vec<String> = self.type_args.iter().map(|arg| arg.to_string()).collect();
+
+        format!("{}<{}>", self.function, args.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonoFunction {
+    pub symbol: String,
+    pub file: &'static str,
+    pub decl: FnDecl,
+    pub type_args: Vec<(&'static str, Type)>,
+    pub sig: FnSignature,
+}
+
+impl Typechecker {
+    pub fn is_concrete(&self, instance: &Instance) -> bool {
+        !instance.type_args.iter().any(|arg| self.mentions_generic(*arg))
+    }
+
+    // checks every generic function once per distinct list of concrete type arguments it is called with,
+    // following the calls made by the instantiated bodies themselves
+    pub fn monomorphize(&mut self) -> Checked<Vec<MonoFunction>> {
+        let mut queue: VecDeque<(Instance, usize)> = self.instances.iter().filter(|instance| self.is_concrete(instance)).map(|instance| (instance.clone(), 0)).collect();
+        let mut functions: Vec<MonoFunction> = vec![];
+
+        while let Some((instance, depth)) = queue.pop_front() {
+            let symbol = instance.symbol();
+
+            if functions.iter().any(|function| function.symbol == symbol) {
+                continue;
+            }
+
+            let generic = self.generic_functions[instance.function].clone();
+
+            if depth > INSTANTIATION_DEPTH_LIMIT {
+                return Err(Error::from_span(generic.decl.name.span, ErrorType::TypeError, &format!("reached the instantiation depth limit of {} while instantiating `{}`", INSTANTIATION_DEPTH_LIMIT, symbol)));
+            }
+
+            let type_args: Vec<(&'static str, Type)> = generic.params.iter().map(|param| param.name).zip(instance.type_args.iter().copied()).collect();
+            let seen = self.instances.len();
+            let caller = self.switch_file(generic.file);
+            let sig = self.with_type_bindings(&type_args, |this| {
+                let sig = this.signature_of(&generic.decl)?;
+
+                this.check_function(&generic.decl, &sig)?;
+
+                Ok(sig)
+            });
+
+            self.switch_file(caller);
+
+            let sig = sig?;
+
+            let requested: Vec<Instance> = self.instances[seen ..].iter().filter(|instance| self.is_concrete(instance)).cloned().collect();
+
+            queue.extend(requested.into_iter().map(|instance| (instance, depth + 1)));
+            functions.push(MonoFunction {
+                symbol,
+                file: generic.file,
+                decl: generic.decl,
+                type_args,
+                sig,
+            });
+        }
+
+        Ok(functions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Spanned }, types::{ * } };
+
+    fn generic_fn(name: &'static str, ty: TypeExpr, body: Expr) -> FnDecl {
+        FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, name),
+            generics: vec![ GenericParam { name: Spanned::new(0, 0, "T"), bounds: vec![] } ],
+            params: vec![ Param { name: Spanned::new(0, 0, "x"), ty } ],
+            ret: None,
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Expr(body)) ]),
+        }
+    }
+
+    #[test]
+    fn instances_are_collected_transitively() {
+        let mut checker = Typechecker::new("main");
+        // fn inner<T>(x: T) { x; }  fn outer<T>(x: T) { inner(x); }  fn main() { outer(true); outer('a'); outer(true); }
+        let inner = generic_fn("inner", named("T"), ident("x"));
+        let outer = generic_fn("outer", named("T"), call("inner", vec![ ident("x") ]));
+        let main = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "main"),
+            generics: vec![],
+            params: vec![],
+            ret: None,
+            body: Some(vec![
+                Spanned::new(0, 0, StmtKind::Expr(call("outer", vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))) ]))),
+                Spanned::new(0, 0, StmtKind::Expr(call("outer", vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Char('a'))) ]))),
+                Spanned::new(0, 0, StmtKind::Expr(call("outer", vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))) ]))),
+            ]),
+        };
+
+        checker.declare_functions(&[ inner, outer, main ]).unwrap();
+
+        let symbols: Vec<String> = checker.monomorphize().unwrap().into_iter().map(|function| function.symbol).collect();
+
+        assert_eq!(symbols, vec![ "outer<bool>", "outer<char>", "inner<bool>", "inner<char>" ]);
+    }
+
+    #[test]
+    fn unbounded_instantiation_is_rejected() {
+        let mut checker = Typechecker::new("main");
+        // fn grow<T>(x: T) { grow(&x); }  fn main() { grow(true); }
+        let address = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::AddressOf, Box::new(ident("x"))));
+        let grow = generic_fn("grow", named("T"), call("grow", vec![ address ]));
+        let main = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "main"),
+            generics: vec![],
+            params: vec![],
+            ret: None,
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Expr(call("grow", vec![ Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))) ]))) ]),
+        };
+
+        checker.declare_functions(&[ grow, main ]).unwrap();
+
+        assert!(checker.monomorphize().unwrap_err().get_message().starts_with("reached the instantiation depth limit"));
+    }
+}
@@ -0,0 +1,186 @@
+use super::{ * };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Binding {
+    pub ty: Type,
+    pub is_mutable: bool,
+    // a `?*T` variable known to be non-null here, see `Typechecker::narrow`
+    pub is_narrowed: bool,
+}
+
+impl Binding {
+    pub fn new(ty: Type, is_mutable: bool) -> Self {
+        Self {
+            ty,
+            is_mutable,
+            is_narrowed: false,
+        }
+    }
+}
+
+impl Typechecker {
+    pub fn is_mutable_pointer(ty: Type) -> bool {
+        matches!(ty, Type::Complex(ComplexType::Pointer(Pointer { is_mutable: true, .. })))
+    }
+
+    pub fn check_assign(&mut self, place: &Expr, value: &Expr) -> Checked<()> {
+        let mut ty = self.check_expr(place, None)?;
+
+        self.check_mutable_place(place)?;
+
+        // a narrowed variable may be given any value of its declared type
+        let narrowed = match place.node {
+            ExprKind::Identifier(name) if self.lookup_binding(name).is_some_and(|(_, binding)| binding.is_narrowed) => Some(name),
+            _ => None,
+        };
+
+        if narrowed.is_some() {
+            ty = Self::nullable(ty);
+        }
+
+        let actual = self.check_expr(value, Some(ty))?;
+
+        self.expect_type(ty, actual, value.span)?;
+
+        if let Some(name) = narrowed.filter(|_| Self::is_nullable(actual)) {
+            self.forget_narrowing(name);
+        }
+
+        Ok(())
+    }
+
+    // whether `place` denotes memory that may be written to: a `let mut` binding, a field of one,
+    // or anything reached through a `*mut` pointer
+    pub(super) fn check_mutable_place(&mut self, place: &Expr) -> Checked<()> {
+        match &place.node {
+            ExprKind::Identifier(name) => {
+                let Some((depth, binding)) = self.lookup_binding(name) else {
+                    if self.static_type(name).is_some() {
+                        return Err(Error::from_span(place.span, ErrorType::TypeError, &format!("cannot assign to static `{}`; statics are immutable", name)));
+                    }
+
+                    return Err(Error::from_span(place.span, ErrorType::TypeError, &format!("cannot assign to `{}`, which is not a variable", name)));
+                };
+
+                if let Some(frame) = self.closure_frames.last() {
+                    if depth < frame.boundary && !frame.by_ref.contains(name) {
+                        return Err(Error::from_span(place.span, ErrorType::TypeError, &format!("cannot assign to `{}`, which is captured by value; capture it with `&{}` instead", name, name)));
+                    }
+                }
+
+                if !binding.is_mutable {
+                    return Err(Error::from_span(place.span, ErrorType::TypeError, &format!("cannot assign to immutable binding `{}`; declare it with `let mut`", name)));
+                }
+
+                Ok(())
+            },
+
+            ExprKind::Unary(UnaryOp::Deref, pointer) => {
+                let ty = self.check_expr(pointer, None)?;
+
+                Self::check_writable_pointer(ty, place.span)
+            },
+
+            ExprKind::Field(base, field) => {
+                let ty = self.check_expr(base, None)?;
+
+                if matches!(ty, Type::Complex(ComplexType::Pointer(_))) {
+                    return Self::check_writable_pointer(ty, place.span);
+                }
+
+                match &base.node {
+                    ExprKind::Identifier(name) if self.lookup_binding(name).is_some_and(|(_, binding)| !binding.is_mutable) => {
+                        Err(Error::from_span(place.span, ErrorType::TypeError, &format!("cannot assign to field `{}` of immutable binding `{}`", field, name)))
+                    },
+
+                    _ => self.check_mutable_place(base),
+                }
+            },
+
+            _ => Err(Error::from_span(place.span, ErrorType::TypeError, "cannot assign to this expression")),
+        }
+    }
+
+    fn check_writable_pointer(ty: Type, span: Span) -> Checked<()> {
+        match ty {
+            _ if Self::is_mutable_pointer(ty) => Ok(()),
+            Type::Complex(ComplexType::Pointer(_)) => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot write through `{}`, which is not a `*mut` pointer", ty))),
+            _ => Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot write through a value of type `{}`", ty))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Spanned }, types::{ * } };
+
+    fn pointer(is_mutable: bool) -> TypeExpr {
+        Spanned::new(0, 0, TypeExprKind::Pointer(1, is_mutable, Box::new(i32_type())))
+    }
+
+    fn run(body: Block) -> std::result::Result<(), String> {
+        let mut checker = Typechecker::new("main");
+        let decl = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "f"),
+            generics: vec![],
+            params: vec![],
+            ret: None,
+            body: Some(body),
+        };
+
+        checker.declare_functions(&[ decl ]).map_err(|err| err.get_message())
+    }
+
+    #[test]
+    fn only_mutable_bindings_are_assignable() {
+        assert_eq!(run(vec![ stmt(StmtKind::Let("x", true, None, int(1))), stmt(StmtKind::Assign(ident("x"), int(2))) ]), Ok(()));
+        assert_eq!(
+            run(vec![ stmt(StmtKind::Let("x", false, None, int(1))), stmt(StmtKind::Assign(ident("x"), int(2))) ]),
+            Err("cannot assign to immutable binding `x`; declare it with `let mut`".to_owned()),
+        );
+    }
+
+    #[test]
+    fn writes_need_a_mut_pointer() {
+        let address_of_mut = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::AddressOfMut, Box::new(ident("x"))));
+        let body = vec![
+            stmt(StmtKind::Let("x", true, None, int(1))),
+            stmt(StmtKind::Let("p", false, Some(pointer(true)), address_of_mut)),
+            stmt(StmtKind::Assign(deref(ident("p")), int(2))),
+            // `*mut i32` coerces to `*i32`, which can no longer be written through
+            stmt(StmtKind::Let("q", false, Some(pointer(false)), ident("p"))),
+            stmt(StmtKind::Assign(deref(ident("q")), int(3))),
+        ];
+
+        assert_eq!(run(body), Err("cannot write through `*i32`, which is not a `*mut` pointer".to_owned()));
+    }
+
+    #[test]
+    fn const_pointers_do_not_coerce_to_mut() {
+        let address_of = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::AddressOf, Box::new(ident("x"))));
+        let body = vec![
+            stmt(StmtKind::Let("x", true, None, int(1))),
+            stmt(StmtKind::Let("p", false, Some(pointer(true)), address_of)),
+        ];
+
+        assert_eq!(run(body), Err("expected `*mut i32`, found `*i32`".to_owned()));
+    }
+
+    #[test]
+    fn casts_cannot_add_mut() {
+        let address_of = Spanned::new(0, 0, ExprKind::Unary(UnaryOp::AddressOf, Box::new(ident("x"))));
+        let cast = |is_mutable| Spanned::new(0, 0, ExprKind::Cast(Box::new(ident("p")), pointer(is_mutable)));
+        let body = |is_mutable| vec![
+            stmt(StmtKind::Let("x", true, None, int(1))),
+            stmt(StmtKind::Let("p", false, Some(pointer(false)), address_of.clone())),
+            stmt(StmtKind::Let("q", false, None, cast(is_mutable))),
+        ];
+
+        assert_eq!(run(body(false)), Ok(()));
+        assert_eq!(run(body(true)), Err("cannot cast `*i32` as `*mut i32`".to_owned()));
+    }
+}
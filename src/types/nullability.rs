@@ -0,0 +1,214 @@
+use super::{ *, mutability::{ Binding } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+impl Typechecker {
+    pub fn is_nullable(ty: Type) -> bool {
+        matches!(ty, Type::Complex(ComplexType::Pointer(Pointer { is_nullable: true, .. })))
+    }
+
+    pub fn nullable(ty: Type) -> Type {
+        match ty {
+            Type::Complex(ComplexType::Pointer(ptr)) => Type::Complex(ComplexType::Pointer(ptr.with_nullability(true))),
+            _ => ty,
+        }
+    }
+
+    pub fn non_null(ty: Type) -> Type {
+        match ty {
+            Type::Complex(ComplexType::Pointer(ptr)) => Type::Complex(ComplexType::Pointer(ptr.with_nullability(false))),
+            _ => ty,
+        }
+    }
+
+    pub(super) fn check_non_null(ty: Type, span: Span) -> Checked<()> {
+        if Self::is_nullable(ty) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("cannot use a value of type `{}` that may be null; compare it against `null` or unwrap it with `?` first", ty)));
+        }
+
+        Ok(())
+    }
+
+    // `null` takes the type of the nullable pointer it is used as
+    pub(super) fn check_null(&self, expected: Option<Type>, span: Span) -> Checked<Type> {
+        match expected {
+            Some(Type::Complex(ComplexType::Pointer(ptr))) if !ptr.is_nullable => {
+                Err(Error::from_span(span, ErrorType::TypeError, &format!("`null` is not a valid `{}`; only nullable pointers like `?{}` can be null", ptr, ptr)))
+            },
+
+            expected => Ok(self.check_literal(Literal::Null, expected)),
+        }
+    }
+
+    pub(super) fn check_unwrap(&mut self, pointer: &Expr, expected: Option<Type>, span: Span) -> Checked<Type> {
+        let ty = self.check_expr(pointer, expected.map(Self::nullable))?;
+
+        if !Self::is_nullable(ty) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("`?` can only be applied to nullable pointers, found `{}`", ty)));
+        }
+
+        Ok(Self::non_null(ty))
+    }
+
+    // the variables a condition proves to be non-null when it evaluates to `outcome`
+    fn non_null_when(condition: &Expr, outcome: bool) -> Vec<&'static str> {
+        let compared = |lhs: &Expr, rhs: &Expr| match (&lhs.node, &rhs.node) {
+            (ExprKind::Identifier(name), ExprKind::Literal(Literal::Null)) | (ExprKind::Literal(Literal::Null), ExprKind::Identifier(name)) => Some(*name),
+            _ => None,
+        };
+
+        match &condition.node {
+            ExprKind::Binary(lhs, BinaryOp::NotEquals, rhs) if outcome => compared(lhs, rhs).into_iter().collect(),
+            ExprKind::Binary(lhs, BinaryOp::Equals, rhs) if !outcome => compared(lhs, rhs).into_iter().collect(),
+
+            // both sides of a true `&&` and of a false `||` hold
+            ExprKind::Binary(lhs, BinaryOp::And, rhs) if outcome => [ Self::non_null_when(lhs, true), Self::non_null_when(rhs, true) ].concat(),
+            ExprKind::Binary(lhs, BinaryOp::Or, rhs) if !outcome => [ Self::non_null_when(lhs, false), Self::non_null_when(rhs, false) ].concat(),
+
+            ExprKind::Unary(UnaryOp::Not, operand) => Self::non_null_when(operand, !outcome),
+
+            _ => vec![],
+        }
+    }
+
+    // rebinds every nullable variable proven non-null by `condition` in the innermost scope
+    pub fn narrow(&mut self, condition: &Expr, outcome: bool) {
+        for name in Self::non_null_when(condition, outcome) {
+            let Some((_, binding)) = self.lookup_binding(name) else {
+                continue;
+            };
+
+            if Self::is_nullable(binding.ty) {
+                let narrowed = Binding {
+                    ty: Self::non_null(binding.ty),
+                    is_narrowed: true,
+                    ..binding
+                };
+
+                self.scopes.last_mut().expect("typechecker has no scope").insert(name, narrowed);
+            }
+        }
+    }
+
+    // `if p == null { return; }` leaves `p` non-null for the rest of the block
+    pub fn narrow_after_if(&mut self, condition: &Expr, then: &Block, otherwise: Option<&Block>) {
+        match otherwise {
+            None if Self::block_returns(then) => self.narrow(condition, false),
+            Some(otherwise) if Self::block_returns(then) && !Self::block_returns(otherwise) => self.narrow(condition, false),
+            Some(otherwise) if Self::block_returns(otherwise) && !Self::block_returns(then) => self.narrow(condition, true),
+            _ => {},
+        }
+    }
+
+    pub(super) fn check_narrowed_block(&mut self, block: &Block, condition: &Expr, outcome: bool) -> Checked<()> {
+        self.push_scope();
+        self.narrow(condition, outcome);
+
+        let checked = self.check_block(block);
+
+        self.pop_scope();
+
+        checked
+    }
+
+    // after `p = maybe_null()`, no enclosing narrowing of `p` holds anymore
+    pub(super) fn forget_narrowing(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            match scope.get_mut(name) {
+                Some(binding) if binding.is_narrowed => {
+                    binding.ty = Self::nullable(binding.ty);
+                    binding.is_narrowed = false;
+                },
+
+                // reached the declaration itself
+                Some(_) => break,
+                None => {},
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Spanned }, types::{ * } };
+
+    fn null() -> Expr {
+        Spanned::new(0, 0, ExprKind::Literal(Literal::Null))
+    }
+
+    fn compare(op: BinaryOp) -> Expr {
+        Spanned::new(0, 0, ExprKind::Binary(Box::new(ident("p")), op, Box::new(null())))
+    }
+
+    fn pointer(is_nullable: bool) -> TypeExpr {
+        let pointer = Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::Builtin(SimpleType::signed(SignedIntegerTypes::Int32))))));
+
+        if is_nullable {
+            Spanned::new(0, 0, TypeExprKind::Nullable(Box::new(pointer)))
+        } else {
+            pointer
+        }
+    }
+
+    // fn f(p: ?*i32) -> i32 { body }
+    fn run(body: Block) -> std::result::Result<(), String> {
+        let mut checker = Typechecker::new("main");
+        let decl = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "f"),
+            generics: vec![],
+            params: vec![ Param { name: Spanned::new(0, 0, "p"), ty: pointer(true) } ],
+            ret: Some(Spanned::new(0, 0, TypeExprKind::Builtin(SimpleType::signed(SignedIntegerTypes::Int32)))),
+            body: Some(body),
+        };
+
+        checker.declare_functions(&[ decl ]).map_err(|err| err.get_message())
+    }
+
+    #[test]
+    fn nullable_pointers_must_be_checked_before_use() {
+        assert_eq!(run(vec![ stmt(StmtKind::Return(Some(deref(ident("p"))))) ]), Err("cannot use a value of type `?*i32` that may be null; compare it against `null` or unwrap it with `?` first".to_owned()));
+
+        let unwrapped = Spanned::new(0, 0, ExprKind::Unwrap(Box::new(ident("p"))));
+
+        assert_eq!(run(vec![ stmt(StmtKind::Return(Some(deref(unwrapped)))) ]), Ok(()));
+    }
+
+    #[test]
+    fn null_checks_narrow_the_pointer() {
+        let checked = vec![
+            stmt(StmtKind::If(compare(BinaryOp::NotEquals), vec![ stmt(StmtKind::Return(Some(deref(ident("p"))))) ], None)),
+            stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))))),
+        ];
+
+        let early_return = vec![
+            stmt(StmtKind::If(compare(BinaryOp::Equals), vec![ stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))))) ], None)),
+            stmt(StmtKind::Return(Some(deref(ident("p"))))),
+        ];
+
+        assert_eq!(run(checked), Ok(()));
+        assert_eq!(run(early_return), Ok(()));
+    }
+
+    #[test]
+    fn null_is_only_allowed_in_nullable_positions() {
+        let body = vec![
+            stmt(StmtKind::Let("q", false, Some(pointer(false)), null())),
+            stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))))),
+        ];
+
+        assert_eq!(run(body), Err("`null` is not a valid `*i32`; only nullable pointers like `?*i32` can be null".to_owned()));
+    }
+
+    #[test]
+    fn casts_cannot_remove_nullability() {
+        let body = |is_nullable| vec![
+            stmt(StmtKind::Let("q", false, None, Spanned::new(0, 0, ExprKind::Cast(Box::new(ident("p")), pointer(is_nullable))))),
+            stmt(StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))))),
+        ];
+
+        assert_eq!(run(body(true)), Ok(()));
+        assert_eq!(run(body(false)), Err("cannot cast `?*i32` as `*i32`".to_owned()));
+    }
+}
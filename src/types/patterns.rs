@@ -0,0 +1,335 @@
+use super::{ * };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Constructor {
+    Variant(usize),
+    Bool(bool),
+    Int(u64),
+    Char(char),
+}
+
+// a pattern reduced to what matters for exhaustiveness: its constructor (or `None` for
+// anything irrefutable) and the sub-patterns of the constructor's fields
+#[derive(Debug, Clone, PartialEq)]
+struct Deconstructed {
+    ctor: Option<Constructor>,
+    fields: Vec<Deconstructed>,
+}
+
+impl Deconstructed {
+    fn wildcard() -> Self {
+        Self {
+            ctor: None,
+            fields: vec![],
+        }
+    }
+}
+
+impl Typechecker {
+    pub(super) fn check_match(&mut self, scrutinee: &Expr, arms: &[MatchArm], expected: Option<Type>, span: Span) -> Checked<Type> {
+        let ty = self.check_expr(scrutinee, None)?;
+        let mut result = expected;
+        let mut rows: Vec<Vec<Deconstructed>> = Vec::with_capacity(arms.len());
+
+        for arm in arms {
+            self.push_scope();
+
+            let checked = self.check_pattern(&arm.pattern, ty).and_then(|pattern| {
+                Ok((pattern, self.check_expr(&arm.body, result)?))
+            });
+
+            self.pop_scope();
+
+            let (pattern, body) = checked?;
+
+            match result {
+                Some(result) if result != body => {
+                    return Err(Error::from_span(arm.body.span, ErrorType::TypeError, &format!("match arms have incompatible types: expected `{}`, found `{}`", result, body)));
+                },
+
+                _ => result = Some(body),
+            }
+
+            if !self.is_useful(&rows, std::slice::from_ref(&pattern), &[ ty ]) {
+                return Err(Error::from_span(arm.pattern.span, ErrorType::TypeError, "unreachable match arm: every value it matches is covered by a previous arm"));
+            }
+
+            rows.push(vec![ pattern ]);
+        }
+
+        if self.is_useful(&rows, &[ Deconstructed::wildcard() ], &[ ty ]) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("non-exhaustive match: {} not covered", self.missing_patterns(&rows, ty).join(", "))));
+        }
+
+        Ok(result.unwrap_or(Type::Simple(SimpleType::Void)))
+    }
+
+    fn check_pattern(&mut self, pattern: &Pattern, ty: Type) -> Checked<Deconstructed> {
+        match &pattern.node {
+            PatternKind::Wildcard => Ok(Deconstructed::wildcard()),
+
+            // a bare name refers to a payload-less variant of the matched enum if there is one
+            PatternKind::Binding(name) => match self.variant_index(ty, name) {
+                Some(index) => self.check_variant_pattern(pattern.span, ty, index, &[]),
+
+                None => {
+                    self.declare_variable(name, ty);
+
+                    Ok(Deconstructed::wildcard())
+                },
+            },
+
+            PatternKind::Literal(literal) => {
+                let literal_type = self.check_literal(*literal, Some(ty));
+
+                if literal_type != ty {
+                    return Err(Error::from_span(pattern.span, ErrorType::TypeError, &format!("expected a pattern of type `{}`, found `{}`", ty, literal_type)));
+                }
+
+                Self::check_literal_range(*literal, false, ty, pattern.span)?;
+
+                let ctor = match literal {
+                    Literal::Int(value, _) => Constructor::Int(*value),
+                    Literal::Bool(value) => Constructor::Bool(*value),
+                    Literal::Char(value) => Constructor::Char(*value),
+
+                    _ => return Err(Error::from_span(pattern.span, ErrorType::TypeError, "only integer, `bool` and `char` literals can be used as patterns")),
+                };
+
+                Ok(Deconstructed {
+                    ctor: Some(ctor),
+                    fields: vec![],
+                })
+            },
+
+            PatternKind::Variant(name, fields) => match self.variant_index(ty, name) {
+                Some(index) => self.check_variant_pattern(pattern.span, ty, index, fields),
+                None => Err(Error::from_span(pattern.span, ErrorType::TypeError, &format!("type `{}` has no variant named `{}`", ty, name))),
+            },
+        }
+    }
+
+    fn check_variant_pattern(&mut self, span: Span, ty: Type, index: usize, fields: &[Pattern]) -> Checked<Deconstructed> {
+        let payload = self.constructor_fields(ty, Constructor::Variant(index));
+
+        if payload.len() != fields.len() {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("this pattern has {} field(s) but the variant has {}", fields.len(), payload.len())));
+        }
+
+        let mut deconstructed = Vec::with_capacity(fields.len());
+
+        for (field, field_type) in fields.iter().zip(payload) {
+            deconstructed.push(self.check_pattern(field, field_type)?);
+        }
+
+        Ok(Deconstructed {
+            ctor: Some(Constructor::Variant(index)),
+            fields: deconstructed,
+        })
+    }
+
+    fn variant_index(&self, ty: Type, name: &str) -> Option<usize> {
+        match ty {
+            Type::Simple(SimpleType::UserType(user_type)) => self.enums.get(&user_type)?.get_variant(name).map(|(index, _)| index),
+            _ => None,
+        }
+    }
+
+    fn constructor_fields(&self, ty: Type, ctor: Constructor) -> Vec<Type> {
+        match (ty, ctor) {
+            (Type::Simple(SimpleType::UserType(user_type)), Constructor::Variant(index)) => {
+                self.enums.get(&user_type).map_or_else(Vec::new, |decl| decl.variants[index].payload.clone())
+            },
+
+            _ => vec![],
+        }
+    }
+
+    // every constructor of `ty` if there are finitely many, `None` for types like integers
+    fn all_constructors(&self, ty: Type) -> Option<Vec<Constructor>> {
+        match ty {
+            Type::Simple(SimpleType::Bool) => Some(vec![ Constructor::Bool(false), Constructor::Bool(true) ]),
+            Type::Simple(SimpleType::UserType(user_type)) => {
+                self.enums.get(&user_type).map(|decl| (0 .. decl.variants.len()).map(Constructor::Variant).collect())
+            },
+
+            _ => None,
+        }
+    }
+
+    // whether a value matched by `pattern` could fail to match every row of `rows`,
+    // i.e. whether adding `pattern` below `rows` would ever make a difference
+    fn is_useful(&self, rows: &[Vec<Deconstructed>], pattern: &[Deconstructed], types: &[Type]) -> bool {
+        let Some((head, rest)) = pattern.split_first() else {
+            return rows.is_empty();
+        };
+
+        match head.ctor {
+            Some(ctor) => self.is_useful_specialized(rows, ctor, &head.fields, rest, types),
+
+            None => {
+                let used: Vec<Constructor> = rows.iter().filter_map(|row| row[0].ctor).collect();
+
+                match self.all_constructors(types[0]) {
+                    Some(all) if all.iter().all(|ctor| used.contains(ctor)) => all.into_iter().any(|ctor| {
+                        let fields = vec![ Deconstructed::wildcard(); self.constructor_fields(types[0], ctor).len() ];
+
+                        self.is_useful_specialized(rows, ctor, &fields, rest, types)
+                    }),
+
+                    // some constructor is never mentioned, so only the rows that match anything matter
+                    _ => {
+                        let defaults: Vec<Vec<Deconstructed>> = rows.iter().filter(|row| row[0].ctor.is_none()).map(|row| row[1 ..].to_vec()).collect();
+
+                        self.is_useful(&defaults, rest, &types[1 ..])
+                    },
+                }
+            },
+        }
+    }
+
+    fn is_useful_specialized(&self, rows: &[Vec<Deconstructed>], ctor: Constructor, fields: &[Deconstructed], rest: &[Deconstructed], types: &[Type]) -> bool {
+        let arity = fields.len();
+        let specialized: Vec<Vec<Deconstructed>> = rows.iter().filter_map(|row| {
+            let mut expanded = match row[0].ctor {
+                Some(other) if other == ctor => row[0].fields.clone(),
+                Some(_) => return None,
+                None => vec![ Deconstructed::wildcard(); arity ],
+            };
+
+            expanded.extend_from_slice(&row[1 ..]);
+
+            Some(expanded)
+        }).collect();
+
+        let mut pattern = fields.to_vec();
+        let mut field_types = self.constructor_fields(types[0], ctor);
+
+        pattern.extend_from_slice(rest);
+        field_types.extend_from_slice(&types[1 ..]);
+
+        self.is_useful(&specialized, &pattern, &field_types)
+    }
+
+    fn missing_patterns(&self, rows: &[Vec<Deconstructed>], ty: Type) -> Vec<String> {
+        let Some(all) = self.all_constructors(ty) else {
+            return vec![ "`_`".to_owned() ];
+        };
+
+        all.into_iter().filter(|ctor| {
+            let fields = vec![ Deconstructed::wildcard(); self.constructor_fields(ty, *ctor).len() ];
+            let pattern = Deconstructed {
+                ctor: Some(*ctor),
+                fields,
+            };
+
+            self.is_useful(rows, &[ pattern ], &[ ty ])
+        }).map(|ctor| match (ctor, ty) {
+            (Constructor::Variant(index), Type::Simple(SimpleType::UserType(user_type))) => {
+                let variant = &self.enums[&user_type].variants[index];
+
+                if variant.payload.is_empty() {
+                    format!("`{}.{}`", user_type.name, variant.name)
+                } else {
+                    format!("`{}.{}(..)`", user_type.name, variant.name)
+                }
+            },
+
+            (Constructor::Bool(value), _) => format!("`{}`", value),
+            (Constructor::Int(value), _) => format!("`{}`", value),
+            (Constructor::Char(value), _) => format!("`'{}'`", value),
+            (Constructor::Variant(index), _) => format!("variant #{}", index),
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ * }, lexer::{ Spanned }, types::{ * } };
+
+    fn pattern(kind: PatternKind) -> Pattern {
+        Spanned::new(0, 0, kind)
+    }
+
+    fn arm(pattern: Pattern, value: u64) -> MatchArm {
+        MatchArm {
+            pattern,
+            body: Spanned::new(0, 0, ExprKind::Literal(Literal::Int(value, None))),
+        }
+    }
+
+    fn checker_with_option() -> Typechecker {
+        let mut checker = Typechecker::new("main");
+        let opt = EnumDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Opt"),
+            generics: vec![],
+            variants: vec![
+                EnumVariantDecl { name: Spanned::new(0, 0, "None"), payload: vec![] },
+                EnumVariantDecl { name: Spanned::new(0, 0, "Some"), payload: vec![ Spanned::new(0, 0, TypeExprKind::Builtin(SimpleType::Bool)) ] },
+            ],
+        };
+
+        checker.declare_enums(&[ opt ]).unwrap();
+        checker.declare_variable("opt", Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Opt"))));
+
+        checker
+    }
+
+    fn check(checker: &mut Typechecker, arms: Vec<MatchArm>) -> std::result::Result<Type, String> {
+        let scrutinee = Spanned::new(0, 0, ExprKind::Identifier("opt"));
+        let expr = Spanned::new(0, 0, ExprKind::Match(Box::new(scrutinee), arms));
+
+        checker.check_expr(&expr, None).map_err(|err| err.get_message())
+    }
+
+    #[test]
+    fn exhaustive_match_over_nested_payloads() {
+        let mut checker = checker_with_option();
+        let arms = vec![
+            arm(pattern(PatternKind::Binding("None")), 0),
+            arm(pattern(PatternKind::Variant("Some", vec![ pattern(PatternKind::Literal(Literal::Bool(true))) ])), 1),
+            arm(pattern(PatternKind::Variant("Some", vec![ pattern(PatternKind::Literal(Literal::Bool(false))) ])), 2),
+        ];
+
+        assert_eq!(check(&mut checker, arms), Ok(Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32))));
+    }
+
+    #[test]
+    fn missing_variant_is_reported() {
+        let mut checker = checker_with_option();
+        let arms = vec![
+            arm(pattern(PatternKind::Variant("Some", vec![ pattern(PatternKind::Binding("value")) ])), 1),
+        ];
+
+        assert_eq!(check(&mut checker, arms), Err("non-exhaustive match: `Opt.None` not covered".to_owned()));
+    }
+
+    #[test]
+    fn arm_after_wildcard_is_unreachable() {
+        let mut checker = checker_with_option();
+        let arms = vec![
+            arm(pattern(PatternKind::Wildcard), 0),
+            arm(pattern(PatternKind::Binding("None")), 1),
+        ];
+
+        assert!(check(&mut checker, arms).unwrap_err().starts_with("unreachable match arm"));
+    }
+
+    #[test]
+    fn literal_patterns_must_fit_the_scrutinee() {
+        let mut checker = checker_with_option();
+
+        checker.declare_variable("opt", Type::Simple(SimpleType::unsigned(UnsignedIntegerTypes::Uint8)));
+
+        let arms = vec![
+            arm(pattern(PatternKind::Literal(Literal::Int(300, None))), 0),
+            arm(pattern(PatternKind::Wildcard), 1),
+        ];
+
+        assert_eq!(check(&mut checker, arms), Err("literal `300` is out of range for `u8`, whose values range from 0 to 255".to_owned()));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{ collections::{ HashMap } };
+
+use super::{ *, consteval::{ ConstValue } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span } };
+
+type Evaluated = std::result::Result<ConstValue, Error>;
+type Checked<T> = std::result::Result<T, Error>;
+
+impl Typechecker {
+    // statics are declared before functions so that function bodies can refer to them,
+    // and evaluated afterwards with `eval_statics` so that their initialisers can call functions
+    pub fn declare_statics(&mut self, decls: &[StaticDecl]) -> Checked<()> {
+        for decl in decls {
+            if self.statics.contains_key(decl.name.node) {
+                return Err(Error::from_span(decl.name.span, ErrorType::NameError, &format!("static `{}` is defined more than once", decl.name.node)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            let ty = self.resolve_type(&decl.ty)?;
+
+            self.statics.insert(decl.name.node, decl.clone());
+            self.static_types.insert(decl.name.node, ty);
+        }
+
+        Ok(())
+    }
+
+    // every static is evaluated, even if nothing refers to it, so that cycles are always reported
+    pub fn eval_statics(&mut self) -> Checked<()> {
+        let mut decls: Vec<StaticDecl> = self.statics.values().cloned().collect();
+
+        decls.sort_by_key(|decl| decl.name.span.start);
+
+        for decl in decls {
+            self.eval_static(decl.name.node, decl.name.span)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn static_value(&self, name: &str) -> Option<ConstValue> {
+        self.static_values.get(name).copied()
+    }
+
+    pub(super) fn static_type(&self, name: &str) -> Option<Type> {
+        self.static_types.get(name).copied()
+    }
+
+    fn eval_static(&mut self, name: &'static str, span: Span) -> Evaluated {
+        if let Some(value) = self.static_values.get(name) {
+            return Ok(*value);
+        }
+
+        self.enter_const(name, span, "static")?;
+
+        let decl = self.statics[name].clone();
+        let ty = self.static_types[name];
+        let value = self.eval_const(&decl.value, Some(ty)).and_then(|value| {
+            self.expect_type(ty, value.get_type(), decl.value.span)?;
+
+            Ok(value)
+        });
+
+        self.evaluating.pop();
+
+        let value = value?;
+
+        self.static_values.insert(name, value);
+
+        Ok(value)
+    }
+
+    // pushes a static or function onto the evaluation stack, unless it is already being evaluated
+    fn enter_const(&mut self, name: &'static str, span: Span, kind: &str) -> Checked<()> {
+        if let Some(start) = self.evaluating.iter().position(|other| *other == name) {
+            let mut cycle: Vec<&str> = self.evaluating[start ..].to_vec();
+
+            cycle.push(name);
+
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("{} `{}` depends on itself at compile time: `{}`", kind, name, cycle.join("` -> `"))));
+        }
+
+        self.evaluating.push(name);
+
+        Ok(())
+    }
+
+    // arguments and `let`s of the function being evaluated come first, then statics
+    pub(super) fn eval_const_identifier(&mut self, name: &'static str, span: Span) -> Evaluated {
+        if let Some(value) = self.const_frames.last().and_then(|frame| frame.get(name)) {
+            return Ok(*value);
+        }
+
+        if self.lookup_variable(name).is_none() && self.statics.contains_key(name) {
+            return self.eval_static(name, span);
+        }
+
+        Err(Error::from_span(span, ErrorType::TypeError, &format!("`{}` is not a constant", name)))
+    }
+
+    // a call is evaluated by binding the evaluated arguments to the parameters and running the body,
+    // which may only consist of `let` statements followed by a `return`
+    pub(super) fn eval_const_call(&mut self, callee: &Expr, args: &[Expr], span: Span) -> Evaluated {
+        let decl = match &callee.node {
+            ExprKind::Identifier(name) if self.lookup_variable(name).is_none() => self.function_decls.get(name).cloned(),
+            _ => None,
+        };
+
+        let Some(decl) = decl else {
+            return Err(Error::from_span(callee.span, ErrorType::TypeError, "only calls to functions declared with a body can be evaluated at compile time"));
+        };
+
+        let sig = self.functions[decl.name.node].clone();
+        let mut frame: HashMap<&'static str, ConstValue> = HashMap::new();
+
+        for ((param, arg), ty) in decl.params.iter().zip(args).zip(sig.params.iter()) {
+            // the call as a whole has been checked, arguments included
+            frame.insert(param.name.node, self.fold_const(arg, Some(*ty))?);
+        }
+
+        self.enter_const(decl.name.node, span, "function")?;
+        self.const_frames.push(frame);
+
+        let value = self.in_fresh_scopes(|checker| {
+            for (param, ty) in decl.params.iter().zip(sig.params.iter()) {
+                checker.declare_variable(param.name.node, *ty);
+            }
+
+            checker.eval_const_body(&decl, sig.ret)
+        });
+
+        self.const_frames.pop();
+        self.evaluating.pop();
+
+        value
+    }
+
+    // runs `f` in the scopes of a new function body, so that a function evaluated at compile time
+    // sees only its own parameters and the globals, not the locals of the expression calling it
+    fn in_fresh_scopes<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let scopes = std::mem::replace(&mut self.scopes, vec![ HashMap::new() ]);
+        let closure_frames = std::mem::take(&mut self.closure_frames);
+        let type_params = std::mem::take(&mut self.type_params);
+
+        let result = f(self);
+
+        self.scopes = scopes;
+        self.closure_frames = closure_frames;
+        self.type_params = type_params;
+
+        result
+    }
+
+    fn eval_const_body(&mut self, decl: &FnDecl, ret: Type) -> Evaluated {
+        for stmt in decl.body.iter().flatten() {
+            match &stmt.node {
+                StmtKind::Let(name, _, ty, value) => {
+                    let expected = ty.as_ref().map(|ty| self.resolve_type(ty)).transpose()?;
+                    let value = self.eval_const(value, expected)?;
+
+                    if let Some(expected) = expected {
+                        self.expect_type(expected, value.get_type(), stmt.span)?;
+                    }
+
+                    self.declare_variable(name, value.get_type());
+                    self.const_frames.last_mut().expect("a call is being evaluated").insert(name, value);
+                },
+
+                StmtKind::Return(Some(value)) => return self.eval_const(value, Some(ret)),
+
+                _ => {
+                    return Err(Error::from_span(stmt.span, ErrorType::TypeError, &format!("function `{}` cannot be evaluated at compile time; only `let` and `return` statements are supported", decl.name.node)));
+                },
+            }
+        }
+
+        Err(Error::from_span(decl.name.span, ErrorType::TypeError, &format!("function `{}` cannot be evaluated at compile time because it does not return a value", decl.name.node)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Spanned }, types::{ * } };
+
+    fn static_decl(name: &'static str, start: usize, value: Expr) -> StaticDecl {
+        StaticDecl {
+            is_pub: false,
+            name: Spanned::new(start, start, name),
+            ty: i32_type(),
+            value,
+        }
+    }
+
+    #[test]
+    fn statics_fold_through_other_statics_and_calls() {
+        let mut checker = Typechecker::new("main");
+        // fn square(x: i32) -> i32 { let y = x * x; return y + 0; }
+        let square = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "square"),
+            generics: vec![],
+            params: vec![ Param { name: Spanned::new(0, 0, "x"), ty: i32_type() } ],
+            ret: Some(i32_type()),
+            body: Some(vec![
+                Spanned::new(0, 0, StmtKind::Let("y", false, None, binary(ident("x"), BinaryOp::Mul, ident("x")))),
+                Spanned::new(0, 0, StmtKind::Return(Some(binary(ident("y"), BinaryOp::Add, int(0))))),
+            ]),
+        };
+        // static AREA: i32 = square(SIDE) + 1;  static SIDE: i32 = 3;
+        let area = static_decl("AREA", 0, binary(Spanned::new(0, 0, ExprKind::Call(Box::new(ident("square")), vec![ ident("SIDE") ])), BinaryOp::Add, int(1)));
+        let side = static_decl("SIDE", 1, int(3));
+
+        checker.declare_statics(&[ area, side ]).unwrap();
+        checker.declare_functions(&[ square ]).unwrap();
+        checker.eval_statics().unwrap();
+
+        assert_eq!(checker.static_value("AREA").and_then(|value| value.as_i128()), Some(10));
+
+        let array = Spanned::new(0, 0, TypeExprKind::Array(Some(Box::new(ident("AREA"))), Box::new(i32_type())));
+
+        assert_eq!(checker.resolve_type(&array).unwrap().to_string(), "[10]i32");
+    }
+
+    #[test]
+    fn calls_do_not_see_the_locals_of_their_caller() {
+        let mut checker = Typechecker::new("main");
+        // static SIDE: i32 = 3;  fn side() -> i32 { return SIDE; }
+        let side = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "side"),
+            generics: vec![],
+            params: vec![],
+            ret: Some(i32_type()),
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Return(Some(ident("SIDE")))) ]),
+        };
+
+        checker.declare_statics(&[ static_decl("SIDE", 0, int(3)) ]).unwrap();
+        checker.declare_functions(&[ side ]).unwrap();
+
+        // a local of the caller shadows the static, but only in the caller
+        checker.push_scope();
+        checker.declare_variable("SIDE", Type::Simple(SimpleType::Bool));
+
+        let value = checker.eval_const(&call("side", vec![]), None).unwrap();
+
+        assert_eq!(value.as_i128(), Some(3));
+    }
+
+    #[test]
+    fn cycles_between_statics_are_reported() {
+        let mut checker = Typechecker::new("main");
+        let a = static_decl("A", 0, binary(ident("B"), BinaryOp::Add, int(1)));
+        let b = static_decl("B", 1, ident("A"));
+
+        checker.declare_statics(&[ a, b ]).unwrap();
+
+        let error = checker.eval_statics().unwrap_err();
+
+        assert_eq!(error.get_message(), "static `A` depends on itself at compile time: `A` -> `B` -> `A`");
+    }
+
+    #[test]
+    fn function_bodies_must_be_simple() {
+        let mut checker = Typechecker::new("main");
+        // fn pick() -> i32 { if true { return 1; } return 2; }
+        let pick = FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "pick"),
+            generics: vec![],
+            params: vec![],
+            ret: Some(i32_type()),
+            body: Some(vec![
+                Spanned::new(0, 0, StmtKind::If(Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true))), vec![ Spanned::new(0, 0, StmtKind::Return(Some(int(1)))) ], None)),
+                Spanned::new(0, 0, StmtKind::Return(Some(int(2)))),
+            ]),
+        };
+        let value = static_decl("V", 0, Spanned::new(0, 0, ExprKind::Call(Box::new(ident("pick")), vec![])));
+
+        checker.declare_statics(&[ value ]).unwrap();
+        checker.declare_functions(&[ pick ]).unwrap();
+
+        let error = checker.eval_statics().unwrap_err();
+
+        assert_eq!(error.get_message(), "function `pick` cannot be evaluated at compile time; only `let` and `return` statements are supported");
+    }
+}
@@ -0,0 +1,530 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ *, functions::{ FnSignature } };
+use crate::{ ast::{ * }, error::{ * }, lexer::{ Span, Spanned } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitMethodType {
+    pub name: &'static str,
+    // written in terms of `SimpleType::SelfType`
+    pub sig: FnSignature,
+    pub is_abstract: bool,
+    pub has_self: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitType {
+    pub name: &'static str,
+    pub methods: Vec<TraitMethodType>,
+    // kept around so that default methods can be checked once for every implementing type
+    pub decl: TraitDecl,
+    // the type arguments of an instantiated generic trait, e.g. `T = u8` for `Into<u8>`
+    pub bindings: Vec<(&'static str, Type)>,
+}
+
+impl TraitType {
+    pub fn get_method(&self, name: &str) -> Option<&TraitMethodType> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitImpl {
+    pub trait_name: &'static str,
+    pub for_type: Type,
+    // every method of the trait, default ones included, with `Self` substituted
+    pub methods: Vec<(&'static str, FnSignature)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Dispatch {
+    Static,
+    // the method is loaded from this slot of the receiver's vtable
+    Dynamic(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedMethod {
+    pub trait_name: &'static str,
+    pub for_type: Type,
+    pub name: &'static str,
+    pub sig: FnSignature,
+    pub dispatch: Dispatch,
+}
+
+impl ResolvedMethod {
+    // the name under which the concrete method body is emitted
+    pub fn symbol(&self) -> String {
+        match self.dispatch {
+            Dispatch::Static => format!("<{} as {}>.{}", self.for_type, self.trait_name, self.name),
+            Dispatch::Dynamic(_) => format!("<dyn {}>.{}", self.trait_name, self.name),
+        }
+    }
+}
+
+impl Display for ResolvedMethod {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}: {}", self.symbol(), self.sig)
+    }
+}
+
+impl Typechecker {
+    pub fn declare_traits(&mut self, decls: &[TraitDecl]) -> Checked<()> {
+        for decl in decls {
+            if self.traits.contains_key(decl.name.node) || self.trait_templates.contains_key(decl.name.node) {
+                return Err(Error::from_span(decl.name.span, ErrorType::NameError, &format!("trait `{}` is defined more than once", decl.name.node)).with_code(codes::DUPLICATE_DEFINITION));
+            }
+
+            // generic traits are instantiated once per list of type arguments they are used with
+            if !decl.generics.is_empty() {
+                self.with_generics(&decl.generics, |_| Ok(()))?;
+                self.trait_templates.insert(decl.name.node, decl.clone());
+
+                continue;
+            }
+
+            let previous = self.self_type.replace(Type::Simple(SimpleType::SelfType));
+            let methods = self.trait_methods(decl);
+
+            self.self_type = previous;
+
+            let trait_type = TraitType {
+                name: decl.name.node,
+                methods: methods?,
+                decl: decl.clone(),
+                bindings: vec![],
+            };
+
+            self.traits.insert(decl.name.node, trait_type);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn trait_methods(&mut self, decl: &TraitDecl) -> Checked<Vec<TraitMethodType>> {
+        let mut methods: Vec<TraitMethodType> = Vec::with_capacity(decl.methods.len());
+
+        for method in &decl.methods {
+            let name = method.decl.name;
+
+            if methods.iter().any(|other| other.name == name.node) {
+                return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("method `{}` is declared more than once in trait `{}`", name.node, decl.name.node)));
+            }
+
+            match (method.is_abstract, &method.decl.body) {
+                (true, Some(_)) => return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("abstract method `{}` cannot have a body", name.node))),
+                (false, None) => return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("method `{}` needs a body or must be declared `abstract`", name.node))),
+                _ => {},
+            }
+
+            methods.push(TraitMethodType {
+                name: name.node,
+                sig: self.signature_of(&method.decl)?,
+                is_abstract: method.is_abstract,
+                has_self: method.decl.params.first().is_some_and(|param| param.name.node == "self"),
+            });
+        }
+
+        Ok(methods)
+    }
+
+    pub fn trait_type(&self, name: &str) -> Option<&TraitType> {
+        self.traits.get(name)
+    }
+
+    // every impl in declaration order, default methods included
+    pub fn trait_impls(&self) -> &[TraitImpl] {
+        &self.impls
+    }
+
+    // puts `Self`, the trait and its type parameters in scope while the impl's bodies are checked or
+    // compiled; the returned state is handed back to `leave_impl`
+    pub fn enter_impl(&mut self, imp: &TraitImpl) -> (Option<Type>, Option<&'static str>, usize) {
+        let depth = self.type_params.len();
+        let bindings = self.traits[imp.trait_name].bindings.clone();
+
+        self.type_params.extend(bindings);
+
+        (self.self_type.replace(imp.for_type), self.impl_trait.replace(imp.trait_name), depth)
+    }
+
+    pub fn leave_impl(&mut self, (self_type, impl_trait, depth): (Option<Type>, Option<&'static str>, usize)) {
+        self.self_type = self_type;
+        self.impl_trait = impl_trait;
+        self.type_params.truncate(depth);
+    }
+
+    pub fn implements(&self, ty: Type, trait_name: &str) -> bool {
+        self.impls.iter().any(|imp| imp.trait_name == trait_name && imp.for_type == ty)
+    }
+
+    // an impl only stays registered if the bodies of all impls declared with it check
+    pub fn declare_impls(&mut self, decls: &[ImplDecl]) -> Checked<()> {
+        let registered = self.impls.len();
+        let checked = self.register_impls(decls).and_then(|()| self.check_impls(decls));
+
+        if checked.is_err() {
+            self.impls.truncate(registered);
+        }
+
+        checked
+    }
+
+    // impls are registered with their signatures before any body is checked, so that methods and
+    // functions can call each other whatever order they are declared in
+    pub fn register_impls(&mut self, decls: &[ImplDecl]) -> Checked<()> {
+        for decl in decls {
+            self.register_impl(decl)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn check_impls(&mut self, decls: &[ImplDecl]) -> Checked<()> {
+        for decl in decls {
+            self.check_impl(decl)?;
+        }
+
+        Ok(())
+    }
+
+    fn register_impl(&mut self, decl: &ImplDecl) -> Checked<()> {
+        let trait_name = self.resolve_trait_ref(decl.trait_name, &decl.trait_args)?;
+        let trait_type = self.traits[trait_name].clone();
+        let for_type = self.resolve_type(&decl.for_type)?;
+
+        if self.implements(for_type, trait_name) {
+            return Err(Error::from_span(decl.for_type.span, ErrorType::TypeError, &format!("conflicting implementations of trait `{}` for type `{}`", trait_name, for_type)));
+        }
+
+        let previous = self.self_type.replace(for_type);
+        let methods = self.impl_methods(decl, &trait_type, for_type);
+
+        self.self_type = previous;
+
+        self.impls.push(TraitImpl {
+            trait_name,
+            for_type,
+            methods: methods?,
+        });
+
+        Ok(())
+    }
+
+    fn check_impl(&mut self, decl: &ImplDecl) -> Checked<()> {
+        let trait_name = self.resolve_trait_ref(decl.trait_name, &decl.trait_args)?;
+        let trait_type = self.traits[trait_name].clone();
+        let for_type = self.resolve_type(&decl.for_type)?;
+        let imp = self.impls.iter().find(|imp| imp.trait_name == trait_name && imp.for_type == for_type).expect("impl was registered before its bodies are checked").clone();
+
+        let state = self.enter_impl(&imp);
+        let checked = self.check_impl_bodies(decl, &trait_type, &imp.methods);
+
+        self.leave_impl(state);
+
+        checked
+    }
+
+    fn impl_methods(&mut self, decl: &ImplDecl, trait_type: &TraitType, for_type: Type) -> Checked<Vec<(&'static str, FnSignature)>> {
+        let mut methods: Vec<(&'static str, FnSignature)> = Vec::with_capacity(trait_type.methods.len());
+
+        for method in &decl.methods {
+            let name = method.name;
+            let Some(expected) = trait_type.get_method(name.node) else {
+                return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("method `{}` is not a member of trait `{}`", name.node, trait_type.name)));
+            };
+
+            if methods.iter().any(|(other, _)| *other == name.node) {
+                return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("method `{}` is implemented more than once", name.node)));
+            }
+
+            let expected = self.substitute_self(&expected.sig, for_type, name.span)?;
+            let sig = self.signature_of(method)?;
+
+            if sig != expected {
+                return Err(Error::from_span(name.span, ErrorType::TypeError, &format!("method `{}` has signature `{}` but trait `{}` requires `{}`", name.node, sig, trait_type.name, expected)));
+            }
+
+            methods.push((name.node, sig));
+        }
+
+        let missing: Vec<&str> = trait_type.methods.iter().filter(|method| method.is_abstract && !methods.iter().any(|(name, _)| *name == method.name)).map(|method| method.name).collect();
+
+        if !missing.is_empty() {
+            return Err(Error::from_span(decl.trait_name.span, ErrorType::TypeError, &format!("not all abstract methods of trait `{}` are implemented for `{}`, missing: `{}`", trait_type.name, for_type, missing.join("`, `"))));
+        }
+
+        for default in trait_type.decl.methods.iter().filter(|method| !method.is_abstract) {
+            if !methods.iter().any(|(name, _)| *name == default.decl.name.node) {
+                let sig = self.substitute_self(&trait_type.get_method(default.decl.name.node).expect("trait method exists").sig, for_type, default.decl.name.span)?;
+
+                methods.push((default.decl.name.node, sig));
+            }
+        }
+
+        Ok(methods)
+    }
+
+    fn check_impl_bodies(&mut self, decl: &ImplDecl, trait_type: &TraitType, methods: &[(&'static str, FnSignature)]) -> Checked<()> {
+        for (name, sig) in methods {
+            // inherited default methods are checked against every type that implements the trait
+            match decl.methods.iter().find(|method| method.name.node == *name) {
+                Some(method) => self.check_function(method, sig)?,
+                None => self.check_function(&trait_type.decl.methods.iter().find(|method| method.decl.name.node == *name).expect("default method exists").decl, sig)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn substitute_self(&self, sig: &FnSignature, for_type: Type, span: Span) -> Checked<FnSignature> {
+        sig.substitute(SimpleType::SelfType, for_type, &self.function_types).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("`Self` cannot be replaced by `{}` in `{}`", for_type, sig))
+        })
+    }
+
+    // finds the single trait method `name` applicable to `receiver`, looking through one pointer if needed
+    pub fn resolve_method(&self, receiver: Type, name: &str, span: Span) -> Checked<ResolvedMethod> {
+        let pointee = receiver.pointee();
+
+        if let Some(Type::Simple(SimpleType::TraitObject(trait_name))) = pointee {
+            return self.resolve_dynamic_method(trait_name, name, span);
+        }
+
+        // a type parameter only has the methods of the traits it is bounded by
+        if let Some(param) = [ Some(receiver), pointee ].into_iter().find_map(|ty| match ty {
+            Some(Type::Simple(SimpleType::Generic(param))) => Some(param),
+            _ => None,
+        }) {
+            let for_type = Type::Simple(SimpleType::Generic(param));
+            let candidates: Vec<ResolvedMethod> = self.bounds_of(param).into_iter().filter_map(|trait_name| {
+                let method = self.traits[trait_name].get_method(name)?;
+
+                Some(ResolvedMethod {
+                    trait_name,
+                    for_type,
+                    name: method.name,
+                    sig: method.sig.substitute(SimpleType::SelfType, for_type, &self.function_types)?,
+                    dispatch: Dispatch::Static,
+                })
+            }).collect();
+
+            return Self::single_candidate(candidates, receiver, name, span);
+        }
+
+        let mut candidates: Vec<ResolvedMethod> = self.impls.iter()
+            .filter(|imp| imp.for_type == receiver || Some(imp.for_type) == pointee)
+            .filter_map(|imp| {
+                let (method, sig) = imp.methods.iter().find(|(method, _)| *method == name)?;
+
+                Some(ResolvedMethod {
+                    trait_name: imp.trait_name,
+                    for_type: imp.for_type,
+                    name: method,
+                    sig: sig.clone(),
+                    dispatch: Dispatch::Static,
+                })
+            })
+            .collect();
+
+        // inside an impl, a method of its own trait called on `Self` wins over same-named methods of other traits
+        let on_self = self.self_type.is_some_and(|ty| ty == receiver || Some(ty) == pointee);
+
+        if on_self && candidates.len() > 1 && candidates.iter().any(|candidate| Some(candidate.trait_name) == self.impl_trait) {
+            candidates.retain(|candidate| Some(candidate.trait_name) == self.impl_trait);
+        }
+
+        Self::single_candidate(candidates, receiver, name, span)
+    }
+
+    fn single_candidate(candidates: Vec<ResolvedMethod>, receiver: Type, name: &str, span: Span) -> Checked<ResolvedMethod> {
+        match candidates.len() {
+            0 => Err(Error::from_span(span, ErrorType::TypeError, &format!("no method named `{}` found for type `{}`", name, receiver))),
+            1 => Ok(candidates.into_iter().next().expect("exactly one candidate")),
+
+            _ => {
+                let traits: Vec<&str> = candidates.iter().map(|candidate| candidate.trait_name).collect();
+
+                Err(Error::from_span(span, ErrorType::TypeError, &format!("multiple methods named `{}` apply to `{}`, from traits `{}`", name, receiver, traits.join("`, `"))))
+            },
+        }
+    }
+
+    pub(super) fn check_method_call(&mut self, receiver: &Expr, method: Spanned<&'static str>, args: &[Expr], span: Span) -> Checked<Type> {
+        let receiver_type = self.check_expr(receiver, None)?;
+
+        Self::check_non_null(receiver_type, receiver.span)?;
+        let resolved = self.resolve_method(receiver_type, method.node, method.span)?;
+
+        let has_self = self.traits[resolved.trait_name].get_method(resolved.name).is_some_and(|method| method.has_self);
+
+        if !has_self {
+            return Err(Error::from_span(method.span, ErrorType::TypeError, &format!("`{}` has no `self` parameter and cannot be called as a method", resolved.symbol())));
+        }
+
+        // the receiver is passed as is, by address or through its pointer
+        let self_param = resolved.sig.params[0];
+        let adjusted = if receiver_type.pointer_to_mut() == Some(self_param) {
+            self.check_mutable_place(receiver)?;
+
+            true
+        } else {
+            self.can_coerce(receiver_type, self_param) || receiver_type.pointer_to() == Some(self_param) || receiver_type.pointee() == Some(self_param)
+        };
+
+        if !adjusted {
+            return Err(Error::from_span(receiver.span, ErrorType::TypeError, &format!("`{}` expects `self` of type `{}`, found `{}`", resolved.symbol(), self_param, receiver_type)));
+        }
+
+        self.check_args(&resolved.sig.params[1 ..], resolved.sig.is_variadic, args, span)?;
+
+        Ok(resolved.sig.ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, types::{ * } };
+
+    fn self_ptr() -> Param {
+        Param {
+            name: Spanned::new(0, 0, "self"),
+            ty: Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::SelfType)))),
+        }
+    }
+
+    fn method_call(receiver: &'static str, method: &'static str) -> Expr {
+        Spanned::new(0, 0, ExprKind::MethodCall(Box::new(Spanned::new(0, 0, ExprKind::Identifier(receiver))), Spanned::new(0, 0, method), vec![]))
+    }
+
+    fn method(name: &'static str, value: Expr) -> FnDecl {
+        FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, name),
+            generics: vec![],
+            params: vec![ self_ptr() ],
+            ret: Some(i32_type()),
+            body: Some(vec![ Spanned::new(0, 0, StmtKind::Return(Some(value))) ]),
+        }
+    }
+
+    fn abstract_method(name: &'static str) -> TraitMethod {
+        TraitMethod {
+            is_abstract: true,
+            decl: FnDecl { body: None, ..method(name, Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))) },
+        }
+    }
+
+    fn impl_for(trait_name: &'static str, ty: &'static str, methods: Vec<FnDecl>) -> ImplDecl {
+        ImplDecl {
+            trait_name: Spanned::new(0, 0, trait_name),
+            trait_args: vec![],
+            for_type: Spanned::new(0, 0, TypeExprKind::Named(ty, vec![])),
+            methods,
+        }
+    }
+
+    // trait Shape { abstract fn area(self: *Self) -> i32; fn double(self: *Self) -> i32 { return self.area() * 2; } }
+    fn checker_with_shape() -> Typechecker {
+        let mut checker = Typechecker::new("main");
+        let square = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Square"),
+            generics: vec![],
+            fields: vec![ StructField { is_pub: false, name: "side", ty: i32_type() } ],
+        };
+
+        let double = Spanned::new(0, 0, ExprKind::Binary(Box::new(method_call("self", "area")), BinaryOp::Mul, Box::new(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(2, None))))));
+        let shape = TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Shape"),
+            generics: vec![],
+            methods: vec![ abstract_method("area"), TraitMethod { is_abstract: false, decl: method("double", double) } ],
+        };
+
+        checker.declare_structs(&[ square ]).unwrap();
+        checker.declare_traits(&[ shape ]).unwrap();
+        checker.declare_variable("sq", Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Square"))));
+
+        checker
+    }
+
+    fn side() -> Expr {
+        Spanned::new(0, 0, ExprKind::Field(Box::new(Spanned::new(0, 0, ExprKind::Identifier("self"))), "side"))
+    }
+
+    #[test]
+    fn default_methods_dispatch_statically() {
+        let mut checker = checker_with_shape();
+
+        checker.declare_impls(&[ impl_for("Shape", "Square", vec![ method("area", side()) ]) ]).unwrap();
+
+        let resolved = checker.resolve_method(checker.lookup_variable("sq").unwrap(), "double", Span::new(0, 0)).unwrap();
+
+        assert_eq!(resolved.symbol(), "<main.Square as Shape>.double");
+        assert_eq!(checker.check_expr(&method_call("sq", "double"), None).unwrap(), Type::Simple(SimpleType::signed(SignedIntegerTypes::Int32)));
+    }
+
+    #[test]
+    fn incomplete_and_duplicate_impls_are_rejected() {
+        let mut checker = checker_with_shape();
+
+        let err = checker.declare_impls(&[ impl_for("Shape", "Square", vec![]) ]).unwrap_err();
+
+        assert!(err.get_message().contains("missing: `area`"));
+
+        let mut checker = checker_with_shape();
+        let imp = impl_for("Shape", "Square", vec![ method("area", side()) ]);
+
+        assert!(checker.declare_impls(&[ imp.clone(), imp ]).is_err());
+    }
+
+    #[test]
+    fn ambiguous_methods_are_rejected() {
+        let mut checker = checker_with_shape();
+        let sized = TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Sized"),
+            generics: vec![],
+            methods: vec![ abstract_method("area") ],
+        };
+
+        checker.declare_traits(&[ sized ]).unwrap();
+        checker.declare_impls(&[
+            impl_for("Shape", "Square", vec![ method("area", side()) ]),
+            impl_for("Sized", "Square", vec![ method("area", side()) ]),
+        ]).unwrap();
+
+        assert!(checker.check_expr(&method_call("sq", "area"), None).is_err());
+    }
+
+    #[test]
+    fn impls_whose_bodies_fail_are_not_kept() {
+        let mut checker = checker_with_shape();
+        let square = checker.lookup_variable("sq").unwrap();
+        let wrong = Spanned::new(0, 0, ExprKind::Literal(Literal::Bool(true)));
+
+        assert!(checker.declare_impls(&[ impl_for("Shape", "Square", vec![ method("area", wrong) ]) ]).is_err());
+        assert!(!checker.implements(square, "Shape"));
+
+        checker.declare_impls(&[ impl_for("Shape", "Square", vec![ method("area", side()) ]) ]).unwrap();
+
+        assert!(checker.implements(square, "Shape"));
+    }
+
+    #[test]
+    fn bodies_may_call_what_is_declared_after_them() {
+        let mut checker = checker_with_shape();
+        // impl Shape for Square { fn area(self: *Self) -> i32 { return unit(); } }  fn unit() -> i32 { return 1; }
+        let imp = impl_for("Shape", "Square", vec![ method("area", call("unit", vec![])) ]);
+        let unit = FnDecl { params: vec![], ..method("unit", int(1)) };
+
+        checker.register_impls(std::slice::from_ref(&imp)).unwrap();
+        checker.register_functions(std::slice::from_ref(&unit)).unwrap();
+
+        assert!(checker.check_impls(&[ imp ]).is_ok());
+        assert!(checker.check_functions(&[ unit ]).is_ok());
+    }
+}
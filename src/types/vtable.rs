@@ -0,0 +1,226 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ *, layout::{ Layout }, traits::{ Dispatch, ResolvedMethod, TraitMethodType } };
+use crate::{ error::{ * }, lexer::{ Span } };
+
+type Checked<T> = std::result::Result<T, Error>;
+
+// every vtable starts with the size and alignment of the concrete type behind the pointer
+pub const VTABLE_HEADER_SLOTS: usize = 2;
+pub const VTABLE_SLOT_SIZE: u64 = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VTable {
+    pub trait_name: &'static str,
+    pub for_type: Type,
+    pub type_layout: Layout,
+    // one symbol per trait method, in declaration order, starting at slot `VTABLE_HEADER_SLOTS`
+    pub methods: Vec<String>,
+}
+
+impl VTable {
+    pub fn slot_of(&self, method: usize) -> usize {
+        VTABLE_HEADER_SLOTS + method
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout::new((VTABLE_HEADER_SLOTS + self.methods.len()) as u64 * VTABLE_SLOT_SIZE, VTABLE_SLOT_SIZE)
+    }
+}
+
+impl Display for VTable {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        writeln!(f, "vtable for `{}` as `dyn {}`:", self.for_type, self.trait_name)?;
+        writeln!(f, "  [0] size = {}", self.type_layout.size)?;
+        write!(f, "  [1] align = {}", self.type_layout.align)?;
+
+        for (index, method) in self.methods.iter().enumerate() {
+            write!(f, "\n  [{}] {}", self.slot_of(index), method)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Typechecker {
+    // a trait can be used as `dyn Trait` if every method takes `self: *Self` and mentions `Self` nowhere else
+    pub fn check_object_safety(&self, trait_name: &str, span: Span) -> Checked<()> {
+        let trait_type = self.traits.get(trait_name).ok_or_else(|| {
+            Error::from_span(span, ErrorType::NameError, &format!("unknown trait `{}`", trait_name)).with_code(codes::UNDEFINED_NAME)
+        })?;
+
+        for method in &trait_type.methods {
+            if let Some(reason) = Self::object_safety_violation(method) {
+                return Err(Error::from_span(span, ErrorType::TypeError, &format!("trait `{}` cannot be made into an object: method `{}` {}", trait_name, method.name, reason)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn object_safety_violation(method: &TraitMethodType) -> Option<&'static str> {
+        let takes_self_pointer = matches!(method.sig.params.first(), Some(Type::Complex(ComplexType::Pointer(Pointer { references: 1, base_type: SimpleType::SelfType, .. }))));
+
+        if !method.has_self || !takes_self_pointer {
+            return Some("does not take `self` as `*Self` or `*mut Self`");
+        }
+
+        let mentions_self = |ty: &Type| ty.mentions(SimpleType::SelfType);
+
+        if method.sig.params[1 ..].iter().any(mentions_self) || mentions_self(&method.sig.ret) {
+            return Some("uses `Self` outside of its receiver");
+        }
+
+        None
+    }
+
+    pub fn vtable(&self, trait_name: &'static str, for_type: Type, span: Span) -> Checked<VTable> {
+        self.check_object_safety(trait_name, span)?;
+
+        if !self.implements(for_type, trait_name) {
+            return Err(Error::from_span(span, ErrorType::TypeError, &format!("type `{}` does not implement trait `{}`", for_type, trait_name)));
+        }
+
+        let methods = self.traits[trait_name].methods.iter().map(|method| {
+            let resolved = ResolvedMethod {
+                trait_name,
+                for_type,
+                name: method.name,
+                sig: method.sig.clone(),
+                dispatch: Dispatch::Static,
+            };
+
+            resolved.symbol()
+        }).collect();
+
+        Ok(VTable {
+            trait_name,
+            for_type,
+            type_layout: self.layout_of(for_type, span)?,
+            methods,
+        })
+    }
+
+    pub(super) fn resolve_dynamic_method(&self, trait_name: &'static str, name: &str, span: Span) -> Checked<ResolvedMethod> {
+        let trait_type = &self.traits[trait_name];
+        let (index, method) = trait_type.methods.iter().enumerate().find(|(_, method)| method.name == name).ok_or_else(|| {
+            Error::from_span(span, ErrorType::TypeError, &format!("no method named `{}` found for `dyn {}`", name, trait_name))
+        })?;
+
+        let object = Type::Simple(SimpleType::TraitObject(trait_name));
+
+        Ok(ResolvedMethod {
+            trait_name,
+            for_type: object,
+            name: method.name,
+            sig: method.sig.substitute(SimpleType::SelfType, object, &self.function_types).expect("object safe methods only use `Self` behind a pointer"),
+            dispatch: Dispatch::Dynamic(VTABLE_HEADER_SLOTS + index),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ ast::{ *, fixtures::{ * } }, lexer::{ Span, Spanned }, types::{ *, layout::{ Layout }, traits::{ Dispatch } } };
+
+    fn self_ptr() -> TypeExpr {
+        Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::SelfType))))
+    }
+
+    fn method(name: &'static str, params: Vec<TypeExpr>, body: Option<Block>) -> FnDecl {
+        let names = [ "self", "other" ];
+
+        FnDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, name),
+            generics: vec![],
+            params: params.into_iter().zip(names).map(|(ty, name)| Param { name: Spanned::new(0, 0, name), ty }).collect(),
+            ret: Some(i32_type()),
+            body,
+        }
+    }
+
+    fn returns_zero() -> Option<Block> {
+        Some(vec![ Spanned::new(0, 0, StmtKind::Return(Some(Spanned::new(0, 0, ExprKind::Literal(Literal::Int(0, None)))))) ])
+    }
+
+    fn checker() -> Typechecker {
+        let mut checker = Typechecker::new("main");
+        let shape = TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Shape"),
+            generics: vec![],
+            methods: vec![
+                TraitMethod { is_abstract: true, decl: method("area", vec![ self_ptr() ], None) },
+                TraitMethod { is_abstract: true, decl: method("sides", vec![ self_ptr() ], None) },
+            ],
+        };
+
+        let eq = TraitDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Eq"),
+            generics: vec![],
+            methods: vec![ TraitMethod { is_abstract: true, decl: method("eq", vec![ self_ptr(), self_ptr() ], None) } ],
+        };
+
+        let square = StructDecl {
+            is_pub: false,
+            name: Spanned::new(0, 0, "Square"),
+            generics: vec![],
+            fields: vec![ StructField { is_pub: false, name: "side", ty: i32_type() } ],
+        };
+
+        let imp = ImplDecl {
+            trait_name: Spanned::new(0, 0, "Shape"),
+            trait_args: vec![],
+            for_type: Spanned::new(0, 0, TypeExprKind::Named("Square", vec![])),
+            methods: vec![ method("area", vec![ self_ptr() ], returns_zero()), method("sides", vec![ self_ptr() ], returns_zero()) ],
+        };
+
+        checker.declare_structs(&[ square ]).unwrap();
+        checker.declare_traits(&[ shape, eq ]).unwrap();
+        checker.declare_impls(&[ imp ]).unwrap();
+
+        checker
+    }
+
+    fn dyn_pointer(name: &'static str) -> TypeExpr {
+        Spanned::new(0, 0, TypeExprKind::Pointer(1, false, Box::new(Spanned::new(0, 0, TypeExprKind::Dyn(name)))))
+    }
+
+    #[test]
+    fn vtable_lists_methods_after_header() {
+        let checker = checker();
+        let square = Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Square")));
+        let vtable = checker.vtable("Shape", square, Span::new(0, 0)).unwrap();
+
+        assert_eq!(vtable.type_layout, Layout::new(4, 4));
+        assert_eq!(vtable.methods, vec![ "<main.Square as Shape>.area", "<main.Square as Shape>.sides" ]);
+        assert_eq!(vtable.slot_of(1), 3);
+        assert_eq!(vtable.layout(), Layout::new(32, 8));
+    }
+
+    #[test]
+    fn dynamic_calls_go_through_vtable_slots() {
+        let mut checker = checker();
+        let object = checker.resolve_type(&dyn_pointer("Shape")).unwrap();
+        let square = Type::Simple(SimpleType::UserType(UserDefinedType::new("main", "Square")));
+
+        assert_eq!(object.to_string(), "*dyn Shape");
+        assert_eq!(checker.layout_of(object, Span::new(0, 0)).unwrap(), Layout::new(16, 8));
+        assert!(checker.can_coerce(square.pointer_to().unwrap(), object));
+
+        let resolved = checker.resolve_method(object, "sides", Span::new(0, 0)).unwrap();
+
+        assert_eq!(resolved.dispatch, Dispatch::Dynamic(3));
+        assert_eq!(resolved.symbol(), "<dyn Shape>.sides");
+    }
+
+    #[test]
+    fn traits_using_self_are_not_object_safe() {
+        let mut checker = checker();
+        let err = checker.resolve_type(&dyn_pointer("Eq")).unwrap_err();
+
+        assert_eq!(err.get_message(), "trait `Eq` cannot be made into an object: method `eq` uses `Self` outside of its receiver");
+    }
+}
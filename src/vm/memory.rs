@@ -0,0 +1,348 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ value::{ Value } };
+use crate::{ bytecode::{ ValueType }, error::{ * }, lexer::{ Span }, types::{ *, half::{ F16 } } };
+
+type Accessed<T> = std::result::Result<T, Error>;
+
+// a byte inside a block; block 0 is never allocated, so it doubles as null
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Address {
+    pub block: u32,
+    pub offset: u32,
+}
+
+impl Address {
+    pub const NULL: Self = Self { block: 0, offset: 0 };
+
+    pub fn is_null(&self) -> bool {
+        self.block == 0
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self.is_null() {
+            true => write!(f, "null"),
+            false => write!(f, "#{}+{}", self.block, self.offset),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BlockKind {
+    // the bytes of a string or vtable constant, which cannot be written
+    Constant,
+    // memory of a call frame, freed when it returns
+    Stack,
+    Heap,
+    // the captures of a closure or an adapter; a closure may outlive the frame that made it and nothing
+    // tracks its copies, so environments are never freed and a debug heap reports them with the leaks
+    Environment,
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    bytes: Vec<u8>,
+    kind: BlockKind,
+    is_live: bool,
+    allocated_at: Span,
+    freed_at: Option<Span>,
+}
+
+// a heap block or closure environment nothing freed, reported by the allocation that made it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Leak {
+    pub address: Address,
+    pub kind: BlockKind,
+    pub size: u32,
+    pub allocated_at: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Memory {
+    blocks: Vec<Block>,
+    // freed blocks whose numbers are handed out again, so a pointer into one may see a later allocation
+    reusable: Vec<u32>,
+    // freed blocks are never reused, which makes every use after free and double free detectable
+    is_debug: bool,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![ Block {
+                bytes: vec![],
+                kind: BlockKind::Constant,
+                is_live: false,
+                allocated_at: Span::new(0, 0),
+                freed_at: None,
+            } ],
+            reusable: vec![],
+            is_debug: false,
+        }
+    }
+
+    pub fn with_debug(self) -> Self {
+        Self {
+            is_debug: true,
+            ..self
+        }
+    }
+
+    pub fn is_debug(&self) -> bool {
+        self.is_debug
+    }
+
+    // a fresh block of `size` zeroed bytes, allocated by the instruction at `span`
+    pub fn allocate(&mut self, size: u32, kind: BlockKind, span: Span) -> Address {
+        let block = Block {
+            bytes: vec![ 0; size as usize ],
+            kind,
+            is_live: true,
+            allocated_at: span,
+            freed_at: None,
+        };
+
+        let index = match self.reusable.pop() {
+            Some(index) => {
+                self.blocks[index as usize] = block;
+                index
+            },
+
+            None => {
+                self.blocks.push(block);
+                (self.blocks.len() - 1) as u32
+            },
+        };
+
+        Address {
+            block: index,
+            offset: 0,
+        }
+    }
+
+    // a constant block holding `bytes`
+    pub fn allocate_constant(&mut self, bytes: Vec<u8>, span: Span) -> Address {
+        let address = self.allocate(bytes.len() as u32, BlockKind::Constant, span);
+
+        self.blocks[address.block as usize].bytes = bytes;
+
+        address
+    }
+
+    pub fn free(&mut self, address: Address, kind: BlockKind, span: Span) -> Accessed<()> {
+        // checked before the block is looked up, so this is not reported as any other dangling pointer
+        if let Some(Block { is_live: false, allocated_at, freed_at: Some(freed_at), .. }) = self.blocks.get(address.block as usize) {
+            return Err(Error::from_span(span, ErrorType::RuntimeError, &format!("double free of {}: it was allocated at {} and already freed at {}", address, allocated_at, freed_at)).with_code(codes::DOUBLE_FREE));
+        }
+
+        let block = self.block(address, span)?;
+
+        if block.kind != kind || address.offset != 0 {
+            return Err(Error::from_span(span, ErrorType::RuntimeError, &format!("cannot free {}, it does not point to the start of a {} block", address, kind_name(kind))));
+        }
+
+        let block = &mut self.blocks[address.block as usize];
+
+        block.is_live = false;
+        block.freed_at = Some(span);
+
+        if !self.is_debug {
+            block.bytes = vec![];
+            self.reusable.push(address.block);
+        }
+
+        Ok(())
+    }
+
+    // how many blocks of any kind are allocated and not yet freed
+    pub fn live_blocks(&self) -> usize {
+        self.blocks.iter().filter(|block| block.is_live).count()
+    }
+
+    // the heap blocks and environments still allocated, ordered by where they were allocated
+    pub fn leaks(&self) -> Vec<Leak> {
+        let mut leaks: Vec<Leak> = self.blocks.iter().enumerate()
+            .filter(|(_, block)| block.is_live && matches!(block.kind, BlockKind::Heap | BlockKind::Environment))
+            .map(|(index, block)| Leak {
+                address: Address { block: index as u32, offset: 0 },
+                kind: block.kind,
+                size: block.bytes.len() as u32,
+                allocated_at: block.allocated_at,
+            })
+            .collect();
+
+        leaks.sort_by_key(|leak| (leak.allocated_at.start, leak.allocated_at.end, leak.address.block));
+        leaks
+    }
+
+    // the number of bytes from `address` to the end of its block
+    pub fn remaining(&self, address: Address, span: Span) -> Accessed<u32> {
+        let block = self.block(address, span)?;
+
+        Ok((block.bytes.len() as u32).saturating_sub(address.offset))
+    }
+
+    pub fn read(&self, address: Address, ty: ValueType, span: Span) -> Accessed<Value> {
+        let bytes = self.bytes(address, ty.size(), span)?;
+
+        Ok(decode(bytes, ty))
+    }
+
+    pub fn write(&mut self, address: Address, ty: ValueType, value: Value, span: Span) -> Accessed<()> {
+        let bytes = encode(value, ty);
+
+        self.bytes_mut(address, bytes.len() as u32, span)?.copy_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    pub fn copy(&mut self, destination: Address, source: Address, size: u32, span: Span) -> Accessed<()> {
+        let bytes = self.bytes(source, size, span)?.to_vec();
+
+        self.bytes_mut(destination, size, span)?.copy_from_slice(&bytes);
+
+        Ok(())
+    }
+
+    fn block(&self, address: Address, span: Span) -> Accessed<&Block> {
+        if address.is_null() {
+            return Err(Error::from_span(span, ErrorType::RuntimeError, "null pointer dereference").with_code(codes::NULL_DEREFERENCE));
+        }
+
+        match self.blocks.get(address.block as usize) {
+            Some(block) if block.is_live => Ok(block),
+
+            Some(Block { kind, allocated_at, freed_at: Some(freed_at), .. }) => {
+                Err(Error::from_span(span, ErrorType::RuntimeError, &format!("dangling pointer {}: its {} block allocated at {} was freed at {}", address, kind_name(*kind), allocated_at, freed_at)).with_code(codes::USE_AFTER_FREE))
+            },
+
+            Some(_) | None => Err(Error::from_span(span, ErrorType::InternalError, &format!("{} points to a block that was never allocated", address))),
+        }
+    }
+
+    fn bytes(&self, address: Address, size: u32, span: Span) -> Accessed<&[u8]> {
+        let block = self.block(address, span)?;
+        let range = address.offset as usize .. address.offset as usize + size as usize;
+
+        block.bytes.get(range).ok_or_else(|| out_of_bounds(address, size, block.bytes.len(), span))
+    }
+
+    fn bytes_mut(&mut self, address: Address, size: u32, span: Span) -> Accessed<&mut [u8]> {
+        let block = self.block(address, span)?;
+
+        if block.kind == BlockKind::Constant {
+            return Err(Error::from_span(span, ErrorType::RuntimeError, &format!("cannot write to {}, it points to a constant", address)));
+        }
+
+        let length = block.bytes.len();
+        let range = address.offset as usize .. address.offset as usize + size as usize;
+
+        self.blocks[address.block as usize].bytes.get_mut(range).ok_or_else(|| out_of_bounds(address, size, length, span))
+    }
+}
+
+fn kind_name(kind: BlockKind) -> &'static str {
+    match kind {
+        BlockKind::Constant => "constant",
+        BlockKind::Stack => "stack",
+        BlockKind::Heap => "heap",
+        BlockKind::Environment => "environment",
+    }
+}
+
+fn out_of_bounds(address: Address, size: u32, length: usize, span: Span) -> Error {
+    Error::from_span(span, ErrorType::RuntimeError, &format!("access of {} bytes at offset {} is out of bounds of a {} byte block", size, address.offset, length)).with_code(codes::OUT_OF_BOUNDS)
+}
+
+// values are stored little-endian at the size of their type; pointers as their block and offset
+fn encode(value: Value, ty: ValueType) -> Vec<u8> {
+    let size = ty.size() as usize;
+
+    match (value, ty) {
+        (Value::Float(v), ValueType::Float(FloatingPointTypes::FP16)) => F16::from_f64(v).to_bits().to_le_bytes().to_vec(),
+        (Value::Float(v), ValueType::Float(FloatingPointTypes::FP32)) => (v as f32).to_le_bytes().to_vec(),
+        (Value::Float(v), _) => v.to_le_bytes().to_vec(),
+        (Value::Pointer(address), _) => [ address.block.to_le_bytes(), address.offset.to_le_bytes() ].concat(),
+        (Value::Function(index), _) => (index as u64).to_le_bytes().to_vec(),
+        (value, _) => value.as_i128().unwrap_or(0).to_le_bytes()[.. size].to_vec(),
+    }
+}
+
+fn decode(bytes: &[u8], ty: ValueType) -> Value {
+    let mut wide = [ 0u8; 8 ];
+
+    wide[.. bytes.len()].copy_from_slice(bytes);
+
+    let raw = u64::from_le_bytes(wide);
+
+    match ty {
+        ValueType::Int(ty) => Value::Int(((raw << (64 - ty.bits())) as i64) >> (64 - ty.bits())),
+        ValueType::Uint(_) => Value::Uint(raw),
+        ValueType::Float(FloatingPointTypes::FP16) => Value::Float(F16::from_bits(raw as u16).to_f64()),
+        ValueType::Float(FloatingPointTypes::FP32) => Value::Float(f32::from_bits(raw as u32) as f64),
+        ValueType::Float(FloatingPointTypes::FP64) => Value::Float(f64::from_bits(raw)),
+        ValueType::Bool => Value::Bool(raw != 0),
+        ValueType::Char => Value::Char(char::from_u32(raw as u32).unwrap_or(char::REPLACEMENT_CHARACTER)),
+        ValueType::Pointer => Value::Pointer(Address { block: raw as u32, offset: (raw >> 32) as u32 }),
+        ValueType::Function => Value::Function(raw as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+
+    const INT: ValueType = ValueType::Int(SignedIntegerTypes::Int32);
+
+    #[test]
+    fn freed_blocks_are_reused_outside_debug_mode() {
+        let mut memory = Memory::new();
+        let first = memory.allocate(4, BlockKind::Heap, Span::new(1, 2));
+
+        memory.free(first, BlockKind::Heap, Span::new(3, 4)).unwrap();
+
+        let second = memory.allocate(8, BlockKind::Heap, Span::new(5, 6));
+
+        assert_eq!(first, second);
+        assert_eq!(memory.leaks(), vec![ Leak { address: second, kind: BlockKind::Heap, size: 8, allocated_at: Span::new(5, 6) } ]);
+    }
+
+    #[test]
+    fn debug_mode_reports_uses_after_free_and_double_frees_with_their_sites() {
+        let mut memory = Memory::new().with_debug();
+        let address = memory.allocate(4, BlockKind::Heap, Span::new(1, 2));
+
+        memory.write(address, INT, Value::Int(1), Span::new(3, 4)).unwrap();
+        memory.free(address, BlockKind::Heap, Span::new(5, 6)).unwrap();
+
+        assert_ne!(memory.allocate(4, BlockKind::Heap, Span::new(7, 8)), address);
+
+        let error = memory.read(address, INT, Span::new(9, 10)).unwrap_err();
+
+        assert_eq!(error.get_code(), codes::USE_AFTER_FREE);
+        assert_eq!(error.get_message(), "dangling pointer #1+0: its heap block allocated at 1:2 was freed at 5:6");
+
+        let error = memory.free(address, BlockKind::Heap, Span::new(11, 12)).unwrap_err();
+
+        assert_eq!(error.get_code(), codes::DOUBLE_FREE);
+        assert_eq!(error.get_message(), "double free of #1+0: it was allocated at 1:2 and already freed at 5:6");
+    }
+
+    #[test]
+    fn chars_are_stored_as_whole_code_points() {
+        let mut memory = Memory::new();
+        let address = memory.allocate(ValueType::Char.size(), BlockKind::Heap, Span::new(1, 2));
+
+        memory.write(address, ValueType::Char, Value::Char('🦀'), Span::new(3, 4)).unwrap();
+
+        assert_eq!(memory.read(address, ValueType::Char, Span::new(5, 6)).unwrap(), Value::Char('🦀'));
+    }
+}
@@ -0,0 +1,678 @@
+use std::{ cmp::{ Ordering }, collections::{ HashMap }, fmt::{ Write } };
+
+use crate::{ bytecode::{ *, verify::{ verify } }, error::{ * }, lexer::{ Span } };
+
+pub mod memory;
+pub mod value;
+
+use memory::{ Address, BlockKind, Memory };
+use value::{ Value };
+
+type Executed<T> = std::result::Result<T, Error>;
+
+// how many calls may be nested before the program is stopped with a stack overflow
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
+
+#[derive(Debug, Clone)]
+struct Frame {
+    function: u32,
+    // the next instruction to execute
+    ip: usize,
+    locals: Vec<Option<Value>>,
+    // the operand stack height when the frame was entered
+    base: usize,
+    // the stack blocks the frame allocated, freed when it returns
+    allocations: Vec<Address>,
+}
+
+enum Flow {
+    Continue,
+    Finish(Option<Value>),
+}
+
+pub struct Vm<'a> {
+    program: &'a Program,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    memory: Memory,
+    max_depth: usize,
+    // the blocks of string and vtable constants, made when first used
+    constant_blocks: HashMap<u32, Address>,
+    // whether the program has passed the verifier, which it has to before any of it runs
+    is_verified: bool,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            stack: vec![],
+            frames: vec![],
+            memory: Memory::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            constant_blocks: HashMap::new(),
+            is_verified: false,
+        }
+    }
+
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            ..self
+        }
+    }
+
+    // catches every use after free and double free, and makes leaks reportable
+    pub fn with_debug_heap(self) -> Self {
+        Self {
+            memory: self.memory.with_debug(),
+            ..self
+        }
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    // in debug mode, the heap blocks and closure environments the program left allocated, grouped by the
+    // allocation that made them
+    pub fn leak_report(&self) -> Option<String> {
+        let leaks = self.memory.leaks();
+
+        if !self.memory.is_debug() || leaks.is_empty() {
+            return None;
+        }
+
+        let total: u64 = leaks.iter().map(|leak| leak.size as u64).sum();
+        let mut report = format!("leaked {} bytes in {} {}", total, leaks.len(), blocks(leaks.len()));
+
+        for site in leaks.chunk_by(|a, b| a.allocated_at == b.allocated_at) {
+            let size: u64 = site.iter().map(|leak| leak.size as u64).sum();
+
+            let what = match site[0].kind {
+                BlockKind::Environment => environments(site.len()),
+                _ => blocks(site.len()),
+            };
+
+            write!(report, "\n    {}: {} bytes in {} {}", site[0].allocated_at, size, site.len(), what).unwrap();
+        }
+
+        Some(report)
+    }
+
+    // calls the function named `name` and runs until it returns
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Executed<Option<Value>> {
+        let function = self.program.function_index(name).ok_or_else(|| {
+            Error::new(0, 0, ErrorType::RuntimeError, &format!("the program has no function named `{}`", name))
+        })?;
+
+        self.run(function, args)
+    }
+
+    pub fn run(&mut self, function: u32, args: &[Value]) -> Executed<Option<Value>> {
+        if !self.is_verified {
+            verify(self.program)?;
+            self.is_verified = true;
+        }
+
+        self.stack.clear();
+        self.stack.extend_from_slice(args);
+
+        let result = self.enter(function, args.len(), Span::new(0, 0)).and_then(|()| self.dispatch());
+
+        if let Err(error) = &result {
+            self.unwind(error.get_location());
+        }
+
+        result
+    }
+
+    fn dispatch(&mut self) -> Executed<Option<Value>> {
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let chunk = &self.program.functions[frame.function as usize].chunk;
+            let ip = frame.ip;
+
+            let Some(instruction) = chunk.get(ip) else {
+                return Err(Error::from_span(chunk.span_at(ip.saturating_sub(1)), ErrorType::InternalError, &format!("function `{}` ran past its last instruction", self.program.functions[frame.function as usize].name)));
+            };
+
+            frame.ip += 1;
+
+            if let Flow::Finish(result) = self.execute(instruction, chunk.span_at(ip))? {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn execute(&mut self, instruction: Instruction, span: Span) -> Executed<Flow> {
+        match instruction {
+            Instruction::Const(index) => {
+                let value = self.constant(index, span)?;
+
+                self.stack.push(value);
+            },
+
+            Instruction::Null => self.stack.push(Value::Pointer(Address::NULL)),
+            Instruction::Function(index) => self.stack.push(Value::Function(index)),
+
+            Instruction::Pop => {
+                self.pop(instruction, span)?;
+            },
+
+            Instruction::Dup => {
+                let value = self.pop(instruction, span)?;
+
+                self.stack.extend([ value, value ]);
+            },
+
+            Instruction::Load(slot) => {
+                let frame = self.frames.last().unwrap();
+
+                match frame.locals.get(slot as usize) {
+                    Some(Some(value)) => self.stack.push(*value),
+                    _ => return Err(Error::from_span(span, ErrorType::InternalError, &format!("local #{} of `{}` is read before it is written", slot, self.program.functions[frame.function as usize].name))),
+                }
+            },
+
+            Instruction::Store(slot) => {
+                let value = self.pop(instruction, span)?;
+                let frame = self.frames.last_mut().unwrap();
+
+                match frame.locals.get_mut(slot as usize) {
+                    Some(local) => *local = Some(value),
+                    None => return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` has no local #{}", self.program.functions[frame.function as usize].name, slot))),
+                }
+            },
+
+            Instruction::Add(ty) => self.arithmetic(instruction, ty, span, i128::wrapping_add, |a, b| a + b)?,
+            Instruction::Sub(ty) => self.arithmetic(instruction, ty, span, i128::wrapping_sub, |a, b| a - b)?,
+            Instruction::Mul(ty) => self.arithmetic(instruction, ty, span, i128::wrapping_mul, |a, b| a * b)?,
+            Instruction::Div(ty) => self.arithmetic(instruction, ty, span, i128::wrapping_div, |a, b| a / b)?,
+            Instruction::Rem(ty) => self.arithmetic(instruction, ty, span, i128::wrapping_rem, |a, b| a % b)?,
+            Instruction::BitAnd(ty) => self.arithmetic(instruction, ty, span, |a, b| a & b, |_, _| unreachable!())?,
+            Instruction::BitOr(ty) => self.arithmetic(instruction, ty, span, |a, b| a | b, |_, _| unreachable!())?,
+
+            Instruction::Neg(ty) => {
+                let value = self.pop_typed(instruction, ty.into(), span)?;
+
+                self.stack.push(match value {
+                    Value::Float(v) => Value::Float(-v),
+                    _ => Value::from_i128(ty, -value.as_i128().unwrap()),
+                });
+            },
+
+            Instruction::BitNot(ty) => {
+                let value = self.pop_integer(instruction, ty, span)?;
+
+                self.stack.push(Value::from_i128(ty, !value));
+            },
+
+            Instruction::Not => {
+                let Value::Bool(value) = self.pop_typed(instruction, ValueType::Bool, span)? else { unreachable!() };
+
+                self.stack.push(Value::Bool(!value));
+            },
+
+            Instruction::Eq(ty) | Instruction::Ne(ty) => {
+                let b = self.pop_typed(instruction, ty, span)?;
+                let a = self.pop_typed(instruction, ty, span)?;
+
+                self.stack.push(Value::Bool((a == b) == matches!(instruction, Instruction::Eq(_))));
+            },
+
+            Instruction::Lt(ty) => self.compare(instruction, ty, span, |ordering| ordering == Ordering::Less)?,
+            Instruction::Le(ty) => self.compare(instruction, ty, span, |ordering| ordering != Ordering::Greater)?,
+            Instruction::Gt(ty) => self.compare(instruction, ty, span, |ordering| ordering == Ordering::Greater)?,
+            Instruction::Ge(ty) => self.compare(instruction, ty, span, |ordering| ordering != Ordering::Less)?,
+
+            Instruction::Cast(from, to) => {
+                let value = self.pop_typed(instruction, from, span)?;
+                let cast = value.cast(to).ok_or_else(|| Error::from_span(span, ErrorType::InternalError, &format!("`{}` cannot convert {}", instruction, value)))?;
+
+                self.stack.push(cast);
+            },
+
+            Instruction::Jump(target) => self.jump(target, span)?,
+
+            Instruction::JumpIfFalse(target) | Instruction::JumpIfTrue(target) => {
+                let Value::Bool(condition) = self.pop_typed(instruction, ValueType::Bool, span)? else { unreachable!() };
+
+                if condition == matches!(instruction, Instruction::JumpIfTrue(_)) {
+                    self.jump(target, span)?;
+                }
+            },
+
+            Instruction::Call(function) => {
+                let argc = self.function(function, span)?.params.len();
+
+                self.enter(function, argc, span)?;
+            },
+
+            Instruction::CallIndirect(argc, ret) => {
+                let at = self.stack.len().checked_sub(argc as usize + 1).ok_or_else(|| underflow(instruction, span))?;
+
+                let Value::Function(function) = self.stack.remove(at) else {
+                    return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` expects a function below its arguments", instruction)));
+                };
+
+                // what the call leaves on the stack has to be what the caller was verified against
+                let callee = self.function(function, span)?;
+
+                if callee.ret != ret {
+                    let returns = callee.ret.map_or("nothing".to_owned(), |ret| format!("a {}", ret));
+
+                    return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` calls `{}`, which returns {}", instruction, callee.name, returns)));
+                }
+
+                self.enter(function, argc as usize, span)?;
+            },
+
+            Instruction::Return => {
+                let value = self.pop(instruction, span)?;
+
+                return self.leave(Some(value), span);
+            },
+
+            Instruction::ReturnVoid => return self.leave(None, span),
+
+            Instruction::StackAlloc(size) => {
+                let address = self.memory.allocate(size, BlockKind::Stack, span);
+
+                self.frames.last_mut().unwrap().allocations.push(address);
+                self.stack.push(Value::Pointer(address));
+            },
+
+            Instruction::New(size) => {
+                let address = self.memory.allocate(size, BlockKind::Heap, span);
+
+                self.stack.push(Value::Pointer(address));
+            },
+
+            Instruction::Env(size) => {
+                let address = self.memory.allocate(size, BlockKind::Environment, span);
+
+                self.stack.push(Value::Pointer(address));
+            },
+
+            Instruction::Delete => {
+                let address = self.pop_pointer(instruction, span)?;
+
+                // like in C, deleting null does nothing
+                if !address.is_null() {
+                    self.memory.free(address, BlockKind::Heap, span)?;
+                }
+            },
+
+            Instruction::Offset(offset) => {
+                let address = self.pop_non_null(instruction, span)?;
+
+                self.stack.push(Value::Pointer(Address {
+                    offset: address.offset.wrapping_add(offset),
+                    ..address
+                }));
+            },
+
+            Instruction::Index(stride) => {
+                let index = self.pop(instruction, span)?;
+                let address = self.pop_non_null(instruction, span)?;
+                let index = index.as_i128().ok_or_else(|| mismatch(instruction, "an integer", index, span))?;
+                let count = self.memory.remaining(address, span)? / stride.max(1);
+
+                if !(0 .. count as i128).contains(&index) {
+                    return Err(Error::from_span(span, ErrorType::RuntimeError, &format!("index {} is out of bounds for {} elements", index, count)).with_code(codes::OUT_OF_BOUNDS));
+                }
+
+                self.stack.push(Value::Pointer(Address {
+                    offset: address.offset + index as u32 * stride,
+                    ..address
+                }));
+            },
+
+            Instruction::Read(ty) => {
+                let address = self.pop_pointer(instruction, span)?;
+                let value = self.memory.read(address, ty, span)?;
+
+                self.stack.push(value);
+            },
+
+            Instruction::Write(ty) => {
+                let value = self.pop_typed(instruction, ty, span)?;
+                let address = self.pop_pointer(instruction, span)?;
+
+                self.memory.write(address, ty, value, span)?;
+            },
+
+            Instruction::Copy(size) => {
+                let source = self.pop_pointer(instruction, span)?;
+                let destination = self.pop_pointer(instruction, span)?;
+
+                self.memory.copy(destination, source, size, span)?;
+            },
+
+            Instruction::NonNull => {
+                let address = self.pop_non_null(instruction, span)?;
+
+                self.stack.push(Value::Pointer(address));
+            },
+
+            Instruction::Trap(index) => {
+                let message = match self.program.constants.get(index) {
+                    Some(Constant::String(message)) => message,
+                    _ => return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` needs a string constant", instruction))),
+                };
+
+                return Err(Error::from_span(span, ErrorType::RuntimeError, message));
+            },
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    // pushes a frame for `function`, whose `argc` arguments are on top of the stack
+    fn enter(&mut self, index: u32, argc: usize, span: Span) -> Executed<()> {
+        let function = self.function(index, span)?;
+
+        if argc != function.params.len() {
+            return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` takes {} arguments but was called with {}", function.name, function.params.len(), argc)));
+        }
+
+        if self.frames.len() >= self.max_depth {
+            let message = format!("stack overflow: calls are nested more than {} deep\nbacktrace:\n{}", self.max_depth, self.backtrace());
+
+            return Err(Error::from_span(span, ErrorType::RuntimeError, &message).with_code(codes::STACK_OVERFLOW));
+        }
+
+        let base = self.stack.len().checked_sub(argc).ok_or_else(|| underflow(Instruction::Call(index), span))?;
+        let mut locals: Vec<Option<Value>> = vec![ None; function.locals.len().max(argc) ];
+
+        for (local, (arg, ty)) in locals.iter_mut().zip(self.stack.drain(base ..).zip(&function.params)) {
+            if !arg.is(*ty) {
+                return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` expects a {} argument, found {}", function.name, ty, arg)));
+            }
+
+            *local = Some(arg);
+        }
+
+        self.frames.push(Frame {
+            function: index,
+            ip: 0,
+            locals,
+            base,
+            allocations: vec![],
+        });
+
+        Ok(())
+    }
+
+    // pops every frame left by an error, releasing the stack blocks they still hold
+    fn unwind(&mut self, span: Span) {
+        while let Some(frame) = self.frames.pop() {
+            for address in frame.allocations {
+                // a block the failing instruction already freed is not freed again
+                let _ = self.memory.free(address, BlockKind::Stack, span);
+            }
+        }
+
+        self.stack.clear();
+    }
+
+    fn leave(&mut self, result: Option<Value>, span: Span) -> Executed<Flow> {
+        let frame = self.frames.pop().unwrap();
+
+        for address in frame.allocations {
+            self.memory.free(address, BlockKind::Stack, span)?;
+        }
+
+        self.stack.truncate(frame.base);
+
+        if self.frames.is_empty() {
+            return Ok(Flow::Finish(result));
+        }
+
+        self.stack.extend(result);
+
+        Ok(Flow::Continue)
+    }
+
+    // the active calls, innermost first, with runs of the same call site collapsed
+    fn backtrace(&self) -> String {
+        let mut lines: Vec<(String, usize)> = vec![];
+
+        for frame in self.frames.iter().rev() {
+            let function = &self.program.functions[frame.function as usize];
+            let line = format!("    {} at {}", function.name, function.chunk.span_at(frame.ip.saturating_sub(1)));
+
+            match lines.last_mut() {
+                Some((last, count)) if *last == line => *count += 1,
+                _ => lines.push((line, 1)),
+            }
+        }
+
+        lines.iter().map(|(line, count)| match count {
+            1 => line.clone(),
+            _ => format!("{} ({} times)", line, count),
+        }).collect::<Vec<String>>().join("\n")
+    }
+
+    fn function(&self, index: u32, span: Span) -> Executed<&'a Function> {
+        self.program.functions.get(index as usize).ok_or_else(|| Error::from_span(span, ErrorType::InternalError, &format!("there is no function #{}", index)))
+    }
+
+    fn constant(&mut self, index: u32, span: Span) -> Executed<Value> {
+        let constant = self.program.constants.get(index).ok_or_else(|| Error::from_span(span, ErrorType::InternalError, &format!("there is no constant #{}", index)))?;
+
+        let bytes = match constant {
+            Constant::Int(v) => return Ok(Value::Int(v.value)),
+            Constant::Uint(v) => return Ok(Value::Uint(v.value)),
+            Constant::Float(v) => return Ok(Value::Float(v.value)),
+            Constant::Bool(b) => return Ok(Value::Bool(*b)),
+            Constant::Char(c) => return Ok(Value::Char(*c)),
+            Constant::String(s) => s.as_bytes().to_vec(),
+            Constant::VTable { size, align, methods } => [ *size, *align ].into_iter().chain(methods.iter().map(|method| *method as u64)).flat_map(u64::to_le_bytes).collect(),
+        };
+
+        let memory = &mut self.memory;
+        let address = *self.constant_blocks.entry(index).or_insert_with(|| memory.allocate_constant(bytes, span));
+
+        Ok(Value::Pointer(address))
+    }
+
+    fn jump(&mut self, target: u32, span: Span) -> Executed<()> {
+        let frame = self.frames.last_mut().unwrap();
+
+        if target as usize >= self.program.functions[frame.function as usize].chunk.len() {
+            return Err(Error::from_span(span, ErrorType::InternalError, &format!("jump to #{} is past the end of `{}`", target, self.program.functions[frame.function as usize].name)));
+        }
+
+        frame.ip = target as usize;
+
+        Ok(())
+    }
+
+    fn arithmetic(&mut self, instruction: Instruction, ty: NumericType, span: Span, int: fn(i128, i128) -> i128, float: fn(f64, f64) -> f64) -> Executed<()> {
+        let result = match ty {
+            NumericType::Float(fp) if !matches!(instruction, Instruction::BitAnd(_) | Instruction::BitOr(_)) => {
+                let b = self.pop_typed(instruction, ty.into(), span)?.as_f64().unwrap();
+                let a = self.pop_typed(instruction, ty.into(), span)?.as_f64().unwrap();
+
+                Value::Float(fp.round(float(a, b)))
+            },
+
+            _ => {
+                let b = self.pop_integer(instruction, ty, span)?;
+                let a = self.pop_integer(instruction, ty, span)?;
+
+                if b == 0 && matches!(instruction, Instruction::Div(_) | Instruction::Rem(_)) {
+                    return Err(Error::from_span(span, ErrorType::RuntimeError, "division by zero").with_code(codes::DIVISION_BY_ZERO));
+                }
+
+                Value::from_i128(ty, int(a, b))
+            },
+        };
+
+        self.stack.push(result);
+
+        Ok(())
+    }
+
+    fn compare(&mut self, instruction: Instruction, ty: NumericType, span: Span, test: fn(Ordering) -> bool) -> Executed<()> {
+        let b = self.pop_typed(instruction, ty.into(), span)?;
+        let a = self.pop_typed(instruction, ty.into(), span)?;
+
+        let ordering = match (a, b) {
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(&b),
+            _ => a.as_i128().partial_cmp(&b.as_i128()),
+        };
+
+        // every ordering test is false when a NaN is involved
+        self.stack.push(Value::Bool(ordering.is_some_and(test)));
+
+        Ok(())
+    }
+
+    fn pop(&mut self, instruction: Instruction, span: Span) -> Executed<Value> {
+        let base = self.frames.last().map_or(0, |frame| frame.base);
+
+        match self.stack.len() > base {
+            true => Ok(self.stack.pop().unwrap()),
+            false => Err(underflow(instruction, span)),
+        }
+    }
+
+    fn pop_typed(&mut self, instruction: Instruction, ty: ValueType, span: Span) -> Executed<Value> {
+        let value = self.pop(instruction, span)?;
+
+        match value.is(ty) {
+            true => Ok(value),
+            false => Err(mismatch(instruction, &format!("a {}", ty), value, span)),
+        }
+    }
+
+    fn pop_integer(&mut self, instruction: Instruction, ty: NumericType, span: Span) -> Executed<i128> {
+        if !ty.is_integer() {
+            return Err(Error::from_span(span, ErrorType::InternalError, &format!("`{}` needs an integer type", instruction)));
+        }
+
+        Ok(self.pop_typed(instruction, ty.into(), span)?.as_i128().unwrap())
+    }
+
+    fn pop_pointer(&mut self, instruction: Instruction, span: Span) -> Executed<Address> {
+        let Value::Pointer(address) = self.pop_typed(instruction, ValueType::Pointer, span)? else { unreachable!() };
+
+        Ok(address)
+    }
+
+    fn pop_non_null(&mut self, instruction: Instruction, span: Span) -> Executed<Address> {
+        let address = self.pop_pointer(instruction, span)?;
+
+        match address.is_null() {
+            true => Err(Error::from_span(span, ErrorType::RuntimeError, "null pointer dereference").with_code(codes::NULL_DEREFERENCE)),
+            false => Ok(address),
+        }
+    }
+}
+
+fn blocks(count: usize) -> &'static str {
+    match count {
+        1 => "block",
+        _ => "blocks",
+    }
+}
+
+fn environments(count: usize) -> &'static str {
+    match count {
+        1 => "closure environment",
+        _ => "closure environments",
+    }
+}
+
+fn underflow(instruction: Instruction, span: Span) -> Error {
+    Error::from_span(span, ErrorType::InternalError, &format!("`{}` found too few values on the operand stack", instruction))
+}
+
+fn mismatch(instruction: Instruction, expected: &str, found: Value, span: Span) -> Error {
+    Error::from_span(span, ErrorType::InternalError, &format!("`{}` expects {} operand, found {}", instruction, expected, found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ * };
+    use crate::{ types::{ * } };
+
+    const I32: NumericType = NumericType::Int(SignedIntegerTypes::Int32);
+
+    fn int(program: &mut Program, value: i64) -> Instruction {
+        Instruction::Const(program.constants.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int32, value))))
+    }
+
+    fn function(name: &'static str, params: usize, locals: usize, code: Vec<Instruction>) -> Function {
+        let mut function = Function::new(name, vec![ ValueType::from(I32); params ], Some(ValueType::from(I32)));
+
+        function.locals = (0 .. locals).map(|_| Local { name: "n", ty: ValueType::from(I32) }).collect();
+
+        for (i, instruction) in code.into_iter().enumerate() {
+            function.chunk.push(instruction, Span::new(i + 1, 1));
+        }
+
+        function
+    }
+
+    #[test]
+    fn recursive_calls_return_values() {
+        let mut program = Program::new();
+        let one = int(&mut program, 1);
+
+        // fn fact(n: i32) -> i32 { if n <= 1 { return 1; } return n * fact(n - 1); }
+        program.add_function(function("fact", 1, 1, vec![
+            Instruction::Load(0), one, Instruction::Le(I32), Instruction::JumpIfFalse(6),
+            one, Instruction::Return,
+            Instruction::Load(0), Instruction::Load(0), one, Instruction::Sub(I32), Instruction::Call(0), Instruction::Mul(I32), Instruction::Return,
+        ]));
+
+        let result = Vm::new(&program).call("fact", &[ Value::Int(10) ]).unwrap();
+
+        assert_eq!(result, Some(Value::Int(3628800)));
+    }
+
+    #[test]
+    fn deep_recursion_is_a_stack_overflow_with_a_backtrace() {
+        let mut program = Program::new();
+
+        program.add_function(function("main", 0, 0, vec![ Instruction::Call(1), Instruction::Return ]));
+        program.add_function(function("forever", 0, 0, vec![ Instruction::Call(1), Instruction::Return ]));
+
+        let error = Vm::new(&program).with_max_depth(16).call("main", &[]).unwrap_err();
+
+        assert_eq!((error.get_error_type(), error.get_code()), (ErrorType::RuntimeError, codes::STACK_OVERFLOW));
+        assert_eq!(error.get_message(), "stack overflow: calls are nested more than 16 deep\nbacktrace:\n    forever at 1:1 (15 times)\n    main at 1:1");
+    }
+
+    #[test]
+    fn arithmetic_wraps_and_memory_is_checked() {
+        let mut program = Program::new();
+        let i8 = NumericType::Int(SignedIntegerTypes::Int8);
+        let max = Instruction::Const(program.constants.add(Constant::Int(SignedInteger::new(SignedIntegerTypes::Int8, 127))));
+        let (zero, four) = (int(&mut program, 0), int(&mut program, 4));
+
+        let mut wrap = function("wrap", 0, 0, vec![ max, max, Instruction::Add(i8), Instruction::Return ]);
+
+        wrap.ret = Some(ValueType::from(i8));
+        program.add_function(wrap);
+        program.add_function(function("divide", 0, 0, vec![ four, zero, Instruction::Div(I32), Instruction::Return ]));
+
+        let mut index = function("index", 0, 1, vec![
+            Instruction::StackAlloc(16), Instruction::Store(0),
+            Instruction::Load(0), four, Instruction::Index(4), Instruction::Read(ValueType::from(I32)), Instruction::Return,
+        ]);
+
+        index.locals[0].ty = ValueType::Pointer;
+        program.add_function(index);
+
+        let mut vm = Vm::new(&program);
+
+        assert_eq!(vm.call("wrap", &[]).unwrap(), Some(Value::Int(-2)));
+        assert_eq!(vm.call("divide", &[]).unwrap_err().get_code(), codes::DIVISION_BY_ZERO);
+        assert_eq!(vm.call("index", &[]).unwrap_err().get_message(), "index 4 is out of bounds for 4 elements");
+    }
+}
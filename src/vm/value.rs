@@ -0,0 +1,113 @@
+use std::{ fmt::{ Display, Formatter, Result } };
+
+use super::{ memory::{ Address } };
+use crate::{ bytecode::{ NumericType, ValueType } };
+
+// a value on the operand stack or in a local; which instruction reads it decides how the payload is interpreted
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Pointer(Address),
+    Function(u32),
+}
+
+impl Value {
+    // `value` wrapped around to the width of an integer type, or converted to a float type
+    pub fn from_i128(ty: NumericType, value: i128) -> Self {
+        match ty {
+            NumericType::Int(ty) => {
+                let shift = 128 - ty.bits();
+
+                Self::Int(((value << shift) >> shift) as i64)
+            },
+            NumericType::Uint(ty) => Self::Uint((value & ty.max()) as u64),
+            NumericType::Float(ty) => Self::Float(ty.round(value as f64)),
+        }
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::Int(v) => Some(*v as i128),
+            Self::Uint(v) => Some(*v as i128),
+            Self::Bool(b) => Some(*b as i128),
+            Self::Char(c) => Some(*c as i128),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    // whether the payload is the kind `ty` expects
+    pub fn is(&self, ty: ValueType) -> bool {
+        matches!((self, ty),
+            (Self::Int(_), ValueType::Int(_)) | (Self::Uint(_), ValueType::Uint(_)) | (Self::Float(_), ValueType::Float(_))
+            | (Self::Bool(_), ValueType::Bool) | (Self::Char(_), ValueType::Char) | (Self::Pointer(_), ValueType::Pointer) | (Self::Function(_), ValueType::Function))
+    }
+
+    // integers wrap around to the target width and floats saturate, as in constant evaluation;
+    // a pointer converts to and from an integer holding its block in the low and its offset in the high half
+    pub fn cast(&self, to: ValueType) -> Option<Self> {
+        let value = match (*self, to) {
+            (Self::Pointer(address), ValueType::Int(ty)) => Self::from_i128(NumericType::Int(ty), address.block as i128 | (address.offset as i128) << 32),
+            (Self::Pointer(address), ValueType::Uint(ty)) => Self::from_i128(NumericType::Uint(ty), address.block as i128 | (address.offset as i128) << 32),
+            (value, ValueType::Pointer) if value.as_i128().is_some() => {
+                let bits = value.as_i128()? as u64;
+
+                Self::Pointer(Address { block: bits as u32, offset: (bits >> 32) as u32 })
+            },
+
+            (Self::Float(f), ValueType::Int(ty)) => Self::Int((f as i128).clamp(ty.min(), ty.max()) as i64),
+            (Self::Float(f), ValueType::Uint(ty)) => Self::Uint((f as i128).clamp(0, ty.max()) as u64),
+            (Self::Float(f), ValueType::Float(ty)) => Self::Float(ty.round(f)),
+            (Self::Float(f), ValueType::Bool) => Self::Bool(f != 0.0),
+
+            (value, ValueType::Int(ty)) => Self::from_i128(NumericType::Int(ty), value.as_i128()?),
+            (value, ValueType::Uint(ty)) => Self::from_i128(NumericType::Uint(ty), value.as_i128()?),
+            (value, ValueType::Float(ty)) => Self::from_i128(NumericType::Float(ty), value.as_i128()?),
+            (value, ValueType::Bool) => Self::Bool(value.as_i128()? != 0),
+            (value, ValueType::Char) => Self::Char(char::from_u32(value.as_i128()? as u32)?),
+
+            (value @ Self::Pointer(_), ValueType::Pointer) | (value @ Self::Function(_), ValueType::Function) => value,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    // whether `cast` converts some values of type `from` to `to`; of these, only casts to `char` can
+    // still fail, for values that are not code points
+    pub fn can_cast(from: ValueType, to: ValueType) -> bool {
+        let is_integral = |ty| matches!(ty, ValueType::Int(_) | ValueType::Uint(_) | ValueType::Bool | ValueType::Char);
+
+        match to {
+            ValueType::Int(_) | ValueType::Uint(_) => is_integral(from) || matches!(from, ValueType::Float(_) | ValueType::Pointer),
+            ValueType::Float(_) | ValueType::Bool => is_integral(from) || matches!(from, ValueType::Float(_)),
+            ValueType::Char => is_integral(from),
+            ValueType::Pointer => matches!(from, ValueType::Int(_) | ValueType::Uint(_) | ValueType::Pointer),
+            ValueType::Function => from == ValueType::Function,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Uint(v) => write!(f, "{}", v),
+            Self::Float(v) => write!(f, "{:?}", v),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Char(c) => write!(f, "{:?}", c),
+            Self::Pointer(address) => write!(f, "{}", address),
+            Self::Function(index) => write!(f, "fn #{}", index),
+        }
+    }
+}